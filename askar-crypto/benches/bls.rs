@@ -0,0 +1,41 @@
+use std::hint::black_box;
+
+use askar_crypto::alg::bls::MsmG1;
+use bls12_381::{G1Projective, Scalar};
+
+use criterion::Criterion;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    const COUNT: u64 = 20;
+
+    let terms: Vec<(G1Projective, Scalar)> = (0..COUNT)
+        .map(|i| {
+            (
+                G1Projective::generator() * Scalar::from(i + 7),
+                Scalar::from_bytes_wide(&[i as u8 + 1; 64]),
+            )
+        })
+        .collect();
+
+    c.bench_function("g1 naive multi-scalar mul", |b| {
+        b.iter(|| {
+            black_box(&terms)
+                .iter()
+                .fold(G1Projective::identity(), |acc, (p, s)| acc + p * s)
+        })
+    });
+
+    c.bench_function("g1 msm", |b| {
+        b.iter(|| {
+            black_box(&terms)
+                .iter()
+                .fold(MsmG1::with_capacity(terms.len()), |msm, (p, s)| {
+                    msm.push(*p, s)
+                })
+                .sum()
+        })
+    });
+}
+
+criterion::criterion_group!(benches, criterion_benchmark);
+criterion::criterion_main!(benches);
@@ -2,6 +2,7 @@ use std::hint::black_box;
 
 use askar_crypto::kdf::concat::{ConcatKDF, ConcatKDFParams};
 use sha2::Sha256;
+use zeroize::Zeroize;
 
 use criterion::Criterion;
 
@@ -22,6 +23,7 @@ fn criterion_benchmark(c: &mut Criterion) {
                 let mut output = [0u8; 32];
                 ConcatKDF::<Sha256>::derive_key(black_box(message), black_box(params), &mut output)
                     .unwrap();
+                output.zeroize();
             })
         });
     }
@@ -10,7 +10,6 @@ use cipher::{
 };
 use digest::{crypto_common::BlockSizeUser, Digest};
 use hmac::{Mac, SimpleHmac};
-use subtle::ConstantTimeEq;
 
 use super::{AesKey, AesType, NonceSize, TagSize};
 use crate::{
@@ -22,6 +21,7 @@ use crate::{
         typenum::{consts, Unsigned},
         GenericArray,
     },
+    util::ct_eq_slices,
 };
 
 /// 128 bit AES-CBC with SHA-256 HMAC
@@ -147,7 +147,7 @@ where
         hmac.update(&buffer.as_ref()[..ctext_end]);
         hmac.update(&((aad.len() as u64) * 8).to_be_bytes());
         let mac = hmac.finalize().into_bytes();
-        let tag_match = tag.as_ref().ct_eq(&mac[..TagSize::<Self>::USIZE]);
+        let tag_match = ct_eq_slices(tag.as_ref(), &mac[..TagSize::<Self>::USIZE]);
 
         let enc_key = GenericArray::from_slice(&self.0[C::KeySize::USIZE..]);
         let dec_len = <CbcDec<C> as KeyIvInit>::new(enc_key, GenericArray::from_slice(nonce))
@@ -6,7 +6,6 @@ use aes_core::{
     cipher::{BlockCipher, BlockDecrypt, BlockEncrypt, KeyInit, KeySizeUser},
     Aes128, Aes256,
 };
-use subtle::ConstantTimeEq;
 
 use super::{AesKey, AesType, NonceSize, TagSize};
 use crate::{
@@ -18,6 +17,7 @@ use crate::{
         typenum::{consts, Unsigned},
         GenericArray,
     },
+    util::ct_eq_slices,
 };
 
 const AES_KW_DEFAULT_IV: [u8; 8] = [166, 166, 166, 166, 166, 166, 166, 166];
@@ -148,7 +148,7 @@ where
             }
         }
 
-        if iv.ct_eq(&AES_KW_DEFAULT_IV).unwrap_u8() == 1 {
+        if ct_eq_slices(&iv, &AES_KW_DEFAULT_IV).into() {
             Ok(())
         } else {
             Err(err_msg!(Encryption))
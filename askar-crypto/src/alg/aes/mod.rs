@@ -8,6 +8,11 @@ use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 use super::{AesTypes, HasKeyAlg, HasKeyBackend, KeyAlg};
+#[cfg(feature = "cose")]
+use crate::{
+    buffer::WriteBuffer,
+    cose::{symmetric_alg, CoseKeyParts, FromCoseKey, ToCoseKey, KTY_SYMMETRIC},
+};
 use crate::{
     buffer::{ArrayKey, ResizeBuffer, Writer},
     encrypt::{KeyAeadInPlace, KeyAeadMeta, KeyAeadParams},
@@ -154,6 +159,36 @@ impl<T: AesType> ToJwk for AesKey<T> {
     }
 }
 
+#[cfg(feature = "cose")]
+impl<T: AesType> ToCoseKey for AesKey<T> {
+    fn write_cose_key(&self, secret: bool, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        if !secret {
+            return Err(err_msg!(Unsupported, "Cannot export as a public key"));
+        }
+        CoseKeyParts {
+            kty: KTY_SYMMETRIC,
+            alg: symmetric_alg(self.algorithm()),
+            k: Some(self.0.as_ref()),
+            ..Default::default()
+        }
+        .write(out)
+    }
+}
+
+#[cfg(feature = "cose")]
+impl<T: AesType> FromCoseKey for AesKey<T> {
+    fn from_cose_key_parts(cose_key: CoseKeyParts<'_>) -> Result<Self, Error> {
+        if cose_key.kty != KTY_SYMMETRIC {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key type"));
+        }
+        if cose_key.alg.is_some() && cose_key.alg != symmetric_alg(KeyAlg::Aes(T::ALG_TYPE)) {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key algorithm"));
+        }
+        let k = cose_key.k.ok_or_else(|| err_msg!(InvalidKeyData))?;
+        Self::from_secret_bytes(k)
+    }
+}
+
 // for direct key agreement (not used currently)
 impl<Lhs, Rhs, T> FromKeyExchange<Lhs, Rhs> for AesKey<T>
 where
@@ -298,6 +333,33 @@ mod tests {
         test_encrypt::<A256CbcHs512>();
     }
 
+    #[test]
+    fn encrypt_gcm_with_aad_expected() {
+        // from the GCM specification (McGrew and Viega), test case 4
+        let key = AesKey::<A128Gcm>::from_secret_bytes(&hex!("feffe9928665731c6d6a8f9467308308"))
+            .unwrap();
+        let nonce = hex!("cafebabefacedbaddecaf888");
+        let aad = hex!("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let input = hex!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72"
+            "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39"
+        );
+        let expected = hex!(
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e"
+            "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091"
+            "5bc94fbc3221a5db94fae95ae7121a47"
+        );
+        let mut buffer = SecretBytes::from_slice(&input);
+        key.encrypt_in_place(&mut buffer, &nonce, &aad).unwrap();
+        assert_eq!(&buffer[..], &expected[..]);
+
+        // the associated data must match for decryption to succeed
+        let mut check = buffer.clone();
+        assert!(key.decrypt_in_place(&mut check, &nonce, &[]).is_err());
+        key.decrypt_in_place(&mut buffer, &nonce, &aad).unwrap();
+        assert_eq!(&buffer[..], &input[..]);
+    }
+
     #[test]
     fn test_random() {
         let key = AesKey::<A128CbcHs256>::random().unwrap();
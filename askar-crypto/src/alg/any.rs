@@ -48,6 +48,7 @@ use crate::{
     random::KeyMaterial,
     repr::{KeyGen, KeyPublicBytes, KeySecretBytes, ToPublicBytes, ToSecretBytes},
     sign::{KeySigVerify, KeySign, SignatureType},
+    util::ct_eq_slices,
 };
 
 #[cfg(any(feature = "k256", feature = "p256", feature = "p384",))]
@@ -84,10 +85,51 @@ impl AnyKey {
     pub fn key_type_id(&self) -> TypeId {
         self.0.as_any().type_id()
     }
+
+    /// Compare the key material of two keys in constant time, without
+    /// exposing the key bytes
+    ///
+    /// When both keys have a secret key, the secret keys are compared.
+    /// Otherwise the public keys are compared. Keys of different algorithms
+    /// are never equal.
+    pub fn secret_eq(&self, other: &AnyKey) -> bool {
+        if self.algorithm() != other.algorithm() {
+            return false;
+        }
+        match (self.to_secret_bytes(), other.to_secret_bytes()) {
+            (Ok(sk), Ok(other_sk)) => ct_eq_slices(sk.as_ref(), other_sk.as_ref()).into(),
+            _ => self.public_eq(other),
+        }
+    }
+
+    /// Compare the public keys of two keys in constant time. Keys without a
+    /// public key, or of different algorithms, are never equal.
+    pub fn public_eq(&self, other: &AnyKey) -> bool {
+        if self.algorithm() != other.algorithm() {
+            return false;
+        }
+        match (self.to_public_bytes(), other.to_public_bytes()) {
+            (Ok(pk), Ok(other_pk)) => ct_eq_slices(pk.as_ref(), other_pk.as_ref()).into(),
+            _ => false,
+        }
+    }
+}
+
+impl HasKeyAlg for AnyKey {
+    fn algorithm(&self) -> KeyAlg {
+        self.0.algorithm()
+    }
 }
 
 /// Create `AnyKey` instances from various sources
 pub trait AnyKeyCreate: Sized {
+    /// Determine whether a key algorithm is supported by the enabled crate
+    /// features. Key creation for an unsupported algorithm returns an
+    /// `Unsupported` error.
+    fn supports(alg: KeyAlg) -> bool {
+        supports_alg_any(alg)
+    }
+
     /// Generate a new key from a key material generator for the given key algorithm.
     fn generate_with_rng(alg: KeyAlg, rng: impl KeyMaterial) -> Result<Self, Error>;
 
@@ -123,6 +165,12 @@ pub trait AnyKeyCreate: Sized {
     fn from_key_derivation(alg: KeyAlg, derive: impl KeyDerivation) -> Result<Self, Error>;
 
     /// Derive the corresponding key for the provided key algorithm
+    ///
+    /// Supported conversions are Ed25519 to X25519, and between BLS12-381 G1
+    /// and G2 keys. Converting an X25519 key to Ed25519 is not possible and
+    /// returns an `Unsupported` error, as are any other conversions.
+    /// Conversion between BLS12-381 G1 and G2 keys requires the secret key,
+    /// and returns an `InvalidKeyData` error for a public key.
     fn convert_key(&self, alg: KeyAlg) -> Result<Self, Error>;
 }
 
@@ -204,6 +252,27 @@ impl AnyKeyCreate for Arc<AnyKey> {
     }
 }
 
+fn supports_alg_any(alg: KeyAlg) -> bool {
+    match alg {
+        #[cfg(feature = "aes")]
+        KeyAlg::Aes(_) => true,
+        #[cfg(feature = "bls")]
+        KeyAlg::Bls12_381(_) => true,
+        #[cfg(feature = "chacha")]
+        KeyAlg::Chacha20(_) => true,
+        #[cfg(feature = "ed25519")]
+        KeyAlg::Ed25519 | KeyAlg::X25519 => true,
+        #[cfg(feature = "k256")]
+        KeyAlg::EcCurve(EcCurves::Secp256k1) => true,
+        #[cfg(feature = "p256")]
+        KeyAlg::EcCurve(EcCurves::Secp256r1) => true,
+        #[cfg(feature = "p384")]
+        KeyAlg::EcCurve(EcCurves::Secp384r1) => true,
+        #[allow(unreachable_patterns)]
+        _ => false,
+    }
+}
+
 #[inline]
 fn generate_any_with_rng<R: AllocKey>(alg: KeyAlg, rng: impl KeyMaterial) -> Result<R, Error> {
     match alg {
@@ -491,6 +560,10 @@ fn convert_key_any<R: AllocKey>(key: &AnyKey, alg: KeyAlg) -> Result<R, Error> {
             key.assume::<Ed25519KeyPair>(),
         )
         .map(R::alloc_key)?),
+        (KeyAlg::X25519, KeyAlg::Ed25519) => Err(err_msg!(
+            Unsupported,
+            "An X25519 key cannot be converted to Ed25519"
+        )),
         #[allow(unreachable_patterns)]
         _ => Err(err_msg!(
             Unsupported,
@@ -710,6 +783,12 @@ impl ToSecretBytes for AnyKey {
         self.key_to_secret()?.secret_bytes_length()
     }
 
+    fn has_secret(&self) -> bool {
+        self.key_to_secret()
+            .map(|key| key.has_secret())
+            .unwrap_or(false)
+    }
+
     fn write_secret_bytes(&self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
         self.key_to_secret()?.write_secret_bytes(out)
     }
@@ -717,8 +796,13 @@ impl ToSecretBytes for AnyKey {
 
 impl KeyExchange for AnyKey {
     fn write_key_exchange(&self, other: &AnyKey, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        // the concrete key types must match, so that keys on different curves
+        // are never combined
         if self.key_type_id() != other.key_type_id() {
-            return Err(err_msg!(Unsupported, "Unsupported key exchange"));
+            return Err(err_msg!(
+                Unsupported,
+                "Key exchange requires keys of the same algorithm"
+            ));
         }
         match self.algorithm() {
             #[cfg(feature = "ed25519")]
@@ -887,6 +971,98 @@ mod tests {
         let _ = key.to_jwk_public(None).unwrap();
     }
 
+    #[test]
+    fn supports_any() {
+        use crate::alg::{AesTypes, BlsCurves, Chacha20Types, EcCurves};
+
+        let algs = [
+            KeyAlg::Aes(AesTypes::A256Gcm),
+            KeyAlg::Bls12_381(BlsCurves::G1),
+            KeyAlg::Chacha20(Chacha20Types::XC20P),
+            KeyAlg::Ed25519,
+            KeyAlg::X25519,
+            KeyAlg::EcCurve(EcCurves::Secp256k1),
+            KeyAlg::EcCurve(EcCurves::Secp256r1),
+            KeyAlg::EcCurve(EcCurves::Secp384r1),
+        ];
+        for alg in algs {
+            let supported = Box::<AnyKey>::supports(alg);
+            let created = Box::<AnyKey>::random_det(alg, &[1u8; 32]);
+            assert_eq!(supported, created.is_ok(), "{:?}", alg);
+            if !supported {
+                assert_eq!(created.unwrap_err().kind(), crate::ErrorKind::Unsupported);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "ed25519", feature = "p256"))]
+    #[test]
+    fn key_lengths_any() {
+        for alg in [KeyAlg::Ed25519, KeyAlg::EcCurve(EcCurves::Secp256r1)] {
+            let key = Box::<AnyKey>::random(alg).unwrap();
+            assert_eq!(
+                key.secret_key_length(),
+                Some(key.to_secret_bytes().unwrap().len())
+            );
+            assert_eq!(
+                key.public_key_length(),
+                Some(key.to_public_bytes().unwrap().len())
+            );
+            assert_eq!(
+                key.signature_length(),
+                Some(key.create_signature(b"test", None).unwrap().len())
+            );
+        }
+        assert_eq!(KeyAlg::X25519.signature_length(), None);
+        let aes = KeyAlg::Aes(crate::alg::AesTypes::A256Gcm);
+        assert_eq!(aes.public_key_length(), None);
+    }
+
+    #[cfg(all(feature = "ed25519", feature = "chacha"))]
+    #[test]
+    fn key_secret_eq_any() {
+        let key = Box::<AnyKey>::random(KeyAlg::Ed25519).unwrap();
+        let sk = key.to_secret_bytes().unwrap();
+        let copy = Box::<AnyKey>::from_secret_bytes(KeyAlg::Ed25519, &sk).unwrap();
+        let other = Box::<AnyKey>::random(KeyAlg::Ed25519).unwrap();
+        assert!(key.secret_eq(&copy));
+        assert!(key.public_eq(&copy));
+        assert!(!key.secret_eq(&other));
+        assert!(!key.public_eq(&other));
+
+        // falls back to public key comparison
+        let pk = key.to_public_bytes().unwrap();
+        let public = Box::<AnyKey>::from_public_bytes(KeyAlg::Ed25519, &pk).unwrap();
+        assert!(key.secret_eq(&public));
+        assert!(!other.secret_eq(&public));
+
+        // the same bytes under a different algorithm are not equal
+        let c20p = KeyAlg::Chacha20(Chacha20Types::C20P);
+        let xc20p = KeyAlg::Chacha20(Chacha20Types::XC20P);
+        let sym = Box::<AnyKey>::from_secret_bytes(c20p, &sk).unwrap();
+        assert!(sym.secret_eq(&Box::<AnyKey>::from_secret_bytes(c20p, &sk).unwrap()));
+        assert!(!sym.secret_eq(&Box::<AnyKey>::from_secret_bytes(xc20p, &sk).unwrap()));
+        assert!(!sym.public_eq(&sym));
+    }
+
+    #[cfg(all(feature = "ed25519", feature = "p256", feature = "chacha"))]
+    #[test]
+    fn has_secret_any() {
+        for alg in [
+            KeyAlg::Ed25519,
+            KeyAlg::X25519,
+            KeyAlg::EcCurve(EcCurves::Secp256r1),
+        ] {
+            let key = Box::<AnyKey>::random(alg).unwrap();
+            assert!(key.has_secret());
+            let pk = key.to_public_bytes().unwrap();
+            let public = Box::<AnyKey>::from_public_bytes(alg, &pk).unwrap();
+            assert!(!public.has_secret());
+        }
+        let sym = Box::<AnyKey>::random(KeyAlg::Chacha20(Chacha20Types::C20P)).unwrap();
+        assert!(sym.has_secret());
+    }
+
     #[cfg(feature = "aes")]
     #[test]
     fn key_exchange_any() {
@@ -901,6 +1077,23 @@ mod tests {
                 .unwrap();
     }
 
+    #[cfg(all(feature = "p256", feature = "p384", feature = "ed25519"))]
+    #[test]
+    fn key_exchange_mismatch_any() {
+        let p256 = Box::<AnyKey>::random(KeyAlg::EcCurve(EcCurves::Secp256r1)).unwrap();
+        let p384 = Box::<AnyKey>::random(KeyAlg::EcCurve(EcCurves::Secp384r1)).unwrap();
+        let x25519 = Box::<AnyKey>::random(KeyAlg::X25519).unwrap();
+        for (a, b) in [(&p256, &p384), (&p384, &p256), (&p256, &x25519)] {
+            let err = a.key_exchange_bytes(b).unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::Unsupported);
+        }
+
+        // keys of the same type without key exchange support are also rejected
+        let ed25519 = Box::<AnyKey>::random(KeyAlg::Ed25519).unwrap();
+        let err = ed25519.key_exchange_bytes(&ed25519).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unsupported);
+    }
+
     #[cfg(feature = "chacha")]
     #[test]
     fn key_encrypt_any() {
@@ -1,14 +1,17 @@
 //! BLS12-381 key support
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug, Formatter},
-    ops::Add,
+    ops::{Add, AddAssign},
 };
 
 use aead::generic_array::GenericArray;
 use blake2::Digest;
 use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
 use sha2::Sha256;
+use subtle::{Choice, ConstantTimeEq};
 use zeroize::{Zeroize, Zeroizing};
 
 use crate::generic_array::{
@@ -40,8 +43,14 @@ pub struct BlsKeyPair<Pk: BlsPublicKeyType> {
 impl<Pk: BlsPublicKeyType> BlsKeyPair<Pk> {
     /// Generate a new BLS key from a seed according to the KeyGen algorithm
     pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        Self::from_seed_with_version(seed, BlsKeyGenVersion::default())
+    }
+
+    /// Generate a new BLS key from a seed according to a specific version
+    /// of the KeyGen algorithm
+    pub fn from_seed_with_version(seed: &[u8], version: BlsKeyGenVersion) -> Result<Self, Error> {
         Ok(Self::from_secret_key(BlsSecretKey::generate(
-            BlsKeyGen::new(seed)?,
+            BlsKeyGen::new_with_version(seed, version)?,
         )?))
     }
 
@@ -75,9 +84,22 @@ impl<Pk: BlsPublicKeyType> Debug for BlsKeyPair<Pk> {
     }
 }
 
+/// Key pairs are compared in constant time, except that the timing may
+/// reveal whether each key pair has a secret key.
+impl<Pk: BlsPublicKeyType> ConstantTimeEq for BlsKeyPair<Pk> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        let secret_eq = match (self.secret.as_ref(), other.secret.as_ref()) {
+            (Some(sk), Some(other_sk)) => sk.ct_eq(other_sk),
+            (None, None) => Choice::from(1),
+            _ => Choice::from(0),
+        };
+        secret_eq & self.public.ct_eq(&other.public)
+    }
+}
+
 impl<Pk: BlsPublicKeyType> PartialEq for BlsKeyPair<Pk> {
     fn eq(&self, other: &Self) -> bool {
-        other.secret == self.secret && other.public == self.public
+        self.ct_eq(other).into()
     }
 }
 
@@ -123,8 +145,7 @@ impl<Pk: BlsPublicKeyType> KeySecretBytes for BlsKeyPair<Pk> {
 
     fn with_secret_bytes<O>(&self, f: impl FnOnce(Option<&[u8]>) -> O) -> O {
         if let Some(sk) = self.secret.as_ref() {
-            let mut skb = Zeroizing::new(sk.0.to_bytes());
-            skb.reverse(); // into big-endian
+            let skb = Zeroizing::new(scalar_to_be_bytes(&sk.0));
             f(Some(&*skb))
         } else {
             f(None)
@@ -148,8 +169,25 @@ where
     }
 }
 
+/// BLS keys are encoded as JWKs with the `EC` key type and uncompressed
+/// `x` and `y` coordinates by default. When the encoder requests the
+/// compressed form (outside of thumbprints), the older `OKP` representation
+/// is produced instead, with the compressed public key as `x` and a
+/// big-endian secret key `d`, for compatibility with previous versions.
 impl<Pk: BlsPublicKeyType> ToJwk for BlsKeyPair<Pk> {
     fn encode_jwk(&self, enc: &mut dyn JwkEncoder) -> Result<(), Error> {
+        if enc.compressed() && !enc.is_thumbprint() {
+            enc.add_str("crv", Pk::JWK_CURVE_OKP)?;
+            enc.add_str("kty", JWK_KEY_TYPE_OKP)?;
+            Pk::with_bytes(&self.public, enc.alg(), |buf| enc.add_as_base64("x", buf))?;
+            if enc.is_secret() {
+                self.with_secret_bytes(|buf| match buf {
+                    Some(sk) => enc.add_as_base64("d", sk),
+                    None => Ok(()),
+                })?;
+            }
+            return Ok(());
+        }
         enc.add_str("crv", Pk::get_jwk_curve(enc.alg()))?;
         enc.add_str("kty", JWK_KEY_TYPE_EC)?;
         Pk::with_bytes_uncompressed(&self.public, enc.alg(), |buf| {
@@ -157,16 +195,11 @@ impl<Pk: BlsPublicKeyType> ToJwk for BlsKeyPair<Pk> {
             enc.add_as_base64("y", &buf[Pk::BufferSize::USIZE..])
         })?;
         if enc.is_secret() {
-            self.with_secret_bytes(|buf| {
-                if let Some(sk) = buf {
-                    let mut skr = Zeroizing::new([0u8; 32]);
-                    skr.copy_from_slice(sk);
-                    skr.reverse(); // into little-endian
-                    enc.add_as_base64("d", skr.as_ref())
-                } else {
-                    Ok(())
-                }
-            })?;
+            if let Some(sk) = self.secret.as_ref() {
+                // the secret scalar is little-endian encoded
+                let skb = Zeroizing::new(sk.0.to_bytes());
+                enc.add_as_base64("d", skb.as_ref())?;
+            }
         }
         Ok(())
     }
@@ -215,10 +248,13 @@ impl<Pk: BlsPublicKeyType> FromJwk for BlsKeyPair<Pk> {
                 if jwk.d.decode_base64(sk_arr)? != sk_arr.len() {
                     Err(err_msg!(InvalidKeyData, "Invalid private key"))
                 } else {
-                    if jwk.kty == JWK_KEY_TYPE_EC {
-                        sk_arr.reverse(); // into big-endian
+                    let sk = if jwk.kty == JWK_KEY_TYPE_EC {
+                        scalar_from_le_bytes(sk_arr)
+                    } else {
+                        scalar_from_be_bytes(sk_arr)
                     }
-                    let result = BlsKeyPair::from_secret_key(BlsSecretKey::from_bytes(sk_arr)?);
+                    .map_err(|_| err_msg!(InvalidKeyData, "Invalid private key"))?;
+                    let result = BlsKeyPair::from_secret_key(BlsSecretKey(sk));
                     if result.public != public {
                         return Err(err_msg!(InvalidKeyData, "Public key mismatch"));
                     }
@@ -234,10 +270,24 @@ impl<Pk: BlsPublicKeyType> FromJwk for BlsKeyPair<Pk> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Zeroize)]
+#[derive(Clone, Debug, Zeroize)]
 #[repr(transparent)]
 pub(crate) struct BlsSecretKey(Scalar);
 
+impl ConstantTimeEq for BlsSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for BlsSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for BlsSecretKey {}
+
 impl BlsSecretKey {
     fn generate(mut rng: impl KeyMaterial) -> Result<Self, Error> {
         let mut secret = Zeroizing::new([0u8; 64]);
@@ -247,38 +297,94 @@ impl BlsSecretKey {
     }
 
     pub fn from_bytes(sk: &[u8]) -> Result<Self, Error> {
-        if sk.len() != 32 {
-            return Err(err_msg!(InvalidKeyData));
-        }
-        let mut skb = Zeroizing::new([0u8; 32]);
-        skb.copy_from_slice(sk);
-        skb.reverse(); // into little endian
-        let result: Option<Scalar> = Scalar::from_bytes(&skb).into();
-        Ok(Self(result.ok_or_else(|| err_msg!(InvalidKeyData))?))
+        Ok(Self(scalar_from_be_bytes(sk)?))
     }
 }
 
+/// Decode a scalar from its canonical 32-byte big-endian encoding,
+/// rejecting values which are not less than the group order
+pub fn scalar_from_be_bytes(bytes: &[u8]) -> Result<Scalar, Error> {
+    let mut buf =
+        Zeroizing::new(<[u8; 32]>::try_from(bytes).map_err(|_| err_msg!(InvalidKeyData))?);
+    buf.reverse();
+    scalar_from_le_bytes(&buf[..])
+}
+
+/// Decode a scalar from its canonical 32-byte little-endian encoding,
+/// rejecting values which are not less than the group order
+pub fn scalar_from_le_bytes(bytes: &[u8]) -> Result<Scalar, Error> {
+    let buf: &[u8; 32] = bytes.try_into().map_err(|_| err_msg!(InvalidKeyData))?;
+    Option::from(Scalar::from_bytes(buf)).ok_or_else(|| err_msg!(InvalidKeyData, "Invalid scalar"))
+}
+
+/// Encode a scalar in its canonical 32-byte big-endian form
+pub fn scalar_to_be_bytes(scalar: &Scalar) -> [u8; 32] {
+    let mut buf = scalar.to_bytes();
+    buf.reverse();
+    buf
+}
+
 impl Drop for BlsSecretKey {
     fn drop(&mut self) {
         self.zeroize();
     }
 }
 
+/// Supported versions of the KeyGen algorithm from the bls-signatures RFC
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlsKeyGenVersion {
+    /// KeyGen as defined by the original EIP-2333, which predates draft 4.
+    /// The salt is used directly as the HKDF salt, the seed is used as the
+    /// input key material without a suffix, and the info parameter is empty.
+    Draft2,
+    /// KeyGen from draft 4, as used by the updated EIP-2333. The salt is
+    /// hashed before each attempt.
+    #[default]
+    Draft4,
+}
+
 /// A key material generator compatible with KeyGen from the
-/// bls-signatures RFC draft 4 (incompatible with earlier)
+/// bls-signatures RFC. Draft 4 is used by default, which produces
+/// different keys than earlier drafts.
 #[derive(Debug, Clone)]
 pub struct BlsKeyGen<'g> {
     salt: Option<GenericArray<u8, U32>>,
     ikm: &'g [u8],
+    key_info: &'g [u8],
+    version: BlsKeyGenVersion,
 }
 
 impl<'g> BlsKeyGen<'g> {
     /// Construct a new `BlsKeyGen` from a seed value
     pub fn new(ikm: &'g [u8]) -> Result<Self, Error> {
+        Self::new_with_version(ikm, BlsKeyGenVersion::default())
+    }
+
+    /// Construct a new `BlsKeyGen` from a seed value, using a specific version
+    /// of the KeyGen algorithm
+    pub fn new_with_version(ikm: &'g [u8], version: BlsKeyGenVersion) -> Result<Self, Error> {
         if ikm.len() < 32 {
             return Err(err_msg!(Usage, "Insufficient length for seed"));
         }
-        Ok(Self { salt: None, ikm })
+        Ok(Self {
+            salt: None,
+            ikm,
+            key_info: &[],
+            version,
+        })
+    }
+
+    /// Construct a new `BlsKeyGen` from a seed value and a `key_info`
+    /// parameter, which may be used to derive multiple independent keys
+    /// from the same seed
+    pub fn new_with_info(ikm: &'g [u8], key_info: &'g [u8]) -> Result<Self, Error> {
+        if key_info.len() > u16::MAX as usize {
+            return Err(err_msg!(Usage, "Exceeded maximum length for key_info"));
+        }
+        Ok(Self {
+            key_info,
+            ..Self::new(ikm)?
+        })
     }
 }
 
@@ -286,15 +392,26 @@ impl KeyMaterial for BlsKeyGen<'_> {
     fn read_okm(&mut self, buf: &mut [u8]) {
         const SALT: &[u8] = b"BLS-SIG-KEYGEN-SALT-";
 
-        self.salt.replace(match self.salt {
-            None => Sha256::digest(SALT),
-            Some(salt) => Sha256::digest(salt),
-        });
-        let mut extract = hkdf::HkdfExtract::<Sha256>::new(Some(self.salt.as_ref().unwrap()));
+        let mut extract = match self.version {
+            BlsKeyGenVersion::Draft2 => hkdf::HkdfExtract::<Sha256>::new(Some(SALT)),
+            BlsKeyGenVersion::Draft4 => {
+                self.salt.replace(match self.salt {
+                    None => Sha256::digest(SALT),
+                    Some(salt) => Sha256::digest(salt),
+                });
+                hkdf::HkdfExtract::<Sha256>::new(Some(self.salt.as_ref().unwrap()))
+            }
+        };
         extract.input_ikm(self.ikm);
+        if self.version == BlsKeyGenVersion::Draft2 {
+            let (_, hkdf) = extract.finalize();
+            hkdf.expand(self.key_info, buf)
+                .expect("HDKF extract failure");
+            return;
+        }
         extract.input_ikm(&[0u8]);
         let (_, hkdf) = extract.finalize();
-        hkdf.expand(&(buf.len() as u16).to_be_bytes(), buf)
+        hkdf.expand_multi_info(&[self.key_info, &(buf.len() as u16).to_be_bytes()], buf)
             .expect("HDKF extract failure");
     }
 }
@@ -302,7 +419,7 @@ impl KeyMaterial for BlsKeyGen<'_> {
 /// Trait implemented by supported BLS public key types
 pub trait BlsPublicKeyType: 'static {
     /// The concrete key representation
-    type Buffer: Clone + Debug + PartialEq + Sized + Zeroize;
+    type Buffer: Clone + ConstantTimeEq + Debug + PartialEq + Sized + Zeroize;
 
     /// The size of the serialized compressed public key
     type BufferSize: ArrayLength<u8>;
@@ -324,7 +441,10 @@ pub trait BlsPublicKeyType: 'static {
     /// Initialize from the secret scalar
     fn from_secret_scalar(secret: &Scalar) -> Self::Buffer;
 
-    /// Initialize from the compressed bytes
+    /// Initialize from the compressed or uncompressed bytes
+    ///
+    /// Points which are not on the curve or not in the prime-order subgroup
+    /// are rejected.
     fn from_public_bytes(key: &[u8]) -> Result<Self::Buffer, Error>;
 
     /// Access the bytes of the public key
@@ -452,6 +572,153 @@ impl TryFrom<&BlsKeyPair<G2>> for BlsKeyPair<G1> {
     }
 }
 
+/// Trait implemented by BLS12-381 group types supporting multi-scalar
+/// multiplication
+pub trait BlsGroup: Copy + Debug + Default + PartialEq + AddAssign + Add<Output = Self> {
+    /// Get the identity element of the group
+    fn identity() -> Self;
+
+    /// Double this group element
+    fn double(&self) -> Self;
+}
+
+impl BlsGroup for G1Projective {
+    #[inline]
+    fn identity() -> Self {
+        G1Projective::identity()
+    }
+
+    #[inline]
+    fn double(&self) -> Self {
+        G1Projective::double(self)
+    }
+}
+
+impl BlsGroup for G2Projective {
+    #[inline]
+    fn identity() -> Self {
+        G2Projective::identity()
+    }
+
+    #[inline]
+    fn double(&self) -> Self {
+        G2Projective::double(self)
+    }
+}
+
+/// Multi-scalar multiplication over BLS12-381 G1
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub type MsmG1 = Msm<G1Projective>;
+
+/// Multi-scalar multiplication over BLS12-381 G2
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub type MsmG2 = Msm<G2Projective>;
+
+/// An accumulator for the multi-scalar multiplication of a sequence of
+/// group elements, using the bucket method of Pippenger.
+///
+/// The computation is not constant-time with respect to the scalars, and
+/// should only be applied to secret scalars when this is acceptable.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Debug)]
+pub struct Msm<G: BlsGroup> {
+    points: Vec<G>,
+    scalars: Vec<[u8; 32]>,
+}
+
+#[cfg(feature = "alloc")]
+impl<G: BlsGroup> Msm<G> {
+    /// Create a new, empty accumulator
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new accumulator with capacity for a number of terms
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            points: Vec::with_capacity(capacity),
+            scalars: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Add a term to the accumulator
+    pub fn push(mut self, point: impl Into<G>, scalar: &Scalar) -> Self {
+        self.append(point, scalar);
+        self
+    }
+
+    /// Add a term to the accumulator in place
+    pub fn append(&mut self, point: impl Into<G>, scalar: &Scalar) {
+        self.points.push(point.into());
+        self.scalars.push(scalar.to_bytes());
+    }
+
+    /// Get the number of terms in the accumulator
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Check whether the accumulator has no terms
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Calculate the sum of each point multiplied by its scalar
+    pub fn sum(&self) -> G {
+        let count = self.points.len();
+        // the window size approximates ln(count) + 2, which minimizes the
+        // number of group operations for the bucket method
+        let window = if count < 32 {
+            3
+        } else {
+            (usize::BITS - count.leading_zeros()) as usize * 69 / 100 + 2
+        };
+        let mut buckets = Vec::with_capacity((1 << window) - 1);
+        let mut acc = G::identity();
+        for start in (0..256).step_by(window).rev() {
+            for _ in 0..window {
+                acc = acc.double();
+            }
+            buckets.clear();
+            buckets.resize((1 << window) - 1, G::identity());
+            for (point, scalar) in self.points.iter().zip(&self.scalars) {
+                let digit = scalar_window(scalar, start, window);
+                if digit > 0 {
+                    buckets[digit - 1] += *point;
+                }
+            }
+            let mut running = G::identity();
+            let mut window_sum = G::identity();
+            for bucket in buckets.iter().rev() {
+                running += *bucket;
+                window_sum += running;
+            }
+            acc += window_sum;
+        }
+        acc
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<G: BlsGroup> Default for Msm<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extract a window of bits from a little-endian scalar
+#[cfg(feature = "alloc")]
+fn scalar_window(scalar: &[u8; 32], start: usize, len: usize) -> usize {
+    let mut digit = 0;
+    for (idx, bit) in (start..(start + len).min(256)).enumerate() {
+        digit |= (((scalar[bit / 8] >> (bit % 8)) & 1) as usize) << idx;
+    }
+    digit
+}
+
 #[cfg(test)]
 mod tests {
     use base64::Engine;
@@ -475,6 +742,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn key_gen_info_expected() {
+        let seed = &hex!(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553
+            1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+        // no published vector covers a non-empty key_info: this is a
+        // regression value produced by this implementation
+        let sk =
+            BlsSecretKey::generate(BlsKeyGen::new_with_info(seed, b"key info").unwrap()).unwrap();
+        assert_eq!(
+            scalar_to_be_bytes(&sk.0),
+            hex!("7339cad83f55e6c49f5aef59082bfb40e1ae96dc56cb6207255af74422dd6665")
+        );
+
+        // an empty key_info matches the default
+        let sk = BlsSecretKey::generate(BlsKeyGen::new_with_info(seed, &[]).unwrap()).unwrap();
+        assert_eq!(
+            scalar_to_be_bytes(&sk.0),
+            hex!("0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070")
+        );
+    }
+
+    // test case 0 from EIP-2333 as originally published, before the update
+    // to KeyGen from draft 4 of the bls-signatures RFC
+    #[test]
+    fn key_gen_draft2_expected() {
+        let seed = &hex!(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553
+            1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+        let kp =
+            BlsKeyPair::<G1>::from_seed_with_version(&seed[..], BlsKeyGenVersion::Draft2).unwrap();
+        let sk = kp.to_secret_bytes().unwrap();
+        assert_eq!(
+            sk.as_hex().to_string(),
+            "1baa85baae3855480656e3e77b8e42c6f4be751a2d089e2caab293c39befaa41"
+        );
+    }
+
     #[test]
     fn g1_key_expected() {
         let sk = hex!("0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070");
@@ -612,6 +919,27 @@ mod tests {
         let g2_expect =
             BlsKeyPair::<G2>::from_jwk(test_jwk_g2).expect("Error decoding BLS key JWK");
         assert_eq!(&g2_expect, as_g2);
+
+        let g1key = g2key
+            .convert_key(KeyAlg::Bls12_381(BlsCurves::G1))
+            .expect("Error converting keypair");
+        assert_eq!(g1key.algorithm(), KeyAlg::Bls12_381(BlsCurves::G1));
+        assert_eq!(
+            g1key.downcast_ref::<BlsKeyPair<G1>>(),
+            Some(as_g1),
+            "G2 to G1 conversion mismatch"
+        );
+
+        // conversion requires the secret key
+        let public_only = Box::<AnyKey>::from_public_bytes(
+            KeyAlg::Bls12_381(BlsCurves::G1),
+            &as_g1.to_public_bytes().unwrap(),
+        )
+        .unwrap();
+        let err = public_only
+            .convert_key(KeyAlg::Bls12_381(BlsCurves::G2))
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::error::ErrorKind::InvalidKeyData);
     }
 
     #[cfg(feature = "any_key")]
@@ -619,6 +947,7 @@ mod tests {
     // test loading of a key with the OKP key type
     fn g1_jwk_any_compat() {
         use crate::alg::{any::AnyKey, BlsCurves, KeyAlg};
+        use crate::jwk::{JwkBufferEncoder, JwkEncoderMode};
         use alloc::boxed::Box;
 
         let test_jwk_compat = r#"
@@ -650,5 +979,105 @@ mod tests {
         let _ = as_bls
             .to_jwk_public(None)
             .expect("Error converting key to JWK");
+
+        // encode using the compressed representation
+        let mut buf = alloc::vec::Vec::new();
+        let mut enc = JwkBufferEncoder::new(&mut buf, JwkEncoderMode::SecretKey).compressed(true);
+        knew.encode_jwk(&mut enc).unwrap();
+        enc.finalize().unwrap();
+        let parts = JwkParts::from_slice(&buf).unwrap();
+        let expect = JwkParts::from_slice(test_jwk_compat.as_bytes()).unwrap();
+        assert_eq!(parts.kty, JWK_KEY_TYPE_OKP);
+        assert_eq!(parts.crv, expect.crv);
+        assert_eq!(parts.x, expect.x);
+        assert_eq!(parts.y, None);
+        assert_eq!(parts.d, expect.d);
+    }
+
+    #[test]
+    fn key_pair_eq() {
+        let kp = BlsKeyPair::<G1>::from_seed(&[1u8; 32]).unwrap();
+        let same = BlsKeyPair::<G1>::from_seed(&[1u8; 32]).unwrap();
+        let other = BlsKeyPair::<G1>::from_seed(&[2u8; 32]).unwrap();
+        let public = BlsKeyPair::<G1>::from_public_bytes(&kp.to_public_bytes().unwrap()).unwrap();
+        assert!(bool::from(kp.ct_eq(&same)));
+        assert!(!bool::from(kp.ct_eq(&other)));
+        assert!(!bool::from(kp.ct_eq(&public)));
+        assert!(bool::from(public.ct_eq(&public)));
+        assert_eq!(kp, same);
+        assert_ne!(kp, public);
+    }
+
+    #[test]
+    fn g1_public_key_subgroup_check() {
+        // a point on the curve which is outside of the prime-order subgroup
+        let compressed = hex!(
+            "80000000000000000000000000000000000000000000000000000000000000000000000000000000"
+            "0000000000000004"
+        );
+        let uncompressed = hex!(
+            "00000000000000000000000000000000000000000000000000000000000000000000000000000000"
+            "00000000000000040a989badd40d6212b33cffc3f3763e9bc760f988c9926b26da9dd85e92848344"
+            "6346b8ed00e1de5d5ea93e354abe706c"
+        );
+        assert!(bool::from(
+            G1Affine::from_compressed_unchecked(&compressed).is_some()
+        ));
+        assert!(bool::from(
+            G1Affine::from_uncompressed_unchecked(&uncompressed).is_some()
+        ));
+        assert!(G1::from_public_bytes(&compressed).is_err());
+        assert!(G1::from_public_bytes(&uncompressed).is_err());
+        assert!(BlsKeyPair::<G1>::from_public_bytes(&compressed).is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn msm_matches_naive() {
+        fn check<G: BlsGroup + core::ops::Mul<Scalar, Output = G>>(generator: G) {
+            for count in [0usize, 1, 5, 40] {
+                let mut msm = Msm::<G>::with_capacity(count);
+                let mut expected = G::identity();
+                for i in 0..count {
+                    let point = generator * Scalar::from(i as u64 + 7);
+                    let scalar = Scalar::from_bytes_wide(&[i as u8 + 1; 64]);
+                    msm.append(point, &scalar);
+                    expected += point * scalar;
+                }
+                assert_eq!(msm.len(), count);
+                assert_eq!(msm.sum(), expected);
+            }
+        }
+        check(G1Projective::generator());
+        check(G2Projective::generator());
+
+        let sum = MsmG1::new()
+            .push(G1Affine::generator(), &Scalar::from(2u64))
+            .push(G1Affine::generator(), &-Scalar::one())
+            .sum();
+        assert_eq!(sum, G1Projective::generator());
+    }
+
+    #[test]
+    fn scalar_encoding_boundaries() {
+        // the group order r
+        let order = hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+        let order_minus_one =
+            hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+
+        assert_eq!(scalar_from_be_bytes(&[0u8; 32]).unwrap(), Scalar::zero());
+        let max = scalar_from_be_bytes(&order_minus_one).unwrap();
+        assert_eq!(max, -Scalar::one());
+        assert_eq!(scalar_to_be_bytes(&max), order_minus_one);
+        assert!(scalar_from_be_bytes(&order).is_err());
+        assert!(scalar_from_be_bytes(&[0xffu8; 32]).is_err());
+        assert!(scalar_from_be_bytes(&order_minus_one[1..]).is_err());
+
+        let mut order_le = order;
+        order_le.reverse();
+        assert!(scalar_from_le_bytes(&order_le).is_err());
+        let mut max_le = order_minus_one;
+        max_le.reverse();
+        assert_eq!(scalar_from_le_bytes(&max_le).unwrap(), max);
     }
 }
@@ -8,6 +8,11 @@ use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 use super::{Chacha20Types, HasKeyAlg, HasKeyBackend, KeyAlg};
+#[cfg(feature = "cose")]
+use crate::{
+    buffer::WriteBuffer,
+    cose::{symmetric_alg, CoseKeyParts, FromCoseKey, ToCoseKey, KTY_SYMMETRIC},
+};
 use crate::{
     buffer::{ArrayKey, ResizeBuffer, Writer},
     encrypt::{KeyAeadInPlace, KeyAeadMeta, KeyAeadParams},
@@ -239,6 +244,36 @@ impl<T: Chacha20Type> ToJwk for Chacha20Key<T> {
     }
 }
 
+#[cfg(feature = "cose")]
+impl<T: Chacha20Type> ToCoseKey for Chacha20Key<T> {
+    fn write_cose_key(&self, secret: bool, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        if !secret {
+            return Err(err_msg!(Unsupported, "Cannot export as a public key"));
+        }
+        CoseKeyParts {
+            kty: KTY_SYMMETRIC,
+            alg: symmetric_alg(self.algorithm()),
+            k: Some(self.0.as_ref()),
+            ..Default::default()
+        }
+        .write(out)
+    }
+}
+
+#[cfg(feature = "cose")]
+impl<T: Chacha20Type> FromCoseKey for Chacha20Key<T> {
+    fn from_cose_key_parts(cose_key: CoseKeyParts<'_>) -> Result<Self, Error> {
+        if cose_key.kty != KTY_SYMMETRIC {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key type"));
+        }
+        if cose_key.alg.is_some() && cose_key.alg != symmetric_alg(KeyAlg::Chacha20(T::ALG_TYPE)) {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key algorithm"));
+        }
+        let k = cose_key.k.ok_or_else(|| err_msg!(InvalidKeyData))?;
+        Self::from_secret_bytes(k)
+    }
+}
+
 // for direct key agreement (not used currently)
 impl<Lhs, Rhs, T> FromKeyExchange<Lhs, Rhs> for Chacha20Key<T>
 where
@@ -281,6 +316,35 @@ mod tests {
         test_encrypt::<XC20P>();
     }
 
+    #[test]
+    fn encrypt_with_aad_expected() {
+        // from RFC 8439 section 2.8.2
+        let key = Chacha20Key::<C20P>::from_secret_bytes(&hex!(
+            "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f"
+        ))
+        .unwrap();
+        let nonce = hex!("070000004041424344454647");
+        let aad = hex!("50515253c0c1c2c3c4c5c6c7");
+        let input = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+            only one tip for the future, sunscreen would be it.";
+        let expected = hex!(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6"
+            "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36"
+            "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc"
+            "3ff4def08e4b7a9de576d26586cec64b6116"
+            "1ae10b594f09e26a7e902ecbd0600691"
+        );
+        let mut buffer = SecretBytes::from_slice(input);
+        key.encrypt_in_place(&mut buffer, &nonce, &aad).unwrap();
+        assert_eq!(&buffer[..], &expected[..]);
+
+        // the associated data must match for decryption to succeed
+        let mut check = buffer.clone();
+        assert!(key.decrypt_in_place(&mut check, &nonce, &[]).is_err());
+        key.decrypt_in_place(&mut buffer, &nonce, &aad).unwrap();
+        assert_eq!(&buffer[..], input);
+    }
+
     #[cfg(feature = "any_key")]
     #[test]
     fn jwk_any_compat() {
@@ -315,4 +379,25 @@ mod tests {
         test_serialize::<C20P>();
         test_serialize::<XC20P>();
     }
+
+    #[cfg(all(feature = "cose", feature = "alloc"))]
+    #[test]
+    fn cose_key_round_trip() {
+        use crate::cose::{FromCoseKey, ToCoseKey};
+
+        let key = Chacha20Key::<C20P>::from_secret_bytes(&[1u8; 32]).unwrap();
+        assert!(key.to_cose_key_public().is_err());
+        let enc = key.to_cose_key_secret().unwrap();
+        let mut expected = hex!("a3010403181820 5820").to_vec();
+        expected.extend_from_slice(&[1u8; 32]);
+        assert_eq!(enc, &expected[..]);
+        assert_eq!(Chacha20Key::<C20P>::from_cose_key(&enc).unwrap(), key);
+        // the registered algorithm must match
+        assert!(Chacha20Key::<XC20P>::from_cose_key(&enc).is_err());
+
+        let key = Chacha20Key::<XC20P>::random().unwrap();
+        let enc = key.to_cose_key_secret().unwrap();
+        assert_eq!(enc[0], 0xa2);
+        assert_eq!(Chacha20Key::<XC20P>::from_cose_key(&enc).unwrap(), key);
+    }
 }
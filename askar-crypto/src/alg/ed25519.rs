@@ -5,23 +5,29 @@ use core::{
     fmt::{self, Debug, Formatter},
 };
 
-use curve25519_dalek::{edwards::CompressedEdwardsY, scalar::clamp_integer};
+use curve25519_dalek::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::{clamp_integer, Scalar},
+};
 use ed25519_dalek::{
     SecretKey, Signature, Signer, SigningKey, VerifyingKey, KEYPAIR_LENGTH, PUBLIC_KEY_LENGTH,
     SECRET_KEY_LENGTH, SIGNATURE_LENGTH as EDDSA_SIGNATURE_LENGTH,
 };
 use sha2::Digest;
-use subtle::ConstantTimeEq;
 use x25519_dalek::{PublicKey as XPublicKey, StaticSecret as XSecretKey};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use super::{x25519::X25519KeyPair, HasKeyAlg, HasKeyBackend, KeyAlg};
+use super::{
+    keypair_from_secret_and_public_bytes, x25519::X25519KeyPair, HasKeyAlg, HasKeyBackend, KeyAlg,
+};
+#[cfg(feature = "cose")]
+use crate::cose::{CoseKeyParts, FromCoseKey, ToCoseKey, ALG_EDDSA, CRV_ED25519, KTY_OKP};
 use crate::{
     buffer::{ArrayKey, WriteBuffer},
     error::Error,
     generic_array::typenum::{U32, U64},
     jwk::{FromJwk, JwkEncoder, JwkParts, ToJwk},
-    random::KeyMaterial,
+    random::{KeyMaterial, SeedKeyGen},
     repr::{KeyGen, KeyMeta, KeyPublicBytes, KeySecretBytes, KeypairBytes, KeypairMeta},
     sign::{KeySigVerify, KeySign, SignatureType},
 };
@@ -49,12 +55,19 @@ impl Ed25519KeyPair {
         }
     }
 
-    pub(crate) fn check_public_bytes(&self, pk: &[u8]) -> Result<(), Error> {
-        if self.public.ct_eq(pk).into() {
-            Ok(())
-        } else {
-            Err(err_msg!(InvalidKeyData, "invalid ed25519 keypair"))
-        }
+    /// Create a keypair from separate secret and public key bytes, checking
+    /// that the public key corresponds to the secret key
+    pub fn from_secret_and_public_bytes(sk: &[u8], pk: &[u8]) -> Result<Self, Error> {
+        keypair_from_secret_and_public_bytes(sk, pk, "invalid ed25519 keypair")
+    }
+
+    /// Create a keypair deterministically from a seed of at least 32 bytes
+    ///
+    /// The secret key is the output of HKDF-SHA256 using the seed as the
+    /// input key material, the salt `ASKAR-SEED-KEYGEN-SALT-`, and the info
+    /// `Ed25519` followed by a 4-byte big-endian counter of zero.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        Self::generate(SeedKeyGen::new(seed, b"Ed25519")?)
     }
 
     /// Create a signing key from the secret key
@@ -65,23 +78,33 @@ impl Ed25519KeyPair {
     }
 
     /// Convert this keypair to an X25519 keypair
+    ///
+    /// The public key is validated whenever an `Ed25519KeyPair` is
+    /// constructed, so this conversion cannot fail.
     pub fn to_x25519_keypair(&self) -> X25519KeyPair {
+        self.try_to_x25519_keypair()
+            .expect("Ed25519 public key must be valid")
+    }
+
+    /// Convert this keypair to an X25519 keypair, returning an error if the
+    /// public key cannot be decompressed
+    pub fn try_to_x25519_keypair(&self) -> Result<X25519KeyPair, Error> {
         if let Some(secret) = self.secret.as_ref() {
             let hash = sha2::Sha512::digest(secret);
             // clamp result: we manually clamp the secret key for consistency with older versions,
             // although it is not strictly necessary.
             let secret = XSecretKey::from(clamp_integer(hash[..32].try_into().unwrap()));
             let public = XPublicKey::from(&secret);
-            X25519KeyPair::new(Some(secret), public)
+            Ok(X25519KeyPair::new(Some(secret), public))
         } else {
             let public = XPublicKey::from(
                 CompressedEdwardsY(self.public)
                     .decompress()
-                    .unwrap()
+                    .ok_or_else(|| err_msg!(InvalidKeyData, "Invalid ed25519 public key"))?
                     .to_montgomery()
                     .to_bytes(),
             );
-            X25519KeyPair::new(None, public)
+            Ok(X25519KeyPair::new(None, public))
         }
     }
 
@@ -92,13 +115,109 @@ impl Ed25519KeyPair {
 
     /// Verify a signature against the public key
     pub fn verify_signature(&self, message: &[u8], signature: &[u8]) -> bool {
-        if let Ok(sig) = Signature::try_from(signature) {
-            let vk = VerifyingKey::from_bytes(&self.public).unwrap();
-            vk.verify_strict(message, &sig).is_ok()
-        } else {
-            false
+        // the public key is validated on construction, but an invalid point
+        // is treated as a verification failure rather than a panic
+        match (
+            Signature::try_from(signature),
+            VerifyingKey::from_bytes(&self.public),
+        ) {
+            (Ok(sig), Ok(vk)) => vk.verify_strict(message, &sig).is_ok(),
+            _ => false,
         }
     }
+
+    /// Sign a message with the secret key using Ed25519ctx (RFC 8032),
+    /// which binds the signature to a context string of up to 255 bytes.
+    ///
+    /// Signatures produced with any context, including an empty context,
+    /// are not valid as plain Ed25519 signatures and vice versa.
+    pub fn sign_with_context(
+        &self,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<[u8; EDDSA_SIGNATURE_LENGTH], Error> {
+        let dom = ctx_dom_prefix(context)?;
+        let secret = self
+            .secret
+            .as_ref()
+            .ok_or_else(|| err_msg!(MissingSecretKey))?;
+        let mut hash: [u8; 64] = sha2::Sha512::digest(secret).into();
+        let mut s = Scalar::from_bytes_mod_order(clamp_integer(hash[..32].try_into().unwrap()));
+        let mut r = Scalar::from_hash(
+            sha2::Sha512::new()
+                .chain_update(dom)
+                .chain_update(context)
+                .chain_update(&hash[32..])
+                .chain_update(message),
+        );
+        hash.zeroize();
+        let big_r = EdwardsPoint::mul_base(&r).compress();
+        let k = Scalar::from_hash(
+            sha2::Sha512::new()
+                .chain_update(dom)
+                .chain_update(context)
+                .chain_update(big_r.as_bytes())
+                .chain_update(self.public)
+                .chain_update(message),
+        );
+        let big_s = k * s + r;
+        s.zeroize();
+        r.zeroize();
+        let mut sig = [0u8; EDDSA_SIGNATURE_LENGTH];
+        sig[..32].copy_from_slice(big_r.as_bytes());
+        sig[32..].copy_from_slice(big_s.as_bytes());
+        Ok(sig)
+    }
+
+    /// Verify an Ed25519ctx (RFC 8032) signature against the public key and
+    /// context string
+    ///
+    /// As for [`Ed25519KeyPair::verify_signature`], small order points and
+    /// non-canonical scalars are rejected. A context longer than 255 bytes
+    /// never verifies.
+    pub fn verify_with_context(&self, message: &[u8], signature: &[u8], context: &[u8]) -> bool {
+        let dom = match ctx_dom_prefix(context) {
+            Ok(dom) => dom,
+            Err(_) => return false,
+        };
+        if signature.len() != EDDSA_SIGNATURE_LENGTH {
+            return false;
+        }
+        let big_r = CompressedEdwardsY(signature[..32].try_into().unwrap());
+        let big_s = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap());
+        match (
+            big_r.decompress(),
+            CompressedEdwardsY(self.public).decompress(),
+            Option::<Scalar>::from(big_s),
+        ) {
+            (Some(point_r), Some(public), Some(big_s))
+                if !point_r.is_small_order() && !public.is_small_order() =>
+            {
+                let k = Scalar::from_hash(
+                    sha2::Sha512::new()
+                        .chain_update(dom)
+                        .chain_update(context)
+                        .chain_update(big_r.as_bytes())
+                        .chain_update(self.public)
+                        .chain_update(message),
+                );
+                EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-public, &big_s).compress()
+                    == big_r
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Construct the `dom2` prefix for Ed25519ctx, excluding the context itself
+fn ctx_dom_prefix(context: &[u8]) -> Result<[u8; 34], Error> {
+    let ctx_len = u8::try_from(context.len())
+        .map_err(|_| err_msg!(Usage, "Ed25519ctx context must be at most 255 bytes"))?;
+    let mut dom = [0u8; 34];
+    dom[..32].copy_from_slice(b"SigEd25519 no Ed25519 collisions");
+    // the flag octet is zero for Ed25519ctx
+    dom[33] = ctx_len;
+    Ok(dom)
 }
 
 impl Debug for Ed25519KeyPair {
@@ -157,10 +276,7 @@ impl KeypairBytes for Ed25519KeyPair {
         if kp.len() != KEYPAIR_LENGTH {
             return Err(err_msg!(InvalidKeyData));
         }
-        // NB: this is infallible if the slice is the right length
-        let result = Ed25519KeyPair::from_secret_bytes(&kp[..SECRET_KEY_LENGTH])?;
-        result.check_public_bytes(&kp[SECRET_KEY_LENGTH..])?;
-        Ok(result)
+        Self::from_secret_and_public_bytes(&kp[..SECRET_KEY_LENGTH], &kp[SECRET_KEY_LENGTH..])
     }
 
     fn with_keypair_bytes<O>(&self, f: impl FnOnce(Option<&[u8]>) -> O) -> O {
@@ -237,6 +353,7 @@ impl ToJwk for Ed25519KeyPair {
         enc.add_str("crv", JWK_CURVE)?;
         enc.add_str("kty", JWK_KEY_TYPE)?;
         self.with_public_bytes(|buf| enc.add_as_base64("x", buf))?;
+        enc.add_sig_alg(&[SignatureType::EdDSA])?;
         if enc.is_secret() {
             self.with_secret_bytes(|buf| {
                 if let Some(sk) = buf {
@@ -266,9 +383,11 @@ impl FromJwk for Ed25519KeyPair {
                     if jwk.d.decode_base64(sk_arr)? != sk_arr.len() {
                         Err(err_msg!(InvalidKeyData))
                     } else {
-                        let kp = Ed25519KeyPair::from_secret_bytes(sk_arr)?;
-                        kp.check_public_bytes(pk_arr)?;
-                        Ok(kp)
+                        keypair_from_secret_and_public_bytes(
+                            sk_arr,
+                            pk_arr,
+                            "invalid ed25519 keypair",
+                        )
                     }
                 })
             } else {
@@ -278,6 +397,43 @@ impl FromJwk for Ed25519KeyPair {
     }
 }
 
+#[cfg(feature = "cose")]
+impl ToCoseKey for Ed25519KeyPair {
+    fn write_cose_key(&self, secret: bool, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        self.with_secret_bytes(|sk| {
+            CoseKeyParts {
+                kty: KTY_OKP,
+                alg: Some(ALG_EDDSA),
+                crv: Some(CRV_ED25519),
+                x: Some(&self.public[..]),
+                d: sk.filter(|_| secret),
+                ..Default::default()
+            }
+            .write(out)
+        })
+    }
+}
+
+#[cfg(feature = "cose")]
+impl FromCoseKey for Ed25519KeyPair {
+    fn from_cose_key_parts(cose_key: CoseKeyParts<'_>) -> Result<Self, Error> {
+        if cose_key.kty != KTY_OKP {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key type"));
+        }
+        if cose_key.crv != Some(CRV_ED25519)
+            || cose_key.alg.map(|alg| alg != ALG_EDDSA).unwrap_or(false)
+        {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key algorithm"));
+        }
+        let pk = cose_key.x.ok_or_else(|| err_msg!(InvalidKeyData))?;
+        if let Some(sk) = cose_key.d {
+            Self::from_secret_and_public_bytes(sk, pk)
+        } else {
+            Self::from_public_bytes(pk)
+        }
+    }
+}
+
 impl Drop for Ed25519KeyPair {
     fn drop(&mut self) {
         self.secret.zeroize();
@@ -306,7 +462,10 @@ mod tests {
     use base64::Engine;
 
     use super::*;
-    use crate::repr::{ToPublicBytes, ToSecretBytes};
+    use crate::{
+        error::ErrorKind,
+        repr::{ToPublicBytes, ToSecretBytes},
+    };
 
     #[test]
     fn expand_keypair() {
@@ -322,6 +481,101 @@ mod tests {
         assert_eq!(cmp.to_keypair_bytes().unwrap(), &test_sk[..]);
     }
 
+    #[test]
+    fn sign_verify_context_expected() {
+        // from RFC 8032 section 7.2
+        let kp = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6"
+        ))
+        .unwrap();
+        assert_eq!(
+            kp.to_public_bytes().unwrap(),
+            &hex!("dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292")[..]
+        );
+        let kp2 = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "ab9c2853ce297ddab85c993b3ae14bcad39b2c682beabc27d6d4eb20711d6560"
+        ))
+        .unwrap();
+        assert_eq!(
+            kp2.to_public_bytes().unwrap(),
+            &hex!("0f1d1274943b91415889152e893d80e93275a1fc0b65fd71b4b0dda10ad7d772")[..]
+        );
+        for (key, message, context, signature) in [
+            (
+                &kp,
+                &hex!("f726936d19c800494e3fdaff20b276a8"),
+                &b"foo"[..],
+                hex!(
+                    "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a"
+                    "8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d"
+                ),
+            ),
+            (
+                &kp,
+                &hex!("f726936d19c800494e3fdaff20b276a8"),
+                &b"bar"[..],
+                hex!(
+                    "fc60d5872fc46b3aa69f8b5b4351d5808f92bcc044606db097abab6dbcb1aee3"
+                    "216c48e8b3b66431b5b186d1d28f8ee15a5ca2df6668346291c2043d4eb3e90d"
+                ),
+            ),
+            (
+                &kp,
+                &hex!("508e9e6882b979fea900f62adceaca35"),
+                &b"foo"[..],
+                hex!(
+                    "8b70c1cc8310e1de20ac53ce28ae6e7207f33c3295e03bb5c0732a1d20dc6490"
+                    "8922a8b052cf99b7c4fe107a5abb5b2c4085ae75890d02df26269d8945f84b0b"
+                ),
+            ),
+            (
+                &kp2,
+                &hex!("f726936d19c800494e3fdaff20b276a8"),
+                &b"foo"[..],
+                hex!(
+                    "21655b5f1aa965996b3f97b3c849eafba922a0a62992f73b3d1b73106a84ad85"
+                    "e9b86a7b6005ea868337ff2d20a7f5fbd4cd10b0be49a68da2b2e0dc0ad8960f"
+                ),
+            ),
+        ] {
+            assert_eq!(key.sign_with_context(message, context).unwrap(), signature);
+            assert!(key.verify_with_context(message, &signature, context));
+            assert!(!key.verify_with_context(message, &signature, b"baz"));
+            assert!(!key.verify_signature(message, &signature));
+        }
+    }
+
+    #[test]
+    fn sign_context_empty_and_long() {
+        let kp = Ed25519KeyPair::from_secret_bytes(&[1u8; 32]).unwrap();
+        let message = b"message";
+        // an empty context is still distinct from plain Ed25519
+        let sig = kp.sign_with_context(message, b"").unwrap();
+        assert_ne!(sig, kp.sign(message).unwrap());
+        assert!(kp.verify_with_context(message, &sig, b""));
+        assert!(!kp.verify_signature(message, &sig));
+        assert!(!kp.verify_with_context(message, &kp.sign(message).unwrap(), b""));
+
+        let context = [0u8; 255];
+        let sig = kp.sign_with_context(message, &context).unwrap();
+        assert!(kp.verify_with_context(message, &sig, &context));
+        assert!(!kp.verify_with_context(message, &sig[..63], &context));
+        assert_eq!(
+            kp.sign_with_context(message, &[0u8; 256])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Usage
+        );
+        assert!(!kp.verify_with_context(message, &sig, &[0u8; 256]));
+
+        let public = Ed25519KeyPair::from_public_bytes(&kp.to_public_bytes().unwrap()).unwrap();
+        assert!(public.verify_with_context(message, &sig, &context));
+        assert_eq!(
+            public.sign_with_context(message, b"").unwrap_err().kind(),
+            ErrorKind::MissingSecretKey
+        );
+    }
+
     #[test]
     fn ed25519_to_x25519() {
         let test_keypair = &hex!("1c1179a560d092b90458fe6ab8291215a427fcd6b3927cb240701778ef55201927c96646f2d4632d4fc241f84cbc427fbc3ecaa95becba55088d6c7b81fc5bbf");
@@ -334,6 +588,44 @@ mod tests {
             .unwrap();
         assert_eq!(&x_pair[..32], x_sk);
         assert_eq!(&x_pair[32..], x_pk);
+
+        let x_public = Ed25519KeyPair::from_public_bytes(&test_keypair[32..])
+            .unwrap()
+            .try_to_x25519_keypair()
+            .unwrap();
+        assert_eq!(x_public.to_public_bytes().unwrap(), &x_pk[..]);
+    }
+
+    #[cfg(feature = "any_key")]
+    #[test]
+    // test conversion of an Ed25519 key to X25519 through AnyKey
+    fn ed25519_to_x25519_any() {
+        use crate::{
+            alg::{any::AnyKey, AnyKeyCreate},
+            error::ErrorKind,
+        };
+        use alloc::boxed::Box;
+
+        let test_keypair = &hex!("1c1179a560d092b90458fe6ab8291215a427fcd6b3927cb240701778ef55201927c96646f2d4632d4fc241f84cbc427fbc3ecaa95becba55088d6c7b81fc5bbf");
+        let key =
+            Box::<AnyKey>::from_key(Ed25519KeyPair::from_keypair_bytes(test_keypair).unwrap());
+        let x_key = key
+            .convert_key(KeyAlg::X25519)
+            .expect("Error converting keypair");
+        assert_eq!(x_key.algorithm(), KeyAlg::X25519);
+        let as_x = x_key
+            .downcast_ref::<X25519KeyPair>()
+            .expect("Error downcasting X25519 key");
+        let x_expect = Ed25519KeyPair::from_keypair_bytes(test_keypair)
+            .unwrap()
+            .to_x25519_keypair();
+        assert_eq!(
+            as_x.to_keypair_bytes().unwrap(),
+            x_expect.to_keypair_bytes().unwrap()
+        );
+
+        let err = x_key.convert_key(KeyAlg::Ed25519).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
     }
 
     #[test]
@@ -397,6 +689,36 @@ mod tests {
         assert!(!kp.verify_signature(test_msg, &[0u8; 64]));
     }
 
+    #[test]
+    fn invalid_public_key() {
+        // not the encoding of a point on the curve
+        let mut public = [0u8; PUBLIC_KEY_LENGTH];
+        public[0] = 2;
+        assert!(Ed25519KeyPair::from_public_bytes(&public).is_err());
+
+        let kp = Ed25519KeyPair {
+            secret: None,
+            public,
+        };
+        assert!(!kp.verify_signature(b"message", &[0u8; EDDSA_SIGNATURE_LENGTH]));
+        assert!(kp.try_to_x25519_keypair().is_err());
+    }
+
+    #[test]
+    fn from_seed_expected() {
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let kp = Ed25519KeyPair::from_seed(&seed).unwrap();
+        assert_eq!(
+            kp.to_secret_bytes().unwrap(),
+            &hex!("3f8afb93155add03f73053719c9ed3446dc1ace2024cd5f814deb94a7a11ba2a")[..]
+        );
+        assert_eq!(
+            kp.to_public_bytes().unwrap(),
+            &hex!("6afcff86d2bf499c77319689e4780d197192445e46f4ed687ae74d545919e0cb")[..]
+        );
+        assert!(Ed25519KeyPair::from_seed(&seed[..31]).is_err());
+    }
+
     #[test]
     fn round_trip_bytes() {
         let kp = Ed25519KeyPair::random().unwrap();
@@ -406,4 +728,63 @@ mod tests {
             cmp.to_keypair_bytes().unwrap()
         );
     }
+
+    #[test]
+    fn secret_and_public_bytes() {
+        let kp = Ed25519KeyPair::random().unwrap();
+        let sk = kp.to_secret_bytes().unwrap();
+        let pk = kp.to_public_bytes().unwrap();
+        let cmp = Ed25519KeyPair::from_secret_and_public_bytes(&sk, &pk).unwrap();
+        assert_eq!(
+            kp.to_keypair_bytes().unwrap(),
+            cmp.to_keypair_bytes().unwrap()
+        );
+
+        let other = Ed25519KeyPair::random().unwrap().to_public_bytes().unwrap();
+        let err = Ed25519KeyPair::from_secret_and_public_bytes(&sk, &other).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidKeyData);
+    }
+
+    #[cfg(all(feature = "cose", feature = "alloc"))]
+    #[test]
+    fn cose_key_expected() {
+        // from RFC 8032 test 1
+        let sk = hex!("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+        let kp = Ed25519KeyPair::from_secret_bytes(&sk).unwrap();
+        let public = kp.to_cose_key_public().unwrap();
+        assert_eq!(
+            public,
+            &hex!(
+                "a4010103272006215820"
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+            )[..]
+        );
+        let secret = kp.to_cose_key_secret().unwrap();
+        assert_eq!(
+            secret,
+            &hex!(
+                "a5010103272006215820"
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+                "235820"
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+            )[..]
+        );
+
+        let pk_load = Ed25519KeyPair::from_cose_key(&public).unwrap();
+        assert_eq!(
+            pk_load.to_public_bytes().unwrap(),
+            kp.to_public_bytes().unwrap()
+        );
+        assert!(pk_load.to_secret_bytes().is_err());
+        let sk_load = Ed25519KeyPair::from_cose_key(&secret).unwrap();
+        assert_eq!(
+            sk_load.to_keypair_bytes().unwrap(),
+            kp.to_keypair_bytes().unwrap()
+        );
+
+        // mismatched curve
+        let mut bad = public.clone();
+        bad[6] = 0x07;
+        assert!(Ed25519KeyPair::from_cose_key(&bad).is_err());
+    }
 }
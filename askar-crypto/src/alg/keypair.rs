@@ -0,0 +1,290 @@
+//! Key wrappers distinguishing public keys from keys with secret material
+
+use alloc::boxed::Box;
+
+use super::{AnyKey, AnyKeyCreate, HasKeyAlg, KeyAlg};
+use crate::{
+    buffer::{SecretBytes, WriteBuffer},
+    error::Error,
+    jwk::{FromJwk, JwkEncoder, JwkParts, ToJwk},
+    kdf::KeyExchange,
+    repr::{ToPublicBytes, ToSecretBytes},
+    sign::{KeySigVerify, KeySign, SignatureType},
+};
+
+/// A public key of any supported algorithm
+///
+/// A `PublicKey` never holds secret key material, so it cannot be used to
+/// create signatures.
+#[derive(Debug)]
+pub struct PublicKey(Box<AnyKey>);
+
+impl PublicKey {
+    /// Load a public key from its byte representation
+    pub fn from_public_bytes(alg: KeyAlg, public: &[u8]) -> Result<Self, Error> {
+        Ok(Self(Box::<AnyKey>::from_public_bytes(alg, public)?))
+    }
+
+    /// Wrap a key instance, discarding any secret key material
+    ///
+    /// Keys which have no public component, such as symmetric keys, result
+    /// in an `Unsupported` error.
+    pub fn from_key(key: Box<AnyKey>) -> Result<Self, Error> {
+        if key.has_secret() {
+            Self::from_public_bytes(key.algorithm(), &key.to_public_bytes()?)
+        } else {
+            Ok(Self(key))
+        }
+    }
+
+    /// Access the wrapped key
+    pub fn as_any_key(&self) -> &AnyKey {
+        &self.0
+    }
+
+    /// Unwrap the key instance
+    pub fn into_any_key(self) -> Box<AnyKey> {
+        self.0
+    }
+
+    /// Verify a signature against the public key
+    pub fn verify_signature(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        sig_type: Option<SignatureType>,
+    ) -> Result<bool, Error> {
+        self.0.verify_signature(message, signature, sig_type)
+    }
+}
+
+impl FromJwk for PublicKey {
+    /// Load a public key from a JWK, ignoring any secret key member
+    fn from_jwk_parts(jwk: JwkParts<'_>) -> Result<Self, Error> {
+        Self::from_key(Box::<AnyKey>::from_jwk_parts(jwk)?)
+    }
+}
+
+impl TryFrom<Box<AnyKey>> for PublicKey {
+    type Error = Error;
+
+    fn try_from(key: Box<AnyKey>) -> Result<Self, Error> {
+        Self::from_key(key)
+    }
+}
+
+/// A key of any supported algorithm which includes secret key material
+///
+/// This may be an asymmetric keypair or a symmetric key. Only a `Keypair`
+/// may be used to create signatures.
+#[derive(Debug)]
+pub struct Keypair(Box<AnyKey>);
+
+impl Keypair {
+    /// Generate a new random key for the given key algorithm
+    #[cfg(feature = "getrandom")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+    pub fn random(alg: KeyAlg) -> Result<Self, Error> {
+        Ok(Self(Box::<AnyKey>::random(alg)?))
+    }
+
+    /// Load a secret key or keypair from its byte representation
+    pub fn from_secret_bytes(alg: KeyAlg, secret: &[u8]) -> Result<Self, Error> {
+        Self::from_key(Box::<AnyKey>::from_secret_bytes(alg, secret)?)
+    }
+
+    /// Wrap a key instance, returning a `MissingSecretKey` error if it does
+    /// not include secret key material
+    pub fn from_key(key: Box<AnyKey>) -> Result<Self, Error> {
+        if key.has_secret() {
+            Ok(Self(key))
+        } else {
+            Err(err_msg!(MissingSecretKey))
+        }
+    }
+
+    /// Access the wrapped key
+    pub fn as_any_key(&self) -> &AnyKey {
+        &self.0
+    }
+
+    /// Unwrap the key instance
+    pub fn into_any_key(self) -> Box<AnyKey> {
+        self.0
+    }
+
+    /// Get the public key corresponding to this keypair
+    ///
+    /// Symmetric keys have no public key and result in an `Unsupported` error.
+    pub fn to_public(&self) -> Result<PublicKey, Error> {
+        PublicKey::from_public_bytes(self.0.algorithm(), &self.0.to_public_bytes()?)
+    }
+
+    /// Sign a message with the secret key
+    pub fn sign(
+        &self,
+        message: &[u8],
+        sig_type: Option<SignatureType>,
+    ) -> Result<SecretBytes, Error> {
+        self.0.create_signature(message, sig_type)
+    }
+
+    /// Verify a signature against the public key
+    pub fn verify_signature(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        sig_type: Option<SignatureType>,
+    ) -> Result<bool, Error> {
+        self.0.verify_signature(message, signature, sig_type)
+    }
+}
+
+impl FromJwk for Keypair {
+    /// Load a keypair from a JWK, which must include the secret key
+    fn from_jwk_parts(jwk: JwkParts<'_>) -> Result<Self, Error> {
+        Self::from_key(Box::<AnyKey>::from_jwk_parts(jwk)?)
+    }
+}
+
+impl TryFrom<Box<AnyKey>> for Keypair {
+    type Error = Error;
+
+    fn try_from(key: Box<AnyKey>) -> Result<Self, Error> {
+        Self::from_key(key)
+    }
+}
+
+impl KeyExchange<PublicKey> for Keypair {
+    fn write_key_exchange(
+        &self,
+        other: &PublicKey,
+        out: &mut dyn WriteBuffer,
+    ) -> Result<(), Error> {
+        self.0.write_key_exchange(&other.0, out)
+    }
+}
+
+impl ToSecretBytes for Keypair {
+    fn secret_bytes_length(&self) -> Result<usize, Error> {
+        self.0.secret_bytes_length()
+    }
+
+    fn has_secret(&self) -> bool {
+        true
+    }
+
+    fn write_secret_bytes(&self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        self.0.write_secret_bytes(out)
+    }
+}
+
+impl KeySign for Keypair {
+    fn write_signature(
+        &self,
+        message: &[u8],
+        sig_type: Option<SignatureType>,
+        out: &mut dyn WriteBuffer,
+    ) -> Result<(), Error> {
+        self.0.write_signature(message, sig_type, out)
+    }
+}
+
+macro_rules! impl_common {
+    ($type:ty) => {
+        impl HasKeyAlg for $type {
+            fn algorithm(&self) -> KeyAlg {
+                self.0.algorithm()
+            }
+        }
+
+        impl AsRef<AnyKey> for $type {
+            fn as_ref(&self) -> &AnyKey {
+                &self.0
+            }
+        }
+
+        impl From<$type> for Box<AnyKey> {
+            fn from(key: $type) -> Self {
+                key.0
+            }
+        }
+
+        impl ToPublicBytes for $type {
+            fn public_bytes_length(&self) -> Result<usize, Error> {
+                self.0.public_bytes_length()
+            }
+
+            fn write_public_bytes(&self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+                self.0.write_public_bytes(out)
+            }
+        }
+
+        impl ToJwk for $type {
+            fn encode_jwk(&self, enc: &mut dyn JwkEncoder) -> Result<(), Error> {
+                self.0.encode_jwk(enc)
+            }
+        }
+
+        impl KeySigVerify for $type {
+            fn verify_signature(
+                &self,
+                message: &[u8],
+                signature: &[u8],
+                sig_type: Option<SignatureType>,
+            ) -> Result<bool, Error> {
+                self.0.verify_signature(message, signature, sig_type)
+            }
+        }
+    };
+}
+
+impl_common!(PublicKey);
+impl_common!(Keypair);
+
+#[cfg(all(test, feature = "ed25519"))]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn keypair_public_key() {
+        let kp = Keypair::from_secret_bytes(KeyAlg::Ed25519, &[1u8; 32]).unwrap();
+        let public = kp.to_public().unwrap();
+        assert!(!public.as_any_key().has_secret());
+        assert_eq!(public.algorithm(), KeyAlg::Ed25519);
+        assert_eq!(
+            public.to_public_bytes().unwrap(),
+            kp.to_public_bytes().unwrap()
+        );
+
+        let sig = kp.sign(b"message", None).unwrap();
+        assert!(public.verify_signature(b"message", &sig, None).unwrap());
+        assert!(kp.verify_signature(b"message", &sig, None).unwrap());
+        assert!(!public.verify_signature(b"other", &sig, None).unwrap());
+
+        // a public key cannot be wrapped as a keypair
+        assert_eq!(
+            Keypair::from_key(public.into_any_key()).unwrap_err().kind(),
+            ErrorKind::MissingSecretKey
+        );
+        // the secret key is discarded when wrapping a keypair as a public key
+        let public = PublicKey::from_key(kp.into_any_key()).unwrap();
+        assert!(!public.as_any_key().has_secret());
+
+        let jwk =
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+        assert!(PublicKey::from_jwk(jwk).is_ok());
+        assert!(Keypair::from_jwk(jwk).is_err());
+    }
+
+    #[test]
+    fn keypair_exchange() {
+        let alice = Keypair::from_secret_bytes(KeyAlg::X25519, &[1u8; 32]).unwrap();
+        let bob = Keypair::from_secret_bytes(KeyAlg::X25519, &[2u8; 32]).unwrap();
+        assert_eq!(
+            alice.key_exchange_bytes(&bob.to_public().unwrap()).unwrap(),
+            bob.key_exchange_bytes(&alice.to_public().unwrap()).unwrap()
+        );
+    }
+}
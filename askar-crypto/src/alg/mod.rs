@@ -21,6 +21,12 @@ mod any;
 #[cfg_attr(docsrs, doc(cfg(feature = "any_key")))]
 pub use any::{AnyKey, AnyKeyCreate};
 
+#[cfg(feature = "any_key")]
+mod keypair;
+#[cfg(feature = "any_key")]
+#[cfg_attr(docsrs, doc(cfg(feature = "any_key")))]
+pub use keypair::{Keypair, PublicKey};
+
 #[cfg(feature = "aes")]
 #[cfg_attr(docsrs, doc(cfg(feature = "aes")))]
 pub mod aes;
@@ -41,7 +47,7 @@ pub mod ed25519;
 #[cfg_attr(docsrs, doc(cfg(feature = "ed25519")))]
 pub mod x25519;
 
-#[cfg(feature = "ec_curves")]
+#[cfg(any(feature = "k256", feature = "p256", feature = "p384"))]
 mod ec_common;
 
 #[cfg(feature = "k256")]
@@ -95,6 +101,47 @@ impl KeyAlg {
             Self::EcCurve(EcCurves::Secp384r1) => "p384",
         }
     }
+
+    /// Get the length of the secret key (or symmetric key) in bytes.
+    pub const fn secret_key_length(&self) -> Option<usize> {
+        match self {
+            Self::Aes(AesTypes::A128Gcm) | Self::Aes(AesTypes::A128Kw) => Some(16),
+            Self::Aes(AesTypes::A256Gcm)
+            | Self::Aes(AesTypes::A128CbcHs256)
+            | Self::Aes(AesTypes::A256Kw) => Some(32),
+            Self::Aes(AesTypes::A256CbcHs512) => Some(64),
+            Self::Bls12_381(_) => Some(32),
+            Self::Chacha20(_) => Some(32),
+            Self::Ed25519 | Self::X25519 => Some(32),
+            Self::EcCurve(EcCurves::Secp256k1) | Self::EcCurve(EcCurves::Secp256r1) => Some(32),
+            Self::EcCurve(EcCurves::Secp384r1) => Some(48),
+        }
+    }
+
+    /// Get the length of the encoded public key in bytes, or `None` for
+    /// symmetric key types.
+    pub const fn public_key_length(&self) -> Option<usize> {
+        match self {
+            Self::Aes(_) | Self::Chacha20(_) => None,
+            Self::Bls12_381(BlsCurves::G1) => Some(48),
+            Self::Bls12_381(BlsCurves::G2) => Some(96),
+            Self::Ed25519 | Self::X25519 => Some(32),
+            Self::EcCurve(EcCurves::Secp256k1) | Self::EcCurve(EcCurves::Secp256r1) => Some(33),
+            Self::EcCurve(EcCurves::Secp384r1) => Some(49),
+        }
+    }
+
+    /// Get the length of a signature produced with the default signature
+    /// type, or `None` if the key type does not support signing.
+    pub const fn signature_length(&self) -> Option<usize> {
+        match self {
+            Self::Ed25519
+            | Self::EcCurve(EcCurves::Secp256k1)
+            | Self::EcCurve(EcCurves::Secp256r1) => Some(64),
+            Self::EcCurve(EcCurves::Secp384r1) => Some(96),
+            _ => None,
+        }
+    }
 }
 
 impl AsRef<str> for KeyAlg {
@@ -261,6 +308,21 @@ pub enum EcCurves {
 pub trait HasKeyAlg: Debug {
     /// Get the corresponding key algorithm.
     fn algorithm(&self) -> KeyAlg;
+
+    /// Get the length of the secret key in bytes, if fixed.
+    fn secret_key_length(&self) -> Option<usize> {
+        self.algorithm().secret_key_length()
+    }
+
+    /// Get the length of the public key in bytes, if applicable.
+    fn public_key_length(&self) -> Option<usize> {
+        self.algorithm().public_key_length()
+    }
+
+    /// Get the length of a signature in bytes, if applicable.
+    fn signature_length(&self) -> Option<usize> {
+        self.algorithm().signature_length()
+    }
 }
 
 /// A trait for accessing the backend of a key, used when
@@ -272,9 +334,65 @@ pub trait HasKeyBackend: Debug {
     }
 }
 
+/// Load a keypair from its secret key bytes, checking in constant time that
+/// the public key bytes correspond to the secret key
+#[cfg(any(feature = "ed25519", feature = "k256", feature = "p384"))]
+pub(crate) fn keypair_from_secret_and_public_bytes<K>(
+    sk: &[u8],
+    pk: &[u8],
+    mismatch: &'static str,
+) -> Result<K, Error>
+where
+    K: crate::repr::KeySecretBytes + crate::repr::KeyPublicBytes,
+{
+    let kp = K::from_secret_bytes(sk).map_err(|_| err_msg!(InvalidKeyData))?;
+    if kp
+        .with_public_bytes(|kp_pk| crate::util::ct_eq_slices(kp_pk, pk))
+        .into()
+    {
+        Ok(kp)
+    } else {
+        Err(err_msg!(InvalidKeyData, mismatch))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "getrandom")]
+    use crate::repr::{KeyGen, KeyPublicBytes, KeySecretBytes};
+
+    #[cfg(feature = "getrandom")]
+    #[allow(unused)]
+    fn check_secret_and_public_bytes<K: Debug + KeyGen + KeySecretBytes + KeyPublicBytes>(
+        load: fn(&[u8], &[u8]) -> Result<K, Error>,
+    ) {
+        let kp = K::random().unwrap();
+        let other = K::random().unwrap();
+        kp.with_secret_bytes(|sk| {
+            let sk = sk.unwrap();
+            let cmp = kp.with_public_bytes(|pk| load(sk, pk)).unwrap();
+            kp.with_public_bytes(|pk| cmp.with_public_bytes(|cmp_pk| assert_eq!(pk, cmp_pk)));
+            let err = other.with_public_bytes(|pk| load(sk, pk)).unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::InvalidKeyData);
+        });
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn secret_and_public_bytes() {
+        #[cfg(feature = "ed25519")]
+        {
+            check_secret_and_public_bytes(ed25519::Ed25519KeyPair::from_secret_and_public_bytes);
+            check_secret_and_public_bytes(x25519::X25519KeyPair::from_secret_and_public_bytes);
+        }
+        #[cfg(feature = "k256")]
+        check_secret_and_public_bytes(k256::K256KeyPair::from_secret_and_public_bytes);
+        #[cfg(feature = "p256")]
+        check_secret_and_public_bytes(p256::P256KeyPair::from_secret_and_public_bytes);
+        #[cfg(feature = "p384")]
+        check_secret_and_public_bytes(p384::P384KeyPair::from_secret_and_public_bytes);
+    }
 
     #[test]
     fn cmp_normalize() {
@@ -15,11 +15,12 @@ use p256::{
         ecdh::diffie_hellman,
         sec1::{Coordinates, FromEncodedPoint, ToEncodedPoint},
     },
-    EncodedPoint, PublicKey, SecretKey,
+    EncodedPoint, FieldBytes, NonZeroScalar, PublicKey, Scalar, SecretKey,
 };
-use subtle::ConstantTimeEq;
 
 use super::{ec_common, EcCurves, HasKeyAlg, HasKeyBackend, KeyAlg};
+#[cfg(feature = "cose")]
+use crate::cose::{CoseKeyParts, FromCoseKey, ToCoseKey, ALG_ES256, CRV_P256, KTY_EC2};
 use crate::{
     buffer::{ArrayKey, WriteBuffer},
     error::Error,
@@ -29,6 +30,7 @@ use crate::{
     random::KeyMaterial,
     repr::{KeyGen, KeyMeta, KeyPublicBytes, KeySecretBytes, KeypairBytes, KeypairMeta},
     sign::{KeySigVerify, KeySign, SignatureType},
+    util::ct_eq_slices,
 };
 
 // SECURITY: PublicKey contains a p256::AffinePoint, which is always checked
@@ -74,9 +76,37 @@ impl P256KeyPair {
         }
     }
 
-    pub(crate) fn check_public_bytes(&self, pk: &[u8]) -> Result<(), Error> {
-        if self.with_public_bytes(|slf| slf.ct_eq(pk)).into() {
-            Ok(())
+    /// Create a keypair deterministically from a seed of at least 32 bytes
+    ///
+    /// The secret key is the output of HKDF-SHA256 using the seed as the
+    /// input key material, the salt `ASKAR-SEED-KEYGEN-SALT-`, and the info
+    /// `P-256` followed by a 4-byte big-endian counter. The counter starts
+    /// at zero and is incremented until the output is a valid secret key.
+    #[cfg(feature = "hkdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+    pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        Self::generate(crate::random::SeedKeyGen::new(seed, b"P-256")?)
+    }
+
+    /// Create a keypair from separate secret and public key bytes, checking
+    /// that the public key corresponds to the secret key
+    ///
+    /// An invalid secret key and a mismatched public key produce the same
+    /// error. Both checks are always performed, so the two cases cannot be
+    /// distinguished by the order or timing of the failure.
+    pub fn from_secret_and_public_bytes(sk: &[u8], pk: &[u8]) -> Result<Self, Error> {
+        if sk.len() != SECRET_KEY_LENGTH || pk.len() != PUBLIC_KEY_LENGTH {
+            return Err(err_msg!(InvalidKeyData, "invalid p256 keypair"));
+        }
+        let scalar = NonZeroScalar::from_repr(*FieldBytes::from_slice(sk));
+        let sk_valid = scalar.is_some();
+        // SECURITY: substitute a fixed scalar for an invalid secret key, so that
+        // the public key is always derived and compared
+        let fallback = NonZeroScalar::new(Scalar::ONE).unwrap();
+        let result = Self::from_secret_key(SecretKey::from(scalar.unwrap_or(fallback)));
+        let pk_valid = result.with_public_bytes(|slf| ct_eq_slices(slf, pk));
+        if (sk_valid & pk_valid).into() {
+            Ok(result)
         } else {
             Err(err_msg!(InvalidKeyData, "invalid p256 keypair"))
         }
@@ -182,12 +212,9 @@ impl KeypairMeta for P256KeyPair {
 impl KeypairBytes for P256KeyPair {
     fn from_keypair_bytes(kp: &[u8]) -> Result<Self, Error> {
         if kp.len() != KEYPAIR_LENGTH {
-            return Err(err_msg!(InvalidKeyData));
+            return Err(err_msg!(InvalidKeyData, "invalid p256 keypair"));
         }
-        let result = P256KeyPair::from_secret_bytes(&kp[..SECRET_KEY_LENGTH])
-            .map_err(|_| err_msg!(InvalidKeyData))?;
-        result.check_public_bytes(&kp[SECRET_KEY_LENGTH..])?;
-        Ok(result)
+        Self::from_secret_and_public_bytes(&kp[..SECRET_KEY_LENGTH], &kp[SECRET_KEY_LENGTH..])
     }
 
     fn with_keypair_bytes<O>(&self, f: impl FnOnce(Option<&[u8]>) -> O) -> O {
@@ -282,6 +309,7 @@ impl ToJwk for P256KeyPair {
         enc.add_str("kty", JWK_KEY_TYPE)?;
         enc.add_as_base64("x", &x[..])?;
         enc.add_as_base64("y", &y[..])?;
+        enc.add_sig_alg(&[SignatureType::ES256, SignatureType::ES256ph])?;
         if enc.is_secret() {
             self.with_secret_bytes(|buf| {
                 if let Some(sk) = buf {
@@ -343,6 +371,76 @@ impl FromJwk for P256KeyPair {
     }
 }
 
+#[cfg(feature = "cose")]
+impl ToCoseKey for P256KeyPair {
+    fn write_cose_key(&self, secret: bool, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        let pk_enc = self.public.to_encoded_point(false);
+        let (x, y) = match pk_enc.coordinates() {
+            Coordinates::Identity => {
+                return Err(err_msg!(
+                    Unsupported,
+                    "Cannot convert identity point to COSE_Key"
+                ))
+            }
+            Coordinates::Uncompressed { x, y } => (x, y),
+            Coordinates::Compressed { .. } | Coordinates::Compact { .. } => unreachable!(),
+        };
+        self.with_secret_bytes(|sk| {
+            CoseKeyParts {
+                kty: KTY_EC2,
+                alg: Some(ALG_ES256),
+                crv: Some(CRV_P256),
+                x: Some(&x[..]),
+                y: Some(&y[..]),
+                d: sk.filter(|_| secret),
+                ..Default::default()
+            }
+            .write(out)
+        })
+    }
+}
+
+#[cfg(feature = "cose")]
+impl FromCoseKey for P256KeyPair {
+    fn from_cose_key_parts(cose_key: CoseKeyParts<'_>) -> Result<Self, Error> {
+        if cose_key.kty != KTY_EC2 {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key type"));
+        }
+        if cose_key.crv != Some(CRV_P256)
+            || cose_key.alg.map(|alg| alg != ALG_ES256).unwrap_or(false)
+        {
+            return Err(err_msg!(InvalidKeyData, "Unsupported key algorithm"));
+        }
+        let (pk_x, pk_y) = match (cose_key.x, cose_key.y) {
+            (Some(x), Some(y)) if x.len() == SECRET_KEY_LENGTH && y.len() == SECRET_KEY_LENGTH => {
+                (x, y)
+            }
+            _ => return Err(err_msg!(InvalidKeyData)),
+        };
+        let pk = Option::from(PublicKey::from_encoded_point(
+            &EncodedPoint::from_affine_coordinates(
+                FieldBytes::from_slice(pk_x),
+                FieldBytes::from_slice(pk_y),
+                false,
+            ),
+        ))
+        .ok_or_else(|| err_msg!(InvalidKeyData))?;
+        if let Some(sk) = cose_key.d {
+            let kp = P256KeyPair::from_secret_bytes(sk)?;
+            if kp.public != pk {
+                Err(err_msg!(InvalidKeyData))
+            } else {
+                Ok(kp)
+            }
+        } else {
+            Ok(Self {
+                secret: None,
+                public: pk,
+            })
+        }
+    }
+}
+
 impl KeyExchange for P256KeyPair {
     fn write_key_exchange(&self, other: &Self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
         match self.secret.as_ref() {
@@ -407,6 +505,20 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "hkdf")]
+    #[test]
+    fn from_seed_expected() {
+        use crate::repr::ToSecretBytes;
+
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let kp = P256KeyPair::from_seed(&seed).unwrap();
+        assert_eq!(
+            kp.to_secret_bytes().unwrap(),
+            &hex!("aae0fcf4c3cdbff472feb0478939c1d421e193da6967d3cd9346a12f57e44271")[..]
+        );
+        assert!(P256KeyPair::from_seed(&seed[..31]).is_err());
+    }
+
     #[test]
     fn jwk_thumbprint() {
         let pk = P256KeyPair::from_jwk(
@@ -422,6 +534,7 @@ mod tests {
             pk.to_jwk_thumbprint(None).unwrap(),
             "8fm8079s3nu4FLV_7dVJoJ69A8XCXn7Za2mtaWCnxR4"
         );
+        assert_eq!(pk.to_key_fingerprint().unwrap(), "8fm8079s3ns");
     }
 
     #[test]
@@ -484,4 +597,74 @@ mod tests {
             cmp.to_keypair_bytes().unwrap()
         );
     }
+
+    #[test]
+    fn invalid_keypair_bytes() {
+        let kp = P256KeyPair::random().unwrap();
+        let kp_bytes = kp.to_keypair_bytes().unwrap();
+        let pk = &kp_bytes[SECRET_KEY_LENGTH..];
+        let other = P256KeyPair::random().unwrap().to_public_bytes().unwrap();
+
+        let bad_pk =
+            P256KeyPair::from_secret_and_public_bytes(&kp_bytes[..SECRET_KEY_LENGTH], &other)
+                .unwrap_err();
+        // scalar larger than the curve order
+        let bad_sk =
+            P256KeyPair::from_secret_and_public_bytes(&[0xff; SECRET_KEY_LENGTH], pk).unwrap_err();
+        assert_eq!(bad_pk.kind(), bad_sk.kind());
+        assert_eq!(bad_pk.message(), bad_sk.message());
+    }
+
+    #[cfg(all(feature = "cose", feature = "alloc"))]
+    #[test]
+    fn cose_key_expected() {
+        use crate::repr::ToSecretBytes;
+
+        // from RFC 9052 appendix C.7.2
+        let x = hex!("65eda5a12577c2bae829437fe338701a10aaa375e1bb5b5de108de439c08551d");
+        let y = hex!("1e52ed75701163f7f9e40ddf9f341b3dc9ba860af7e0ca7ca7e9eecd0084d19c");
+        let d = hex!("aff907c99f9ad3aae6c4cdf21122bce2bd68b5283e6907154ad911840fa208cf");
+        let input = hex!(
+            "a6200121582065eda5a12577c2bae829437fe338701a10aaa375e1bb5b5de108de439c08551d"
+            "2258201e52ed75701163f7f9e40ddf9f341b3dc9ba860af7e0ca7ca7e9eecd0084d19c"
+            "235820aff907c99f9ad3aae6c4cdf21122bce2bd68b5283e6907154ad911840fa208cf"
+            "01020258246d65726961646f632e6272616e64796275636b406275636b6c616e642e6578616d706c65"
+        );
+        let parts = CoseKeyParts::from_slice(&input).unwrap();
+        assert_eq!(
+            parts.kid,
+            Some(&b"meriadoc.brandybuck@buckland.example"[..])
+        );
+        let kp = P256KeyPair::from_cose_key_parts(parts).unwrap();
+        assert_eq!(kp.to_secret_bytes().unwrap(), &d[..]);
+
+        let public = kp.to_cose_key_public().unwrap();
+        let mut expected = hex!("a5010203262001215820").to_vec();
+        expected.extend_from_slice(&x);
+        expected.extend_from_slice(&hex!("225820"));
+        expected.extend_from_slice(&y);
+        assert_eq!(public, expected);
+        let secret = kp.to_cose_key_secret().unwrap();
+        expected[0] = 0xa6;
+        expected.extend_from_slice(&hex!("235820"));
+        expected.extend_from_slice(&d);
+        assert_eq!(secret, &expected[..]);
+
+        let pk_load = P256KeyPair::from_cose_key(&public).unwrap();
+        assert_eq!(
+            pk_load.to_public_bytes().unwrap(),
+            kp.to_public_bytes().unwrap()
+        );
+        let sk_load = P256KeyPair::from_cose_key(&secret).unwrap();
+        assert_eq!(
+            sk_load.to_keypair_bytes().unwrap(),
+            kp.to_keypair_bytes().unwrap()
+        );
+
+        // mismatched secret key
+        let mut bad = secret.to_vec();
+        let last = bad.len() - 1;
+        bad[last] ^= 1;
+        assert!(P256KeyPair::from_cose_key(&bad).is_err());
+    }
 }
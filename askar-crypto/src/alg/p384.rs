@@ -17,9 +17,10 @@ use p384::{
     },
     EncodedPoint, PublicKey, SecretKey,
 };
-use subtle::ConstantTimeEq;
 
-use super::{ec_common, EcCurves, HasKeyAlg, HasKeyBackend, KeyAlg};
+use super::{
+    ec_common, keypair_from_secret_and_public_bytes, EcCurves, HasKeyAlg, HasKeyBackend, KeyAlg,
+};
 use crate::{
     buffer::{ArrayKey, WriteBuffer},
     error::Error,
@@ -73,12 +74,10 @@ impl P384KeyPair {
         }
     }
 
-    pub(crate) fn check_public_bytes(&self, pk: &[u8]) -> Result<(), Error> {
-        if self.with_public_bytes(|slf| slf.ct_eq(pk)).into() {
-            Ok(())
-        } else {
-            Err(err_msg!(InvalidKeyData, "invalid p384 keypair"))
-        }
+    /// Create a keypair from separate secret and public key bytes, checking
+    /// that the public key corresponds to the secret key
+    pub fn from_secret_and_public_bytes(sk: &[u8], pk: &[u8]) -> Result<Self, Error> {
+        keypair_from_secret_and_public_bytes(sk, pk, "invalid p384 keypair")
     }
 
     pub(crate) fn to_signing_key(&self) -> Option<SigningKey> {
@@ -185,10 +184,7 @@ impl KeypairBytes for P384KeyPair {
         if kp.len() != KEYPAIR_LENGTH {
             return Err(err_msg!(InvalidKeyData));
         }
-        let result = P384KeyPair::from_secret_bytes(&kp[..SECRET_KEY_LENGTH])
-            .map_err(|_| err_msg!(InvalidKeyData))?;
-        result.check_public_bytes(&kp[SECRET_KEY_LENGTH..])?;
-        Ok(result)
+        Self::from_secret_and_public_bytes(&kp[..SECRET_KEY_LENGTH], &kp[SECRET_KEY_LENGTH..])
     }
 
     fn with_keypair_bytes<O>(&self, f: impl FnOnce(Option<&[u8]>) -> O) -> O {
@@ -283,6 +279,7 @@ impl ToJwk for P384KeyPair {
         enc.add_str("kty", JWK_KEY_TYPE)?;
         enc.add_as_base64("x", &x[..])?;
         enc.add_as_base64("y", &y[..])?;
+        enc.add_sig_alg(&[SignatureType::ES384, SignatureType::ES384ph])?;
         if enc.is_secret() {
             self.with_secret_bytes(|buf| {
                 if let Some(sk) = buf {
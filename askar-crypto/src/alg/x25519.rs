@@ -5,11 +5,12 @@ use core::{
     fmt::{self, Debug, Formatter},
 };
 
-use subtle::ConstantTimeEq;
 use x25519_dalek::{PublicKey, StaticSecret as SecretKey};
 use zeroize::Zeroizing;
 
-use super::{ed25519::Ed25519KeyPair, HasKeyAlg, HasKeyBackend, KeyAlg};
+use super::{
+    ed25519::Ed25519KeyPair, keypair_from_secret_and_public_bytes, HasKeyAlg, HasKeyBackend, KeyAlg,
+};
 use crate::{
     buffer::{ArrayKey, WriteBuffer},
     error::Error,
@@ -62,12 +63,10 @@ impl X25519KeyPair {
         }
     }
 
-    pub(crate) fn check_public_bytes(&self, pk: &[u8]) -> Result<(), Error> {
-        if self.public.as_bytes().ct_eq(pk).into() {
-            Ok(())
-        } else {
-            Err(err_msg!(InvalidKeyData, "invalid x25519 keypair"))
-        }
+    /// Create a keypair from separate secret and public key bytes, checking
+    /// that the public key corresponds to the secret key
+    pub fn from_secret_and_public_bytes(sk: &[u8], pk: &[u8]) -> Result<Self, Error> {
+        keypair_from_secret_and_public_bytes(sk, pk, "invalid x25519 keypair")
     }
 }
 
@@ -134,9 +133,7 @@ impl KeypairBytes for X25519KeyPair {
         if kp.len() != KEYPAIR_LENGTH {
             return Err(err_msg!(InvalidKeyData));
         }
-        let result = Self::from_secret_bytes(&kp[..SECRET_KEY_LENGTH])?;
-        result.check_public_bytes(&kp[SECRET_KEY_LENGTH..])?;
-        Ok(result)
+        Self::from_secret_and_public_bytes(&kp[..SECRET_KEY_LENGTH], &kp[SECRET_KEY_LENGTH..])
     }
 
     fn with_keypair_bytes<O>(&self, f: impl FnOnce(Option<&[u8]>) -> O) -> O {
@@ -197,9 +194,11 @@ impl FromJwk for X25519KeyPair {
                     if jwk.d.decode_base64(sk_arr)? != sk_arr.len() {
                         Err(err_msg!(InvalidKeyData))
                     } else {
-                        let kp = X25519KeyPair::from_secret_bytes(sk_arr)?;
-                        kp.check_public_bytes(pk_arr)?;
-                        Ok(kp)
+                        keypair_from_secret_and_public_bytes(
+                            sk_arr,
+                            pk_arr,
+                            "invalid x25519 keypair",
+                        )
                     }
                 })
             } else {
@@ -226,7 +225,7 @@ impl TryFrom<&Ed25519KeyPair> for X25519KeyPair {
     type Error = Error;
 
     fn try_from(value: &Ed25519KeyPair) -> Result<Self, Self::Error> {
-        Ok(value.to_x25519_keypair())
+        value.try_to_x25519_keypair()
     }
 }
 
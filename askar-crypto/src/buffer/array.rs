@@ -16,9 +16,12 @@ use crate::{
     error::Error,
     kdf::{FromKeyDerivation, KeyDerivation},
     random::KeyMaterial,
+    util::ct_eq_slices,
 };
 
 /// A secure representation for fixed-length keys
+///
+/// The key data is zeroized when the instance is dropped.
 #[derive(Clone)]
 #[repr(transparent)]
 pub struct ArrayKey<L: ArrayLength<u8>>(
@@ -148,7 +151,7 @@ impl<L: ArrayLength<u8>> Debug for ArrayKey<L> {
 
 impl<L: ArrayLength<u8>> ConstantTimeEq for ArrayKey<L> {
     fn ct_eq(&self, other: &Self) -> Choice {
-        ConstantTimeEq::ct_eq(self.0.as_ref(), other.0.as_ref())
+        ct_eq_slices(self.0.as_ref(), other.0.as_ref())
     }
 }
 
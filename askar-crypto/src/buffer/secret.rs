@@ -7,12 +7,17 @@ use core::{
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use subtle::{Choice, ConstantTimeEq};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use super::{string::MaybeStr, HexRepr, ResizeBuffer, WriteBuffer};
-use crate::error::Error;
+use crate::{error::Error, util::ct_eq_slices};
 
 /// A heap-allocated, zeroized byte buffer
+///
+/// The contents are zeroized when the buffer is dropped or cleared, and when
+/// the buffer is reallocated to grow or shrink its capacity. Converting the
+/// buffer with [`SecretBytes::into_vec`] gives up this guarantee, so prefer
+/// [`SecretBytes::into_zeroizing`] when an owned vector is required.
 #[derive(Clone, Default, Zeroize)]
 pub struct SecretBytes(Vec<u8>);
 
@@ -125,6 +130,8 @@ impl SecretBytes {
     }
 
     /// Unwrap this buffer into a Vec<u8>
+    ///
+    /// The returned vector is not zeroized on drop.
     #[inline]
     pub fn into_vec(mut self) -> Vec<u8> {
         // FIXME zeroize extra capacity in case it was used previously?
@@ -133,6 +140,12 @@ impl SecretBytes {
         v
     }
 
+    /// Unwrap this buffer into a Vec<u8> which is zeroized on drop
+    #[inline]
+    pub fn into_zeroizing(self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.into_vec())
+    }
+
     #[cfg(feature = "crypto_box")]
     pub(crate) fn as_vec_mut(&mut self) -> &mut Vec<u8> {
         &mut self.0
@@ -199,7 +212,7 @@ impl Drop for SecretBytes {
 
 impl ConstantTimeEq for SecretBytes {
     fn ct_eq(&self, other: &Self) -> Choice {
-        ConstantTimeEq::ct_eq(self.0.as_slice(), other.0.as_slice())
+        ct_eq_slices(self.0.as_slice(), other.0.as_slice())
     }
 }
 
@@ -332,4 +345,11 @@ mod tests {
     fn resize_buffer_secret() {
         test_resize_buffer(SecretBytes::with_capacity(10));
     }
+
+    #[test]
+    fn into_zeroizing_secret() {
+        let buf = SecretBytes::from_slice(b"secret");
+        let vec = buf.into_zeroizing();
+        assert_eq!(&vec[..], b"secret");
+    }
 }
@@ -40,8 +40,10 @@ impl<B: AsRef<[u8]>> PartialEq<&str> for HexRepr<B> {
 }
 
 /// A utility type for debug printing of byte strings
+#[cfg(feature = "alloc")]
 pub struct MaybeStr<'a>(pub &'a [u8]);
 
+#[cfg(feature = "alloc")]
 impl Debug for MaybeStr<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Ok(sval) = core::str::from_utf8(self.0) {
@@ -68,6 +70,7 @@ mod tests {
         assert_ne!(HexRepr(&[100, 101, 102]), "00ff00ff");
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
     fn maybe_str_output() {
         assert_eq!(format!("{:?}", MaybeStr(&[])), "\"\"");
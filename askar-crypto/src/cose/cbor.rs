@@ -0,0 +1,259 @@
+//! Minimal CBOR encoding and decoding for COSE structures
+
+use crate::{buffer::WriteBuffer, error::Error};
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NINT: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_SIMPLE: u8 = 7;
+
+/// Writes CBOR items in the preferred (shortest) serialization
+#[derive(Debug)]
+pub(crate) struct CborWriter<'w> {
+    out: &'w mut dyn WriteBuffer,
+}
+
+impl<'w> CborWriter<'w> {
+    pub fn new(out: &'w mut dyn WriteBuffer) -> Self {
+        Self { out }
+    }
+
+    fn write_head(&mut self, major: u8, arg: u64) -> Result<(), Error> {
+        let major = major << 5;
+        if arg < 24 {
+            self.out.buffer_write(&[major | arg as u8])
+        } else if arg <= u8::MAX as u64 {
+            self.out.buffer_write(&[major | 24, arg as u8])
+        } else if arg <= u16::MAX as u64 {
+            self.out.buffer_write(&[major | 25])?;
+            self.out.buffer_write(&(arg as u16).to_be_bytes())
+        } else if arg <= u32::MAX as u64 {
+            self.out.buffer_write(&[major | 26])?;
+            self.out.buffer_write(&(arg as u32).to_be_bytes())
+        } else {
+            self.out.buffer_write(&[major | 27])?;
+            self.out.buffer_write(&arg.to_be_bytes())
+        }
+    }
+
+    pub fn write_int(&mut self, value: i64) -> Result<(), Error> {
+        if value < 0 {
+            self.write_head(MAJOR_NINT, (-1 - value) as u64)
+        } else {
+            self.write_head(MAJOR_UINT, value as u64)
+        }
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+        self.write_head(MAJOR_BYTES, value.len() as u64)?;
+        self.out.buffer_write(value)
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn write_text(&mut self, value: &str) -> Result<(), Error> {
+        self.write_head(MAJOR_TEXT, value.len() as u64)?;
+        self.out.buffer_write(value.as_bytes())
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn write_array(&mut self, len: usize) -> Result<(), Error> {
+        self.write_head(MAJOR_ARRAY, len as u64)
+    }
+
+    pub fn write_map(&mut self, len: usize) -> Result<(), Error> {
+        self.write_head(MAJOR_MAP, len as u64)
+    }
+}
+
+/// A decoded CBOR data item which is not a container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CborValue<'r> {
+    Int(i64),
+    Bytes(&'r [u8]),
+    Text(&'r str),
+}
+
+/// Reads CBOR items from a byte slice, rejecting indefinite-length items
+#[derive(Debug)]
+pub(crate) struct CborReader<'r> {
+    data: &'r [u8],
+    pos: usize,
+}
+
+impl<'r> CborReader<'r> {
+    pub fn new(data: &'r [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'r [u8], Error> {
+        if self.data.len() - self.pos < len {
+            return Err(err_msg!(Invalid, "Unexpected end of CBOR input"));
+        }
+        let result = &self.data[self.pos..(self.pos + len)];
+        self.pos += len;
+        Ok(result)
+    }
+
+    fn read_head(&mut self) -> Result<(u8, u64), Error> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        let arg = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            _ => return Err(err_msg!(Invalid, "Unsupported CBOR encoding")),
+        };
+        Ok((major, arg))
+    }
+
+    fn peek_major(&self) -> Option<u8> {
+        self.data.get(self.pos).map(|b| b >> 5)
+    }
+
+    fn read_len(&mut self, major: u8) -> Result<usize, Error> {
+        let (found, len) = self.read_head()?;
+        if found != major {
+            return Err(err_msg!(Invalid, "Unexpected CBOR item type"));
+        }
+        usize::try_from(len).map_err(|_| err_msg!(Invalid, "Invalid CBOR length"))
+    }
+
+    pub fn read_array(&mut self) -> Result<usize, Error> {
+        self.read_len(MAJOR_ARRAY)
+    }
+
+    pub fn read_map(&mut self) -> Result<usize, Error> {
+        self.read_len(MAJOR_MAP)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'r [u8], Error> {
+        let len = self.read_len(MAJOR_BYTES)?;
+        self.take(len)
+    }
+
+    pub fn read_int(&mut self) -> Result<i64, Error> {
+        match self.read_value()? {
+            CborValue::Int(value) => Ok(value),
+            _ => Err(err_msg!(Invalid, "Expected CBOR integer")),
+        }
+    }
+
+    /// Read an integer, byte string or text string value
+    pub fn read_value(&mut self) -> Result<CborValue<'r>, Error> {
+        let (major, arg) = self.read_head()?;
+        match major {
+            MAJOR_UINT => i64::try_from(arg)
+                .map(CborValue::Int)
+                .map_err(|_| err_msg!(Invalid, "CBOR integer out of range")),
+            MAJOR_NINT => i64::try_from(arg)
+                .map(|n| CborValue::Int(-1 - n))
+                .map_err(|_| err_msg!(Invalid, "CBOR integer out of range")),
+            MAJOR_BYTES | MAJOR_TEXT => {
+                let len =
+                    usize::try_from(arg).map_err(|_| err_msg!(Invalid, "Invalid CBOR length"))?;
+                let data = self.take(len)?;
+                if major == MAJOR_BYTES {
+                    Ok(CborValue::Bytes(data))
+                } else {
+                    core::str::from_utf8(data)
+                        .map(CborValue::Text)
+                        .map_err(|_| err_msg!(Invalid, "Invalid CBOR text string"))
+                }
+            }
+            _ => Err(err_msg!(Invalid, "Unexpected CBOR item type")),
+        }
+    }
+
+    /// Skip over a complete data item, including any nested items
+    pub fn skip(&mut self) -> Result<(), Error> {
+        let mut remain = 1usize;
+        while remain > 0 {
+            remain -= 1;
+            match self.peek_major() {
+                Some(MAJOR_ARRAY) => {
+                    let len = self.read_array()?;
+                    remain = remain
+                        .checked_add(len)
+                        .ok_or_else(|| err_msg!(Invalid, "Invalid CBOR length"))?;
+                }
+                Some(MAJOR_MAP) => {
+                    let len = self.read_map()?;
+                    remain = len
+                        .checked_mul(2)
+                        .and_then(|len| remain.checked_add(len))
+                        .ok_or_else(|| err_msg!(Invalid, "Invalid CBOR length"))?;
+                }
+                Some(MAJOR_SIMPLE) => {
+                    let (_, arg) = self.read_head()?;
+                    if arg > 23 {
+                        return Err(err_msg!(Invalid, "Unsupported CBOR simple value"));
+                    }
+                }
+                _ => {
+                    self.read_value()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Writer;
+
+    #[test]
+    fn encode_int_expected() {
+        for (value, expected) in [
+            (0i64, &[0x00][..]),
+            (23, &[0x17]),
+            (24, &[0x18, 0x18]),
+            (1000, &[0x19, 0x03, 0xe8]),
+            (-1, &[0x20]),
+            (-8, &[0x27]),
+            (-100, &[0x38, 0x63]),
+        ] {
+            let mut buf = [0u8; 16];
+            let mut w = Writer::from_slice(&mut buf);
+            CborWriter::new(&mut w).write_int(value).unwrap();
+            assert_eq!(w.as_ref(), expected);
+            let mut r = CborReader::new(expected);
+            assert_eq!(r.read_int().unwrap(), value);
+            assert!(r.is_empty());
+        }
+    }
+
+    #[test]
+    fn read_skip_nested() {
+        // {1: [1, h'00', "a"], 2: {3: true}, 4: -1}
+        let data = hex!("a301830141006161 02a103f5 0420");
+        let mut r = CborReader::new(&data);
+        assert_eq!(r.read_map().unwrap(), 3);
+        assert_eq!(r.read_int().unwrap(), 1);
+        r.skip().unwrap();
+        assert_eq!(r.read_int().unwrap(), 2);
+        r.skip().unwrap();
+        assert_eq!(r.read_int().unwrap(), 4);
+        assert_eq!(r.read_value().unwrap(), CborValue::Int(-1));
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn read_truncated() {
+        let mut r = CborReader::new(&hex!("5820"));
+        assert!(r.read_bytes().is_err());
+        let mut r = CborReader::new(&hex!("5f"));
+        assert!(r.read_bytes().is_err());
+    }
+}
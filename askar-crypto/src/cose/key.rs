@@ -0,0 +1,181 @@
+use core::fmt::{self, Debug, Formatter};
+
+use super::{
+    cbor::{CborReader, CborValue, CborWriter},
+    KTY_SYMMETRIC,
+};
+use crate::{buffer::WriteBuffer, error::Error};
+
+const LABEL_KTY: i64 = 1;
+const LABEL_KID: i64 = 2;
+const LABEL_ALG: i64 = 3;
+const LABEL_CRV_K: i64 = -1;
+const LABEL_X: i64 = -2;
+const LABEL_Y: i64 = -3;
+const LABEL_D: i64 = -4;
+
+/// A parsed COSE_Key
+///
+/// The label `-1` holds the curve identifier for curve keys and the key
+/// bytes for symmetric keys, and is parsed into `crv` or `k` according
+/// to the key type.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoseKeyParts<'a> {
+    /// Key type
+    pub kty: i64,
+    /// Key ID
+    pub kid: Option<&'a [u8]>,
+    /// Key algorithm
+    pub alg: Option<i64>,
+    /// Curve identifier
+    pub crv: Option<i64>,
+    /// Curve key public x coordinate
+    pub x: Option<&'a [u8]>,
+    /// Curve key public y coordinate
+    pub y: Option<&'a [u8]>,
+    /// Curve key private key bytes
+    pub d: Option<&'a [u8]>,
+    /// Symmetric key bytes
+    pub k: Option<&'a [u8]>,
+}
+
+impl<'a> CoseKeyParts<'a> {
+    /// Parse a COSE_Key from a byte slice
+    ///
+    /// Unrecognized parameters are ignored.
+    pub fn from_slice(cose_key: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = CborReader::new(cose_key);
+        let mut result = Self::default();
+        let mut kty = None;
+        let mut crv_k = None;
+        let count = reader.read_map()?;
+        for _ in 0..count {
+            let label = match reader.read_value()? {
+                CborValue::Int(label) => label,
+                _ => {
+                    reader.skip()?;
+                    continue;
+                }
+            };
+            match label {
+                LABEL_KTY => set_once(&mut kty, reader.read_int()?)?,
+                LABEL_KID => set_once(&mut result.kid, reader.read_bytes()?)?,
+                LABEL_ALG => set_once(&mut result.alg, reader.read_int()?)?,
+                LABEL_CRV_K => set_once(&mut crv_k, reader.read_value()?)?,
+                LABEL_X => set_once(&mut result.x, reader.read_bytes()?)?,
+                LABEL_Y => set_once(&mut result.y, reader.read_bytes()?)?,
+                LABEL_D => set_once(&mut result.d, reader.read_bytes()?)?,
+                _ => reader.skip()?,
+            }
+        }
+        if !reader.is_empty() {
+            return Err(err_msg!(Invalid, "Unexpected trailing data after COSE_Key"));
+        }
+        result.kty = kty.ok_or_else(|| err_msg!(Invalid, "Missing COSE_Key type"))?;
+        match (result.kty, crv_k) {
+            (_, None) => (),
+            (KTY_SYMMETRIC, Some(CborValue::Bytes(k))) => result.k = Some(k),
+            (kty, Some(CborValue::Int(crv))) if kty != KTY_SYMMETRIC => result.crv = Some(crv),
+            _ => return Err(err_msg!(Invalid, "Invalid COSE_Key parameter")),
+        }
+        Ok(result)
+    }
+
+    /// Write the COSE_Key to a buffer
+    ///
+    /// Parameters are written in the deterministic encoding order defined by
+    /// RFC 8949 section 4.2.1.
+    pub fn write(&self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
+        let symmetric = self.kty == KTY_SYMMETRIC;
+        let has_crv_k = if symmetric {
+            self.k.is_some()
+        } else {
+            self.crv.is_some()
+        };
+        let count = 1
+            + self.kid.is_some() as usize
+            + self.alg.is_some() as usize
+            + has_crv_k as usize
+            + self.x.is_some() as usize
+            + self.y.is_some() as usize
+            + self.d.is_some() as usize;
+        let mut w = CborWriter::new(out);
+        w.write_map(count)?;
+        w.write_int(LABEL_KTY)?;
+        w.write_int(self.kty)?;
+        if let Some(kid) = self.kid {
+            w.write_int(LABEL_KID)?;
+            w.write_bytes(kid)?;
+        }
+        if let Some(alg) = self.alg {
+            w.write_int(LABEL_ALG)?;
+            w.write_int(alg)?;
+        }
+        if symmetric {
+            if let Some(k) = self.k {
+                w.write_int(LABEL_CRV_K)?;
+                w.write_bytes(k)?;
+            }
+        } else if let Some(crv) = self.crv {
+            w.write_int(LABEL_CRV_K)?;
+            w.write_int(crv)?;
+        }
+        for (label, value) in [(LABEL_X, self.x), (LABEL_Y, self.y), (LABEL_D, self.d)] {
+            if let Some(value) = value {
+                w.write_int(label)?;
+                w.write_bytes(value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for CoseKeyParts<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoseKeyParts")
+            .field("kty", &self.kty)
+            .field("kid", &self.kid)
+            .field("alg", &self.alg)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &self.d.map(|_| "<secret>"))
+            .field("k", &self.k.map(|_| "<secret>"))
+            .finish()
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, value: T) -> Result<(), Error> {
+    if field.replace(value).is_some() {
+        Err(err_msg!(Invalid, "Duplicate COSE_Key parameter"))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_skip_unknown() {
+        // {1: 4, -1: h'0102', "x": [1, 2], 4: [1]}
+        let data = hex!("a4 0104 20420102 617882 0102 0481 01");
+        let parts = CoseKeyParts::from_slice(&data).unwrap();
+        assert_eq!(parts.kty, KTY_SYMMETRIC);
+        assert_eq!(parts.k, Some(&[1u8, 2][..]));
+        assert_eq!(parts.crv, None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        // missing key type
+        assert!(CoseKeyParts::from_slice(&hex!("a1 2001")).is_err());
+        // duplicate label
+        assert!(CoseKeyParts::from_slice(&hex!("a2 0101 0101")).is_err());
+        // symmetric key with an integer key value
+        assert!(CoseKeyParts::from_slice(&hex!("a2 0104 2001")).is_err());
+        // trailing data
+        assert!(CoseKeyParts::from_slice(&hex!("a1 0104 00")).is_err());
+    }
+}
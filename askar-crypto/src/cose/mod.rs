@@ -0,0 +1,86 @@
+//! CBOR Object Signing and Encryption (COSE) support
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::buffer::SecretBytes;
+use crate::{buffer::WriteBuffer, error::Error};
+
+pub(crate) mod cbor;
+
+mod key;
+pub use self::key::CoseKeyParts;
+
+#[cfg(feature = "alloc")]
+mod sign;
+#[cfg(feature = "alloc")]
+pub use self::sign::{sign_cose_sign1, verify_cose_sign1, CoseSign1};
+
+/// The COSE key type of an octet key pair (OKP)
+pub const KTY_OKP: i64 = 1;
+/// The COSE key type of a double-coordinate elliptic curve key (EC2)
+pub const KTY_EC2: i64 = 2;
+/// The COSE key type of a symmetric key
+pub const KTY_SYMMETRIC: i64 = 4;
+
+/// The COSE curve identifier for P-256
+pub const CRV_P256: i64 = 1;
+/// The COSE curve identifier for Ed25519
+pub const CRV_ED25519: i64 = 6;
+
+/// The COSE algorithm identifier for ECDSA with SHA-256
+pub const ALG_ES256: i64 = -7;
+/// The COSE algorithm identifier for EdDSA
+pub const ALG_EDDSA: i64 = -8;
+
+/// Get the COSE algorithm identifier for a symmetric key algorithm, if one
+/// is registered
+#[cfg(any(feature = "aes", feature = "chacha"))]
+pub(crate) fn symmetric_alg(alg: crate::alg::KeyAlg) -> Option<i64> {
+    use crate::alg::{AesTypes, Chacha20Types, KeyAlg};
+    match alg {
+        KeyAlg::Aes(AesTypes::A128Gcm) => Some(1),
+        KeyAlg::Aes(AesTypes::A256Gcm) => Some(3),
+        KeyAlg::Aes(AesTypes::A128Kw) => Some(-3),
+        KeyAlg::Aes(AesTypes::A256Kw) => Some(-5),
+        KeyAlg::Chacha20(Chacha20Types::C20P) => Some(24),
+        _ => None,
+    }
+}
+
+/// Support for converting a key into a COSE_Key
+pub trait ToCoseKey {
+    /// Write the COSE_Key representation to a buffer. The secret key
+    /// parameter is only included when `secret` is set.
+    fn write_cose_key(&self, secret: bool, out: &mut dyn WriteBuffer) -> Result<(), Error>;
+
+    /// Create a COSE_Key of the public key
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    fn to_cose_key_public(&self) -> Result<Vec<u8>, Error> {
+        let mut v = Vec::with_capacity(128);
+        self.write_cose_key(false, &mut v)?;
+        Ok(v)
+    }
+
+    /// Create a COSE_Key of the secret key
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    fn to_cose_key_secret(&self) -> Result<SecretBytes, Error> {
+        let mut v = SecretBytes::with_capacity(128);
+        self.write_cose_key(true, &mut v)?;
+        Ok(v)
+    }
+}
+
+/// Support for loading a key instance from a COSE_Key
+pub trait FromCoseKey: Sized {
+    /// Import the key from an encoded COSE_Key
+    fn from_cose_key(cose_key: &[u8]) -> Result<Self, Error> {
+        CoseKeyParts::from_slice(cose_key).and_then(Self::from_cose_key_parts)
+    }
+
+    /// Import the key from a pre-parsed COSE_Key
+    fn from_cose_key_parts(cose_key: CoseKeyParts<'_>) -> Result<Self, Error>;
+}
@@ -0,0 +1,239 @@
+use alloc::vec::Vec;
+
+use super::{
+    cbor::{CborReader, CborValue, CborWriter},
+    ALG_EDDSA, ALG_ES256,
+};
+use crate::{
+    alg::{EcCurves, HasKeyAlg, KeyAlg},
+    error::Error,
+    sign::{KeySigVerify, KeySign, SignatureType},
+};
+
+/// The CBOR tag identifying a COSE_Sign1 structure
+const TAG_COSE_SIGN1: u8 = 0xd2;
+
+const HEADER_ALG: i64 = 1;
+
+/// Sign a payload, producing an untagged COSE_Sign1 structure
+///
+/// The `protected_headers` must be an encoded CBOR map. When provided, it
+/// must include an `alg` (1) parameter matching the signing key. When
+/// omitted, a protected header containing only the `alg` parameter is used.
+/// The unprotected header map is always empty.
+pub fn sign_cose_sign1<K: KeySign + HasKeyAlg + ?Sized>(
+    key: &K,
+    protected_headers: Option<&[u8]>,
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let (sig_type, alg) = cose_sig_alg(key.algorithm())?;
+    let mut default_headers = Vec::new();
+    let protected = match protected_headers {
+        Some(headers) => {
+            if header_alg(headers)? != Some(alg) {
+                return Err(err_msg!(
+                    Invalid,
+                    "Protected header algorithm does not match the signing key"
+                ));
+            }
+            headers
+        }
+        None => {
+            let mut w = CborWriter::new(&mut default_headers);
+            w.write_map(1)?;
+            w.write_int(HEADER_ALG)?;
+            w.write_int(alg)?;
+            &default_headers[..]
+        }
+    };
+    let tbs = sig_structure(protected, payload, external_aad)?;
+    let mut signature = Vec::with_capacity(64);
+    key.write_signature(&tbs, Some(sig_type), &mut signature)?;
+
+    let mut out = Vec::with_capacity(protected.len() + payload.len() + signature.len() + 8);
+    let mut w = CborWriter::new(&mut out);
+    w.write_array(4)?;
+    w.write_bytes(protected)?;
+    w.write_map(0)?;
+    w.write_bytes(payload)?;
+    w.write_bytes(&signature)?;
+    Ok(out)
+}
+
+/// Verify a COSE_Sign1 structure, returning `true` if the signature is valid
+pub fn verify_cose_sign1<K: KeySigVerify + HasKeyAlg + ?Sized>(
+    key: &K,
+    cose_sign1: &[u8],
+    external_aad: &[u8],
+) -> Result<bool, Error> {
+    CoseSign1::from_slice(cose_sign1)?.verify(key, external_aad)
+}
+
+/// A parsed COSE_Sign1 structure
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoseSign1<'a> {
+    /// The encoded protected header map
+    pub protected: &'a [u8],
+    /// The message payload
+    pub payload: &'a [u8],
+    /// The signature bytes
+    pub signature: &'a [u8],
+}
+
+impl<'a> CoseSign1<'a> {
+    /// Parse a tagged or untagged COSE_Sign1 structure from a byte slice
+    ///
+    /// Detached payloads are not supported.
+    pub fn from_slice(cose_sign1: &'a [u8]) -> Result<Self, Error> {
+        let data = cose_sign1
+            .strip_prefix(&[TAG_COSE_SIGN1])
+            .unwrap_or(cose_sign1);
+        let mut reader = CborReader::new(data);
+        if reader.read_array()? != 4 {
+            return Err(err_msg!(Invalid, "Invalid COSE_Sign1 structure"));
+        }
+        let protected = reader.read_bytes()?;
+        // unprotected header parameters are not used
+        for _ in 0..reader.read_map()? {
+            reader.skip()?;
+            reader.skip()?;
+        }
+        let payload = reader.read_bytes()?;
+        let signature = reader.read_bytes()?;
+        if !reader.is_empty() {
+            return Err(err_msg!(
+                Invalid,
+                "Unexpected trailing data after COSE_Sign1"
+            ));
+        }
+        Ok(Self {
+            protected,
+            payload,
+            signature,
+        })
+    }
+
+    /// Get the `alg` parameter of the protected header
+    pub fn alg(&self) -> Result<Option<i64>, Error> {
+        header_alg(self.protected)
+    }
+
+    /// Verify the signature, returning `true` if it is valid
+    ///
+    /// The `alg` parameter of the protected header must be present. If it
+    /// does not correspond to the verification key, the signature is rejected.
+    pub fn verify<K: KeySigVerify + HasKeyAlg + ?Sized>(
+        &self,
+        key: &K,
+        external_aad: &[u8],
+    ) -> Result<bool, Error> {
+        let (sig_type, alg) = cose_sig_alg(key.algorithm())?;
+        match self.alg()? {
+            None => Err(err_msg!(Invalid, "Missing COSE_Sign1 algorithm")),
+            Some(found) if found != alg => Ok(false),
+            Some(_) => {
+                let tbs = sig_structure(self.protected, self.payload, external_aad)?;
+                key.verify_signature(&tbs, self.signature, Some(sig_type))
+            }
+        }
+    }
+}
+
+fn cose_sig_alg(key_alg: KeyAlg) -> Result<(SignatureType, i64), Error> {
+    match key_alg {
+        KeyAlg::Ed25519 => Ok((SignatureType::EdDSA, ALG_EDDSA)),
+        KeyAlg::EcCurve(EcCurves::Secp256r1) => Ok((SignatureType::ES256, ALG_ES256)),
+        _ => Err(err_msg!(
+            Unsupported,
+            "Unsupported key algorithm for COSE signatures"
+        )),
+    }
+}
+
+/// Find the `alg` parameter in an encoded header map
+fn header_alg(headers: &[u8]) -> Result<Option<i64>, Error> {
+    if headers.is_empty() {
+        return Ok(None);
+    }
+    let mut reader = CborReader::new(headers);
+    let mut alg = None;
+    for _ in 0..reader.read_map()? {
+        match reader.read_value()? {
+            CborValue::Int(HEADER_ALG) if alg.is_none() => alg = Some(reader.read_int()?),
+            CborValue::Int(HEADER_ALG) => {
+                return Err(err_msg!(Invalid, "Duplicate COSE header parameter"))
+            }
+            _ => reader.skip()?,
+        }
+    }
+    if !reader.is_empty() {
+        return Err(err_msg!(Invalid, "Invalid COSE header map"));
+    }
+    Ok(alg)
+}
+
+/// Construct the `Sig_structure` for a COSE_Sign1 signature
+fn sig_structure(protected: &[u8], payload: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut tbs = Vec::with_capacity(protected.len() + payload.len() + external_aad.len() + 20);
+    let mut w = CborWriter::new(&mut tbs);
+    w.write_array(4)?;
+    w.write_text("Signature1")?;
+    w.write_bytes(protected)?;
+    w.write_bytes(external_aad)?;
+    w.write_bytes(payload)?;
+    Ok(tbs)
+}
+
+#[cfg(all(test, feature = "ed25519", feature = "ec_curves"))]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{ed25519::Ed25519KeyPair, p256::P256KeyPair},
+        cose::FromCoseKey,
+        repr::KeyGen,
+    };
+
+    #[test]
+    fn verify_sign1_expected() {
+        // from RFC 9052 appendix C.2.1
+        let input = hex!(
+            "d28443a10126a10442313154546869732069732074686520636f6e74656e742e"
+            "58408eb33e4ca31d1c465ab05aac34cc6b23d58fef5c083106c4d25a91aef0b0"
+            "117e2af9a291aa32e14ab834dc56ed2a223444547e01f11d3b0916e5a4c345ca"
+            "cb36"
+        );
+        let key = P256KeyPair::from_cose_key(&hex!(
+            "a401022001215820bac5b11cad8f99f9c72b05cf4b9e26d244dc189f745228255a219a86d6a09eff"
+            "22582020138bf82dc1b6d562be0fa54ab7804a3a64b6d72ccfed6b6fb6ed28bbfc117e"
+        ))
+        .unwrap();
+        let parsed = CoseSign1::from_slice(&input).unwrap();
+        assert_eq!(parsed.payload, b"This is the content.");
+        assert_eq!(parsed.alg().unwrap(), Some(ALG_ES256));
+        assert!(parsed.verify(&key, &[]).unwrap());
+        assert!(!parsed.verify(&key, b"aad").unwrap());
+    }
+
+    #[test]
+    fn sign1_round_trip_aad() {
+        fn check<K: KeySign + KeyGen + HasKeyAlg>() {
+            let key = K::random().unwrap();
+            let aad = b"external aad";
+            let signed = sign_cose_sign1(&key, None, b"payload", aad).unwrap();
+            assert_eq!(CoseSign1::from_slice(&signed).unwrap().payload, b"payload");
+            assert!(verify_cose_sign1(&key, &signed, aad).unwrap());
+            assert!(!verify_cose_sign1(&key, &signed, b"other aad").unwrap());
+            assert!(!verify_cose_sign1(&K::random().unwrap(), &signed, aad).unwrap());
+        }
+        check::<Ed25519KeyPair>();
+        check::<P256KeyPair>();
+
+        // the protected header algorithm must match the key
+        let key = Ed25519KeyPair::random().unwrap();
+        let signed = sign_cose_sign1(&key, Some(&hex!("a201270442 3131")), b"", b"").unwrap();
+        assert!(verify_cose_sign1(&key, &signed, b"").unwrap());
+        assert!(sign_cose_sign1(&key, Some(&hex!("a10126")), b"", b"").is_err());
+        assert!(!verify_cose_sign1(&P256KeyPair::random().unwrap(), &signed, b"").unwrap());
+    }
+}
@@ -0,0 +1,384 @@
+//! Hierarchical deterministic key derivation following SLIP-0010
+//!
+//! Child keys are derived from a seed and a derivation path such as
+//! `m/44'/0'/0'`, where a trailing `'` (or `h`) marks a hardened index.
+//! Ed25519 supports hardened derivation only, while the elliptic curve types
+//! support both hardened and non-hardened derivation.
+
+use hmac::{
+    digest::{generic_array::GenericArray, FixedOutput},
+    Hmac, Mac,
+};
+use sha2::Sha512;
+
+use crate::{
+    buffer::ArrayKey,
+    error::Error,
+    generic_array::typenum::{U32, U64},
+    repr::KeySecretBytes,
+};
+
+#[cfg(feature = "ed25519")]
+use crate::alg::ed25519::Ed25519KeyPair;
+#[cfg(feature = "ec_curves")]
+use crate::{
+    alg::{k256::K256KeyPair, p256::P256KeyPair},
+    repr::KeyPublicBytes,
+};
+
+/// The first hardened child index
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Supported curves for SLIP-0010 derivation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Slip10Curve {
+    /// Ed25519, supporting hardened derivation only
+    #[cfg(feature = "ed25519")]
+    Ed25519,
+    /// K-256 (secp256k1), compatible with BIP-0032
+    #[cfg(feature = "ec_curves")]
+    K256,
+    /// P-256 (nist256p1)
+    #[cfg(feature = "ec_curves")]
+    P256,
+}
+
+impl Slip10Curve {
+    fn seed_key(&self) -> &'static [u8] {
+        match self {
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519 => b"ed25519 seed",
+            #[cfg(feature = "ec_curves")]
+            Self::K256 => b"Bitcoin seed",
+            #[cfg(feature = "ec_curves")]
+            Self::P256 => b"Nist256p1 seed",
+        }
+    }
+
+    /// Check that a derived value is a valid secret key. For the elliptic
+    /// curve types, the result is the sum of `parent` and `derived` modulo
+    /// the curve order.
+    #[cfg_attr(not(feature = "ec_curves"), allow(unused_variables))]
+    fn combine_secret(&self, parent: Option<&[u8]>, derived: &[u8]) -> Option<ArrayKey<U32>> {
+        match self {
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519 => Some(ArrayKey::from_slice(derived)),
+            #[cfg(feature = "ec_curves")]
+            Self::K256 => ec::combine_k256(parent, derived),
+            #[cfg(feature = "ec_curves")]
+            Self::P256 => ec::combine_p256(parent, derived),
+        }
+    }
+
+    #[cfg_attr(not(feature = "ec_curves"), allow(unused_variables))]
+    fn write_public_key(&self, secret: &[u8], mac: &mut Hmac<Sha512>) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519 => Err(err_msg!(
+                Unsupported,
+                "Non-hardened derivation is not supported for ed25519"
+            )),
+            #[cfg(feature = "ec_curves")]
+            Self::K256 => {
+                K256KeyPair::from_secret_bytes(secret)?.with_public_bytes(|pk| mac.update(pk));
+                Ok(())
+            }
+            #[cfg(feature = "ec_curves")]
+            Self::P256 => {
+                P256KeyPair::from_secret_bytes(secret)?.with_public_bytes(|pk| mac.update(pk));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Key types which may be derived using SLIP-0010
+pub trait Slip10Key: KeySecretBytes {
+    /// The curve used for derivation
+    const SLIP10_CURVE: Slip10Curve;
+}
+
+#[cfg(feature = "ed25519")]
+impl Slip10Key for Ed25519KeyPair {
+    const SLIP10_CURVE: Slip10Curve = Slip10Curve::Ed25519;
+}
+
+#[cfg(feature = "ec_curves")]
+impl Slip10Key for K256KeyPair {
+    const SLIP10_CURVE: Slip10Curve = Slip10Curve::K256;
+}
+
+#[cfg(feature = "ec_curves")]
+impl Slip10Key for P256KeyPair {
+    const SLIP10_CURVE: Slip10Curve = Slip10Curve::P256;
+}
+
+/// Derive a keypair from a seed and a derivation path such as `m/44'/0'/0'`
+pub fn derive_key<K: Slip10Key>(seed: &[u8], path: &str) -> Result<K, Error> {
+    let ext = ExtendedSecretKey::from_seed(K::SLIP10_CURVE, seed)?.derive_path(path)?;
+    K::from_secret_bytes(ext.secret_bytes())
+}
+
+/// A secret key and chain code in a SLIP-0010 derivation
+#[derive(Clone, Debug)]
+pub struct ExtendedSecretKey {
+    curve: Slip10Curve,
+    secret: ArrayKey<U32>,
+    chain_code: ArrayKey<U32>,
+}
+
+impl ExtendedSecretKey {
+    /// Derive the master key for a curve from a seed
+    pub fn from_seed(curve: Slip10Curve, seed: &[u8]) -> Result<Self, Error> {
+        let mut output = hmac_sha512(curve.seed_key(), &[seed])?;
+        loop {
+            let (derived, chain_code) = output.split_at(32);
+            if let Some(secret) = curve.combine_secret(None, derived) {
+                return Ok(Self {
+                    curve,
+                    secret,
+                    chain_code: ArrayKey::from_slice(chain_code),
+                });
+            }
+            output = hmac_sha512(curve.seed_key(), &[&output[..]])?;
+        }
+    }
+
+    /// Derive a child key. Indexes at or above [`HARDENED_OFFSET`] are hardened.
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        let index_bytes = index.to_be_bytes();
+        let mut output = if index >= HARDENED_OFFSET {
+            hmac_sha512(&self.chain_code, &[&[0u8], &self.secret, &index_bytes])?
+        } else {
+            let mut mac = new_hmac(&self.chain_code)?;
+            self.curve.write_public_key(&self.secret, &mut mac)?;
+            mac.update(&index_bytes);
+            finalize_hmac(mac)
+        };
+        loop {
+            let (derived, chain_code) = output.split_at(32);
+            if let Some(secret) = self.curve.combine_secret(Some(&self.secret), derived) {
+                return Ok(Self {
+                    curve: self.curve,
+                    secret,
+                    chain_code: ArrayKey::from_slice(chain_code),
+                });
+            }
+            output = hmac_sha512(&self.chain_code, &[&[1u8], chain_code, &index_bytes])?;
+        }
+    }
+
+    /// Derive a descendant key from a path such as `m/44'/0'/0'`
+    pub fn derive_path(&self, path: &str) -> Result<Self, Error> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(err_msg!(Invalid, "Derivation path must start with 'm'"));
+        }
+        let mut key = self.clone();
+        for part in parts {
+            key = key.derive_child(parse_path_index(part)?)?;
+        }
+        Ok(key)
+    }
+
+    /// Accessor for the curve used in derivation
+    pub fn curve(&self) -> Slip10Curve {
+        self.curve
+    }
+
+    /// Accessor for the secret key bytes
+    pub fn secret_bytes(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Accessor for the chain code
+    pub fn chain_code(&self) -> &[u8] {
+        &self.chain_code
+    }
+}
+
+fn parse_path_index(part: &str) -> Result<u32, Error> {
+    let (index, hardened) = match part.strip_suffix(['\'', 'h', 'H']) {
+        Some(index) => (index, true),
+        None => (part, false),
+    };
+    let index: u32 = index
+        .parse()
+        .map_err(|_| err_msg!(Invalid, "Invalid derivation path index"))?;
+    if index >= HARDENED_OFFSET {
+        return Err(err_msg!(Invalid, "Derivation path index out of range"));
+    }
+    Ok(if hardened {
+        index + HARDENED_OFFSET
+    } else {
+        index
+    })
+}
+
+#[inline]
+fn new_hmac(key: &[u8]) -> Result<Hmac<Sha512>, Error> {
+    Hmac::<Sha512>::new_from_slice(key).map_err(|_| err_msg!(Unexpected, "Error creating HMAC"))
+}
+
+#[inline]
+fn finalize_hmac(mac: Hmac<Sha512>) -> ArrayKey<U64> {
+    ArrayKey::new_with(|buf| mac.finalize_into(GenericArray::from_mut_slice(buf)))
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Result<ArrayKey<U64>, Error> {
+    let mut mac = new_hmac(key)?;
+    for d in data {
+        mac.update(d);
+    }
+    Ok(finalize_hmac(mac))
+}
+
+#[cfg(feature = "ec_curves")]
+mod ec {
+    use elliptic_curve::{ff::PrimeField, Field};
+
+    use crate::{buffer::ArrayKey, generic_array::typenum::U32};
+
+    macro_rules! combine_scalar {
+        ($name:ident, $curve:ident) => {
+            pub(super) fn $name(parent: Option<&[u8]>, derived: &[u8]) -> Option<ArrayKey<U32>> {
+                let derived: Option<$curve::Scalar> =
+                    $curve::Scalar::from_repr(*$curve::FieldBytes::from_slice(derived)).into();
+                let mut secret = derived?;
+                if let Some(parent) = parent {
+                    let parent: Option<$curve::Scalar> =
+                        $curve::Scalar::from_repr(*$curve::FieldBytes::from_slice(parent)).into();
+                    secret += parent?;
+                }
+                if bool::from(secret.is_zero()) {
+                    None
+                } else {
+                    Some(ArrayKey::from(secret.to_repr()))
+                }
+            }
+        };
+    }
+
+    combine_scalar!(combine_k256, k256);
+    combine_scalar!(combine_p256, p256);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::{ToPublicBytes, ToSecretBytes};
+
+    const SEED_1: &[u8] = &hex!("000102030405060708090a0b0c0d0e0f");
+
+    fn check_vector(curve: Slip10Curve, path: &str, chain_code: &[u8], secret: &[u8]) {
+        let key = ExtendedSecretKey::from_seed(curve, SEED_1)
+            .unwrap()
+            .derive_path(path)
+            .unwrap();
+        assert_eq!(key.chain_code(), chain_code);
+        assert_eq!(key.secret_bytes(), secret);
+    }
+
+    #[test]
+    fn parse_path() {
+        assert_eq!(parse_path_index("0").unwrap(), 0);
+        assert_eq!(parse_path_index("44'").unwrap(), 44 + HARDENED_OFFSET);
+        assert_eq!(parse_path_index("44h").unwrap(), 44 + HARDENED_OFFSET);
+        assert!(parse_path_index("").is_err());
+        assert!(parse_path_index("x").is_err());
+        assert!(parse_path_index("2147483648").is_err());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn ed25519_vectors() {
+        // SLIP-0010 test vector 1 for ed25519
+        check_vector(
+            Slip10Curve::Ed25519,
+            "m",
+            &hex!("90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"),
+            &hex!("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"),
+        );
+        check_vector(
+            Slip10Curve::Ed25519,
+            "m/0'",
+            &hex!("8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69"),
+            &hex!("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"),
+        );
+        check_vector(
+            Slip10Curve::Ed25519,
+            "m/0'/1'/2'/2'/1000000000'",
+            &hex!("68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230"),
+            &hex!("8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793"),
+        );
+
+        let kp = derive_key::<Ed25519KeyPair>(SEED_1, "m/0'").unwrap();
+        assert_eq!(
+            kp.to_secret_bytes().unwrap(),
+            &hex!("68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3")[..]
+        );
+        assert_eq!(
+            kp.to_public_bytes().unwrap(),
+            &hex!("8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c")[..]
+        );
+
+        let err = derive_key::<Ed25519KeyPair>(SEED_1, "m/0'/1").unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unsupported);
+        assert!(derive_key::<Ed25519KeyPair>(SEED_1, "0'").is_err());
+        assert!(derive_key::<Ed25519KeyPair>(SEED_1, "m/").is_err());
+    }
+
+    #[cfg(feature = "ec_curves")]
+    #[test]
+    fn k256_vectors() {
+        // SLIP-0010 test vector 1 for secp256k1
+        check_vector(
+            Slip10Curve::K256,
+            "m",
+            &hex!("873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"),
+            &hex!("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"),
+        );
+        check_vector(
+            Slip10Curve::K256,
+            "m/0'",
+            &hex!("47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141"),
+            &hex!("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"),
+        );
+        check_vector(
+            Slip10Curve::K256,
+            "m/0'/1/2'/2/1000000000",
+            &hex!("c783e67b921d2beb8f6b389cc646d7263b4145701dadd2161548a8b078e65e9e"),
+            &hex!("471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"),
+        );
+
+        let kp = derive_key::<K256KeyPair>(SEED_1, "m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(
+            kp.to_public_bytes().unwrap(),
+            &hex!("022a471424da5e657499d1ff51cb43c47481a03b1e77f951fe64cec9f5a48f7011")[..]
+        );
+    }
+
+    #[cfg(feature = "ec_curves")]
+    #[test]
+    fn p256_vectors() {
+        // SLIP-0010 test vector 1 for nist256p1
+        check_vector(
+            Slip10Curve::P256,
+            "m",
+            &hex!("beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea"),
+            &hex!("612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2"),
+        );
+        check_vector(
+            Slip10Curve::P256,
+            "m/0'/1/2'/2/1000000000",
+            &hex!("b9b7b82d326bb9cb5b5b121066feea4eb93d5241103c9e7a18aad40f1dde8059"),
+            &hex!("21c4f269ef0a5fd1badf47eeacebeeaa3de22eb8e5b0adcd0f27dd99d34d0119"),
+        );
+
+        let kp = derive_key::<P256KeyPair>(SEED_1, "m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(
+            kp.to_public_bytes().unwrap(),
+            &hex!("02216cd26d31147f72427a453c443ed2cde8a1e53c9cc44e5ddf739725413fe3f4")[..]
+        );
+    }
+}
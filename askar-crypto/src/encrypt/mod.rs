@@ -2,7 +2,7 @@
 
 use crate::{buffer::ResizeBuffer, error::Error, generic_array::ArrayLength};
 
-#[cfg(feature = "getrandom")]
+#[cfg(any(feature = "getrandom", feature = "hkdf"))]
 use crate::generic_array::GenericArray;
 
 #[cfg(feature = "crypto_box")]
@@ -53,6 +53,50 @@ pub trait KeyAeadMeta {
     }
 }
 
+/// The maximum number of nonces which may be derived for an AEAD with a nonce
+/// of 96 bits or less
+pub const MAX_DERIVED_NONCES_96: u64 = 1 << 32;
+
+/// Derive a nonce for the AEAD key type `K` from a secret key, a context
+/// value and a message counter.
+///
+/// The nonce is expanded from the secret `key` using HKDF-SHA256, with the
+/// counter and context as the info parameter. The same inputs always produce
+/// the same nonce, so the caller must never reuse a counter value for the same
+/// key and context. The derivation key should be independent of the
+/// encryption key or separated from it by the context value.
+///
+/// The derived nonces are indistinguishable from random values, so the usual
+/// bounds for random nonces apply. For AEADs with a 96-bit nonce, such as
+/// AES-GCM and ChaCha20-Poly1305, the encryption key must be rotated after
+/// 2^32 messages to keep the probability of a collision negligible; a counter
+/// at or beyond [`MAX_DERIVED_NONCES_96`] is rejected for these key types.
+/// Extended nonce types such as XChaCha20-Poly1305 do not carry this limit.
+#[cfg(feature = "hkdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+pub fn derive_nonce<K: KeyAeadMeta>(
+    key: &[u8],
+    context: &[u8],
+    counter: u64,
+) -> Result<GenericArray<u8, K::NonceSize>, Error> {
+    use crate::generic_array::typenum::Unsigned;
+
+    if K::NonceSize::USIZE <= 12 && counter >= MAX_DERIVED_NONCES_96 {
+        return Err(err_msg!(
+            Usage,
+            "Exceeded the nonce limit for this key, the key must be rotated"
+        ));
+    }
+    let mut nonce = GenericArray::default();
+    hkdf::Hkdf::<sha2::Sha256>::new(None, key)
+        .expand_multi_info(
+            &[b"askar-nonce", &counter.to_be_bytes(), context],
+            nonce.as_mut_slice(),
+        )
+        .map_err(|_| err_msg!(Unexpected, "Error deriving nonce"))?;
+    Ok(nonce)
+}
+
 /// A structure combining the AEAD parameters
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct KeyAeadParams {
@@ -61,3 +105,42 @@ pub struct KeyAeadParams {
     /// The length of the tag
     pub tag_length: usize,
 }
+
+#[cfg(all(test, feature = "hkdf", feature = "chacha"))]
+mod tests {
+    use super::*;
+    use crate::alg::chacha20::{Chacha20Key, C20P, XC20P};
+
+    #[test]
+    fn derive_nonce_expected() {
+        let key = b"nonce derivation key";
+        let nonce = derive_nonce::<Chacha20Key<C20P>>(key, b"context", 0).unwrap();
+        assert_eq!(nonce.len(), 12);
+        assert_eq!(
+            nonce,
+            derive_nonce::<Chacha20Key<C20P>>(key, b"context", 0).unwrap()
+        );
+        assert_ne!(
+            nonce,
+            derive_nonce::<Chacha20Key<C20P>>(key, b"context", 1).unwrap()
+        );
+        assert_ne!(
+            nonce,
+            derive_nonce::<Chacha20Key<C20P>>(key, b"other", 0).unwrap()
+        );
+        assert_eq!(
+            derive_nonce::<Chacha20Key<XC20P>>(key, b"context", 0)
+                .unwrap()
+                .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn derive_nonce_limit() {
+        let key = b"nonce derivation key";
+        assert!(derive_nonce::<Chacha20Key<C20P>>(key, b"", MAX_DERIVED_NONCES_96 - 1).is_ok());
+        assert!(derive_nonce::<Chacha20Key<C20P>>(key, b"", MAX_DERIVED_NONCES_96).is_err());
+        assert!(derive_nonce::<Chacha20Key<XC20P>>(key, b"", MAX_DERIVED_NONCES_96).is_ok());
+    }
+}
@@ -6,7 +6,11 @@ use core::fmt::{self, Display, Formatter};
 use std::error::Error as StdError;
 
 /// The possible kinds of error produced by the crate
+///
+/// The error kind is accessible via [`Error::kind`] and is intended for
+/// matching by callers. Additional kinds may be added in future releases.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Custom error, for use external integrations
     Custom,
@@ -83,7 +87,7 @@ impl Error {
         }
     }
 
-    /// Accessor for the error kind
+    /// Accessor for the error kind, for use in matching on the error type
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
@@ -195,4 +199,14 @@ mod tests {
         let e = err.source().unwrap().downcast_ref::<E>();
         assert!(e.is_some());
     }
+
+    #[test]
+    fn match_kind() {
+        let err = err_msg!(Unsupported, "Unsupported key type");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(err.message(), "Unsupported key type");
+        let err = err_msg!(InvalidKeyData);
+        assert_eq!(err.kind(), ErrorKind::InvalidKeyData);
+        assert_eq!(err.message(), ErrorKind::InvalidKeyData.as_str());
+    }
 }
@@ -5,11 +5,12 @@ use serde::{
     Serializer,
 };
 
-use super::{ops::KeyOpsSet, ToJwk};
+use super::{ops::KeyOpsSet, ToJwk, X5cChain};
 use crate::{
     alg::KeyAlg,
     buffer::{WriteBuffer, Writer},
     error::Error,
+    sign::SignatureType,
 };
 
 fn write_hex_buffer(mut buffer: impl Write, value: &[u8]) -> Result<(), Error> {
@@ -35,11 +36,59 @@ pub enum JwkEncoderMode {
     Thumbprint,
 }
 
+/// The signature algorithm to include as the `alg` member of a signing key JWK
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JwkSigAlg {
+    /// Use the default signature algorithm for the key type
+    Default,
+    /// Use a specific signature algorithm supported by the key type
+    Specific(SignatureType),
+}
+
 /// Common interface for JWK encoders
 pub trait JwkEncoder {
     /// Get the requested algorithm for the JWK
     fn alg(&self) -> Option<KeyAlg>;
 
+    /// Get the requested signature algorithm to include in the JWK, if any
+    fn sig_alg(&self) -> Option<JwkSigAlg> {
+        None
+    }
+
+    /// Check if the compressed public key representation is requested.
+    ///
+    /// This only applies to key types with a legacy compressed JWK form,
+    /// currently the `OKP` representation of BLS12-381 keys. It does not
+    /// affect thumbprints.
+    fn compressed(&self) -> bool {
+        false
+    }
+
+    /// Add the `alg` member for a signing key, if requested by the encoder.
+    ///
+    /// The `supported` signature types are those available for the key type,
+    /// starting with the default. The member is never included when encoding
+    /// a thumbprint.
+    fn add_sig_alg(&mut self, supported: &[SignatureType]) -> Result<(), Error> {
+        if self.is_thumbprint() {
+            return Ok(());
+        }
+        let sig_type = match self.sig_alg() {
+            None => return Ok(()),
+            Some(JwkSigAlg::Default) => supported.first().copied(),
+            Some(JwkSigAlg::Specific(sig_type)) => {
+                supported.iter().find(|s| **s == sig_type).copied()
+            }
+        };
+        match sig_type.and_then(|s| s.jose_alg()) {
+            Some(alg) => self.add_str("alg", alg),
+            None => Err(err_msg!(
+                Unsupported,
+                "Unsupported signature algorithm for JWK"
+            )),
+        }
+    }
+
     /// Add a string attribute
     fn add_str(&mut self, key: &str, value: &str) -> Result<(), Error>;
 
@@ -66,17 +115,33 @@ pub trait JwkEncoder {
 }
 
 /// A helper structure which writes a JWK to a buffer
+///
+/// The encoder may target any [`WriteBuffer`], including a fixed-size
+/// [`Writer`] over a byte slice, so it does not depend on the `alloc` feature.
+/// Create the encoder with the desired [`JwkEncoderMode`], pass it to
+/// [`ToJwk::encode_jwk`], then call [`JwkBufferEncoder::finalize`] to close
+/// the JSON object:
+///
+/// - `PublicKey` writes the public key members only
+/// - `SecretKey` additionally writes the private key member(s)
+/// - `Thumbprint` writes the required members in canonical order, suitable
+///   for hashing into a JWK thumbprint
+///
+/// See [`write_jwk`](super::write_jwk) for a helper performing these steps.
 #[derive(Debug)]
-pub struct JwkBufferEncoder<'b, B: WriteBuffer> {
+pub struct JwkBufferEncoder<'b, B: WriteBuffer + ?Sized> {
     mode: JwkEncoderMode,
     buffer: &'b mut B,
     empty: bool,
     alg: Option<KeyAlg>,
+    sig_alg: Option<JwkSigAlg>,
+    compressed: bool,
     key_ops: Option<KeyOpsSet>,
     kid: Option<&'b str>,
+    x5c: Option<X5cChain<'b>>,
 }
 
-impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
+impl<'b, B: WriteBuffer + ?Sized> JwkBufferEncoder<'b, B> {
     /// Create a new instance
     pub fn new(buffer: &'b mut B, mode: JwkEncoderMode) -> Self {
         Self {
@@ -84,8 +149,11 @@ impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
             buffer,
             empty: true,
             alg: None,
+            sig_alg: None,
+            compressed: false,
             key_ops: None,
             kid: None,
+            x5c: None,
         }
     }
 
@@ -107,6 +175,16 @@ impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
         Self { alg, ..self }
     }
 
+    /// Set the signature algorithm to include for a signing key
+    pub fn sig_alg(self, sig_alg: Option<JwkSigAlg>) -> Self {
+        Self { sig_alg, ..self }
+    }
+
+    /// Request the compressed public key representation, where supported
+    pub fn compressed(self, compressed: bool) -> Self {
+        Self { compressed, ..self }
+    }
+
     /// Set the supported key operations
     pub fn key_ops(self, key_ops: Option<KeyOpsSet>) -> Self {
         Self { key_ops, ..self }
@@ -117,6 +195,11 @@ impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
         Self { kid, ..self }
     }
 
+    /// Set the X.509 certificate chain, which is written without validation
+    pub fn x5c(self, x5c: Option<X5cChain<'b>>) -> Self {
+        Self { x5c, ..self }
+    }
+
     /// Complete the JWK output
     pub fn finalize(mut self) -> Result<(), Error> {
         if let Some(ops) = self.key_ops {
@@ -136,6 +219,20 @@ impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
         if let Some(kid) = self.kid {
             self.add_str("kid", kid)?;
         }
+        if let Some(x5c) = self.x5c {
+            self.start_attr("x5c")?;
+            let buffer = &mut *self.buffer;
+            buffer.buffer_write(b"[")?;
+            for (idx, cert) in x5c.as_slice().iter().enumerate() {
+                if idx > 0 {
+                    buffer.buffer_write(b",")?;
+                }
+                buffer.buffer_write(b"\"")?;
+                buffer.buffer_write(cert.as_bytes())?;
+                buffer.buffer_write(b"\"")?;
+            }
+            buffer.buffer_write(b"]")?;
+        }
         if !self.empty {
             self.buffer.buffer_write(b"}")?;
         }
@@ -143,12 +240,22 @@ impl<'b, B: WriteBuffer> JwkBufferEncoder<'b, B> {
     }
 }
 
-impl<B: WriteBuffer> JwkEncoder for JwkBufferEncoder<'_, B> {
+impl<B: WriteBuffer + ?Sized> JwkEncoder for JwkBufferEncoder<'_, B> {
     #[inline]
     fn alg(&self) -> Option<KeyAlg> {
         self.alg
     }
 
+    #[inline]
+    fn sig_alg(&self) -> Option<JwkSigAlg> {
+        self.sig_alg
+    }
+
+    #[inline]
+    fn compressed(&self) -> bool {
+        self.compressed
+    }
+
     fn add_str(&mut self, key: &str, value: &str) -> Result<(), Error> {
         self.start_attr(key)?;
         let buffer = &mut *self.buffer;
@@ -179,8 +286,11 @@ pub struct JwkSerialize<'s, K: ToJwk> {
     mode: JwkEncoderMode,
     key: &'s K,
     alg: Option<KeyAlg>,
+    sig_alg: Option<JwkSigAlg>,
+    compressed: bool,
     key_ops: Option<KeyOpsSet>,
     kid: Option<&'s str>,
+    x5c: Option<X5cChain<'s>>,
 }
 
 impl<'s, K: ToJwk> JwkSerialize<'s, K> {
@@ -188,10 +298,13 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
     pub fn new(key: &'s K, mode: JwkEncoderMode) -> Self {
         Self {
             alg: None,
+            sig_alg: None,
+            compressed: false,
             mode,
             key,
             key_ops: None,
             kid: None,
+            x5c: None,
         }
     }
 
@@ -201,8 +314,11 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
             mode: JwkEncoderMode::PublicKey,
             key,
             alg: None,
+            sig_alg: None,
+            compressed: false,
             key_ops: None,
             kid: None,
+            x5c: None,
         }
     }
 
@@ -212,8 +328,11 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
             mode: JwkEncoderMode::SecretKey,
             key,
             alg: None,
+            sig_alg: None,
+            compressed: false,
             key_ops: None,
             kid: None,
+            x5c: None,
         }
     }
 
@@ -223,8 +342,11 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
             mode: JwkEncoderMode::Thumbprint,
             key,
             alg: None,
+            sig_alg: None,
+            compressed: false,
             key_ops: None,
             kid: None,
+            x5c: None,
         }
     }
 
@@ -233,6 +355,16 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
         Self { alg, ..self }
     }
 
+    /// Set the signature algorithm to include for a signing key
+    pub fn sig_alg(self, sig_alg: Option<JwkSigAlg>) -> Self {
+        Self { sig_alg, ..self }
+    }
+
+    /// Request the compressed public key representation, where supported
+    pub fn compressed(self, compressed: bool) -> Self {
+        Self { compressed, ..self }
+    }
+
     /// Set the key operations
     pub fn key_ops(self, key_ops: Option<KeyOpsSet>) -> Self {
         Self { key_ops, ..self }
@@ -242,6 +374,11 @@ impl<'s, K: ToJwk> JwkSerialize<'s, K> {
     pub fn kid(self, kid: Option<&'s str>) -> Self {
         Self { kid, ..self }
     }
+
+    /// Set the X.509 certificate chain, which is written without validation
+    pub fn x5c(self, x5c: Option<X5cChain<'s>>) -> Self {
+        Self { x5c, ..self }
+    }
 }
 
 impl<K: ToJwk> Serialize for JwkSerialize<'_, K> {
@@ -251,6 +388,8 @@ impl<K: ToJwk> Serialize for JwkSerialize<'_, K> {
     {
         struct Enc<'m, M> {
             alg: Option<KeyAlg>,
+            sig_alg: Option<JwkSigAlg>,
+            compressed: bool,
             mode: JwkEncoderMode,
             map: &'m mut M,
         }
@@ -260,6 +399,14 @@ impl<K: ToJwk> Serialize for JwkSerialize<'_, K> {
                 self.alg
             }
 
+            fn sig_alg(&self) -> Option<JwkSigAlg> {
+                self.sig_alg
+            }
+
+            fn compressed(&self) -> bool {
+                self.compressed
+            }
+
             fn add_str(&mut self, key: &str, value: &str) -> Result<(), Error> {
                 self.map
                     .serialize_entry(key, value)
@@ -285,6 +432,8 @@ impl<K: ToJwk> Serialize for JwkSerialize<'_, K> {
         let mut map = serializer.serialize_map(None)?;
         let mut enc = Enc {
             alg: self.alg,
+            sig_alg: self.sig_alg,
+            compressed: self.compressed,
             mode: self.mode,
             map: &mut map,
         };
@@ -297,6 +446,9 @@ impl<K: ToJwk> Serialize for JwkSerialize<'_, K> {
         if let Some(kid) = self.kid {
             map.serialize_entry("kid", kid)?;
         }
+        if let Some(x5c) = self.x5c.as_ref() {
+            map.serialize_entry("x5c", x5c)?;
+        }
         map.end()
     }
 }
@@ -306,10 +458,11 @@ mod tests {
     #[cfg(feature = "ed25519")]
     #[test]
     fn serialize_jwk() {
-        use super::JwkSerialize;
+        use super::{JwkBufferEncoder, JwkEncoderMode, JwkSerialize};
         use crate::{
             alg::ed25519::Ed25519KeyPair,
-            jwk::{JwkParts, KeyOps},
+            buffer::Writer,
+            jwk::{JwkParts, KeyOps, ToJwk, X5cChain},
             repr::KeySecretBytes,
         };
 
@@ -321,7 +474,8 @@ mod tests {
         let len = serde_json_core::to_slice(
             &JwkSerialize::as_secret(&kp)
                 .kid(Some("FdFYFzERwC2uCBB46pZQi4GG85LujR8obt-KWRBICVQ"))
-                .key_ops(Some(KeyOps::Sign | KeyOps::Verify)),
+                .key_ops(Some(KeyOps::Sign | KeyOps::Verify))
+                .x5c(Some(X5cChain::new(&["AQID"]).unwrap())),
             &mut buf,
         )
         .unwrap();
@@ -337,5 +491,120 @@ mod tests {
         assert_eq!(parts.d, Some("nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A"));
         assert_eq!(parts.k, None);
         assert_eq!(parts.key_ops, Some(KeyOps::Sign | KeyOps::Verify));
+        assert_eq!(parts.x5c.unwrap().as_slice(), ["AQID"]);
+
+        let mut buf = [0u8; 512];
+        let mut w = Writer::from_slice(&mut buf);
+        let mut enc = JwkBufferEncoder::new(&mut w, JwkEncoderMode::PublicKey)
+            .x5c(Some(X5cChain::new(&["AQID", "BAUG"]).unwrap()));
+        kp.encode_jwk(&mut enc).unwrap();
+        enc.finalize().unwrap();
+        let parts = JwkParts::from_slice(w.as_ref()).unwrap();
+        assert_eq!(parts.x5c.unwrap().as_slice(), ["AQID", "BAUG"]);
+    }
+
+    #[cfg(all(feature = "alloc", feature = "ed25519"))]
+    #[test]
+    fn write_jwk_fixed_buffer() {
+        use super::JwkEncoderMode;
+        use crate::{
+            alg::ed25519::Ed25519KeyPair,
+            buffer::{HashBuffer, WriteBuffer, Writer},
+            jwk::{write_jwk, ToJwk},
+            repr::KeySecretBytes,
+        };
+        use base64::Engine;
+        use sha2::Sha256;
+
+        let kp = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+        ))
+        .unwrap();
+
+        let mut buf = [0u8; 256];
+        let mut w = Writer::from_slice(&mut buf);
+        write_jwk(&kp, JwkEncoderMode::PublicKey, None, &mut w).unwrap();
+        assert_eq!(w.as_ref(), kp.to_jwk_public(None).unwrap().as_bytes());
+
+        let mut buf = [0u8; 256];
+        let mut w = Writer::from_slice(&mut buf);
+        write_jwk(&kp, JwkEncoderMode::SecretKey, None, &mut w).unwrap();
+        assert_eq!(w.as_ref(), kp.to_jwk_secret(None).unwrap().as_ref());
+
+        let mut buf = [0u8; 256];
+        let mut w = Writer::from_slice(&mut buf);
+        write_jwk(&kp, JwkEncoderMode::Thumbprint, None, &mut w).unwrap();
+        let mut hasher = HashBuffer::<Sha256>::new();
+        hasher.buffer_write(w.as_ref()).unwrap();
+        let thumbprint = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize());
+        assert_eq!(thumbprint, kp.to_jwk_thumbprint(None).unwrap());
+
+        let mut buf = [0u8; 16];
+        let mut w = Writer::from_slice(&mut buf);
+        assert!(write_jwk(&kp, JwkEncoderMode::PublicKey, None, &mut w).is_err());
+    }
+
+    #[cfg(all(feature = "alloc", feature = "ed25519", feature = "ec_curves"))]
+    #[test]
+    fn encode_sig_alg() {
+        use super::{JwkBufferEncoder, JwkEncoderMode, JwkSigAlg};
+        use crate::{
+            alg::{ed25519::Ed25519KeyPair, p256::P256KeyPair},
+            jwk::{JwkParts, ToJwk},
+            repr::KeySecretBytes,
+            sign::SignatureType,
+        };
+        use alloc::vec::Vec;
+
+        fn encode<K: ToJwk>(
+            key: &K,
+            mode: JwkEncoderMode,
+            sig_alg: JwkSigAlg,
+        ) -> Result<Vec<u8>, crate::Error> {
+            let mut v = Vec::new();
+            let mut enc = JwkBufferEncoder::new(&mut v, mode).sig_alg(Some(sig_alg));
+            key.encode_jwk(&mut enc)?;
+            enc.finalize()?;
+            Ok(v)
+        }
+
+        let kp = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+        ))
+        .unwrap();
+        let jwk = encode(&kp, JwkEncoderMode::PublicKey, JwkSigAlg::Default).unwrap();
+        let parts = JwkParts::from_slice(&jwk).unwrap();
+        assert_eq!(parts.alg, Some("EdDSA"));
+        assert_eq!(parts.crv, Some("Ed25519"));
+
+        // the thumbprint input is unaffected
+        let thumb = encode(&kp, JwkEncoderMode::Thumbprint, JwkSigAlg::Default).unwrap();
+        let mut expected = Vec::new();
+        crate::jwk::write_jwk(&kp, JwkEncoderMode::Thumbprint, None, &mut expected).unwrap();
+        assert_eq!(thumb, expected);
+
+        let kp = P256KeyPair::from_secret_bytes(&hex!(
+            "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"
+        ))
+        .unwrap();
+        let jwk = encode(
+            &kp,
+            JwkEncoderMode::PublicKey,
+            JwkSigAlg::Specific(SignatureType::ES256),
+        )
+        .unwrap();
+        assert_eq!(JwkParts::from_slice(&jwk).unwrap().alg, Some("ES256"));
+        assert!(encode(
+            &kp,
+            JwkEncoderMode::PublicKey,
+            JwkSigAlg::Specific(SignatureType::ES256ph)
+        )
+        .is_err());
+        assert!(encode(
+            &kp,
+            JwkEncoderMode::PublicKey,
+            JwkSigAlg::Specific(SignatureType::EdDSA)
+        )
+        .is_err());
     }
 }
@@ -0,0 +1,217 @@
+//! Password-protected private key JWKs, encrypted as a compact JWE using
+//! PBES2-HS512+A256KW key wrapping and A256GCM content encryption
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+use base64::Engine;
+use serde::Deserialize;
+use sha2::Sha512;
+
+use super::{FromJwk, ToJwk};
+use crate::{
+    alg::aes::{A256Gcm, A256Kw, AesKey},
+    buffer::{SecretBytes, WriteBuffer},
+    encrypt::KeyAeadInPlace,
+    error::Error,
+    kdf::{pbkdf2::Pbkdf2, FromKeyDerivation},
+    repr::KeySecretBytes,
+};
+
+/// The JWE key management algorithm
+pub const JWE_ALG: &str = "PBES2-HS512+A256KW";
+/// The JWE content encryption algorithm
+pub const JWE_ENC: &str = "A256GCM";
+/// The PBKDF2 iteration count used when encrypting
+pub const PBES2_ITERATIONS: u32 = 210_000;
+
+const PBES2_MIN_ITERATIONS: u32 = 1000;
+const PBES2_MAX_ITERATIONS: u32 = 1_000_000;
+const SALT_INPUT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+
+#[derive(Deserialize)]
+struct Pbes2Header<'h> {
+    alg: &'h str,
+    enc: &'h str,
+    p2s: &'h str,
+    p2c: u32,
+}
+
+/// Encrypt the private JWK of a key under a password, producing a JWE in
+/// compact serialization
+#[cfg(feature = "getrandom")]
+#[cfg_attr(docsrs, doc(cfg(feature = "getrandom")))]
+pub fn to_jwk_encrypted<K: ToJwk + ?Sized>(key: &K, password: &[u8]) -> Result<String, Error> {
+    let mut salt_input = [0u8; SALT_INPUT_LENGTH];
+    crate::random::fill_random(&mut salt_input);
+    let mut nonce = [0u8; NONCE_LENGTH];
+    crate::random::fill_random(&mut nonce);
+    let cek = <AesKey<A256Gcm> as crate::repr::KeyGen>::random()?;
+    encrypt_jwk(key, password, &salt_input, PBES2_ITERATIONS, &cek, &nonce)
+}
+
+fn encrypt_jwk<K: ToJwk + ?Sized>(
+    key: &K,
+    password: &[u8],
+    salt_input: &[u8],
+    iterations: u32,
+    cek: &AesKey<A256Gcm>,
+    nonce: &[u8],
+) -> Result<String, Error> {
+    let mut header = String::with_capacity(128);
+    write!(
+        header,
+        "{{\"alg\":\"{}\",\"enc\":\"{}\",\"cty\":\"jwk+json\",\"p2s\":\"",
+        JWE_ALG, JWE_ENC
+    )
+    .and_then(|_| write_base64(&mut header, salt_input))
+    .and_then(|_| write!(header, "\",\"p2c\":{}}}", iterations))
+    .map_err(|_| err_msg!(Unexpected, "Error encoding JWE header"))?;
+
+    let mut token = String::with_capacity(512);
+    write_base64(&mut token, header.as_bytes())
+        .map_err(|_| err_msg!(Unexpected, "Error encoding base64"))?;
+    let protected_len = token.len();
+
+    let kek = derive_kek(password, salt_input, iterations)?;
+    let mut wrapped = SecretBytes::with_capacity(40);
+    cek.with_secret_bytes(|sk| match sk {
+        Some(sk) => wrapped.buffer_write(sk),
+        None => Err(err_msg!(MissingSecretKey)),
+    })?;
+    kek.encrypt_in_place(&mut wrapped, &[], &[])?;
+
+    let mut buf = key.to_jwk_secret(None)?;
+    cek.encrypt_in_place(&mut buf, nonce, &token.as_bytes()[..protected_len])?;
+    let (ciphertext, tag) = buf.as_ref().split_at(buf.len() - TAG_LENGTH);
+
+    for part in [wrapped.as_ref(), nonce, ciphertext, tag] {
+        token.push('.');
+        write_base64(&mut token, part)
+            .map_err(|_| err_msg!(Unexpected, "Error encoding base64"))?;
+    }
+    Ok(token)
+}
+
+/// Decrypt a password-protected private JWK produced by [`to_jwk_encrypted`]
+/// and load the key. The decrypted JWK is zeroized after parsing.
+pub fn from_jwk_encrypted<K: FromJwk>(jwe: &str, password: &[u8]) -> Result<K, Error> {
+    let mut parts = jwe.split('.');
+    let (protected, wrapped, nonce, ciphertext, tag) = match (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) {
+        (Some(p), Some(w), Some(n), Some(c), Some(t), None) => (p, w, n, c, t),
+        _ => return Err(err_msg!(Invalid, "Invalid JWE compact serialization")),
+    };
+
+    let header_json = decode_base64(protected)?;
+    let (header, _) = serde_json_core::from_slice::<Pbes2Header<'_>>(&header_json)
+        .map_err(err_map!(Invalid, "Error parsing JWE header"))?;
+    if header.alg != JWE_ALG || header.enc != JWE_ENC {
+        return Err(err_msg!(Unsupported, "Unsupported JWE algorithm"));
+    }
+    if !(PBES2_MIN_ITERATIONS..=PBES2_MAX_ITERATIONS).contains(&header.p2c) {
+        return Err(err_msg!(Unsupported, "Unsupported PBES2 iteration count"));
+    }
+    let salt_input = decode_base64(header.p2s)?;
+    if salt_input.len() < 8 {
+        return Err(err_msg!(Invalid, "Invalid PBES2 salt input"));
+    }
+    let nonce = decode_base64(nonce)?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(err_msg!(InvalidNonce));
+    }
+    let tag = decode_base64(tag)?;
+    if tag.len() != TAG_LENGTH {
+        return Err(err_msg!(Invalid, "Invalid JWE authentication tag"));
+    }
+
+    let kek = derive_kek(password, &salt_input, header.p2c)?;
+    let mut cek = SecretBytes::from(decode_base64(wrapped)?);
+    kek.decrypt_in_place(&mut cek, &[], &[])?;
+    let cek = AesKey::<A256Gcm>::from_secret_bytes(&cek)?;
+
+    let mut buf = SecretBytes::from(decode_base64(ciphertext)?);
+    buf.buffer_write(&tag)?;
+    cek.decrypt_in_place(&mut buf, &nonce, protected.as_bytes())?;
+    K::from_jwk_slice(&buf)
+}
+
+fn derive_kek(
+    password: &[u8],
+    salt_input: &[u8],
+    iterations: u32,
+) -> Result<AesKey<A256Kw>, Error> {
+    // the PBES2 salt is prefixed with the algorithm name (RFC 7518 section 4.8.1.1)
+    let mut salt = Vec::with_capacity(JWE_ALG.len() + 1 + salt_input.len());
+    salt.extend_from_slice(JWE_ALG.as_bytes());
+    salt.push(0);
+    salt.extend_from_slice(salt_input);
+    AesKey::<A256Kw>::from_key_derivation(Pbkdf2::<Sha512>::new(password, &salt, iterations))
+}
+
+fn write_base64(out: &mut String, value: &[u8]) -> core::fmt::Result {
+    write!(
+        out,
+        "{}",
+        base64::display::Base64Display::new(
+            value,
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD
+        )
+    )
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; base64::decoded_len_estimate(value.len())];
+    let len = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode_slice(value, &mut buf)
+        .map_err(|_| err_msg!(Invalid, "Base64 decoding error"))?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+#[cfg(all(test, feature = "ed25519"))]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{alg::ed25519::Ed25519KeyPair, repr::ToSecretBytes};
+
+    #[test]
+    fn encrypted_jwk_round_trip() {
+        let key = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+        ))
+        .unwrap();
+        let cek = AesKey::<A256Gcm>::from_secret_bytes(&[7u8; 32]).unwrap();
+        let jwe = encrypt_jwk(
+            &key,
+            b"password",
+            &[1u8; SALT_INPUT_LENGTH],
+            PBES2_MIN_ITERATIONS,
+            &cek,
+            &[2u8; NONCE_LENGTH],
+        )
+        .unwrap();
+        assert_eq!(jwe.split('.').count(), 5);
+
+        let loaded = from_jwk_encrypted::<Ed25519KeyPair>(&jwe, b"password").unwrap();
+        assert_eq!(
+            loaded.to_secret_bytes().unwrap(),
+            key.to_secret_bytes().unwrap()
+        );
+        assert!(from_jwk_encrypted::<Ed25519KeyPair>(&jwe, b"wrong").is_err());
+
+        let tag_start = jwe.rfind('.').unwrap() + 1;
+        let mut tampered = jwe[..tag_start].to_string();
+        write_base64(&mut tampered, &[0u8; TAG_LENGTH]).unwrap();
+        assert!(from_jwk_encrypted::<Ed25519KeyPair>(&tampered, b"password").is_err());
+    }
+}
@@ -15,13 +15,17 @@ use crate::{
 };
 
 mod encode;
-pub use self::encode::{JwkBufferEncoder, JwkEncoder, JwkEncoderMode, JwkSerialize};
+pub use self::encode::{JwkBufferEncoder, JwkEncoder, JwkEncoderMode, JwkSerialize, JwkSigAlg};
+
+#[cfg(all(feature = "aes", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "aes", feature = "alloc"))))]
+pub mod encrypted;
 
 mod ops;
 pub use self::ops::{KeyOps, KeyOpsSet};
 
 mod parts;
-pub use self::parts::JwkParts;
+pub use self::parts::{JwkParts, X5cChain, X5C_MAX_CERTS};
 
 /// Support for converting a key into a JWK
 pub trait ToJwk {
@@ -37,6 +41,15 @@ pub trait ToJwk {
         Ok(String::from_utf8(v).unwrap())
     }
 
+    /// Create a short fingerprint of the public key, see [`write_key_fingerprint`]
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    fn to_key_fingerprint(&self) -> Result<String, Error> {
+        let mut v = Vec::with_capacity(11);
+        write_key_fingerprint(self, &mut v)?;
+        Ok(String::from_utf8(v).unwrap())
+    }
+
     /// Create a JWK of the public key
     #[cfg(feature = "alloc")]
     #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
@@ -60,20 +73,65 @@ pub trait ToJwk {
     }
 }
 
+/// Encode a key's JWK into a buffer, without allocating
+///
+/// A fixed-size [`Writer`](crate::buffer::Writer) may be used as the output
+/// when the `alloc` feature is not enabled. The output matches that of
+/// [`ToJwk::to_jwk_public`] or [`ToJwk::to_jwk_secret`] for the corresponding
+/// mode, while the `Thumbprint` mode produces the canonical input to the
+/// JWK thumbprint hash.
+pub fn write_jwk<K: ToJwk + ?Sized>(
+    key: &K,
+    mode: JwkEncoderMode,
+    alg: Option<KeyAlg>,
+    output: &mut dyn WriteBuffer,
+) -> Result<(), Error> {
+    let mut buf = JwkBufferEncoder::new(output, mode).alg(alg);
+    key.encode_jwk(&mut buf)?;
+    buf.finalize()
+}
+
 /// Encode a key's JWK into a buffer
 pub fn write_jwk_thumbprint<K: ToJwk + ?Sized>(
     key: &K,
     alg: Option<KeyAlg>,
     output: &mut dyn WriteBuffer,
 ) -> Result<(), Error> {
+    let hash = jwk_thumbprint_hash(key, alg)?;
+    write_base64(&hash, output)
+}
+
+/// Write a short, stable identifier for a key to a buffer
+///
+/// The fingerprint is the unpadded base64-URL encoding of the first 8 bytes
+/// of the SHA-256 JWK thumbprint hash, producing an 11 character string.
+/// It does not reveal the key and is suitable for logging and indexing,
+/// but it is not a JWK thumbprint (or thumbprint URI) and is not
+/// guaranteed to be unique.
+pub fn write_key_fingerprint<K: ToJwk + ?Sized>(
+    key: &K,
+    output: &mut dyn WriteBuffer,
+) -> Result<(), Error> {
+    let hash = jwk_thumbprint_hash(key, None)?;
+    write_base64(&hash[..FINGERPRINT_LENGTH], output)
+}
+
+const FINGERPRINT_LENGTH: usize = 8;
+
+fn jwk_thumbprint_hash<K: ToJwk + ?Sized>(key: &K, alg: Option<KeyAlg>) -> Result<[u8; 32], Error> {
     let mut hasher = HashBuffer::<Sha256>::new();
     let mut buf = JwkBufferEncoder::new(&mut hasher, JwkEncoderMode::Thumbprint).alg(alg);
     key.encode_jwk(&mut buf)?;
     buf.finalize()?;
-    let hash = hasher.finalize();
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    Ok(hash)
+}
+
+fn write_base64(data: &[u8], output: &mut dyn WriteBuffer) -> Result<(), Error> {
     let mut buf = [0u8; 43];
     let len = base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .encode_slice(hash, &mut buf)
+        .encode_slice(data, &mut buf)
         .map_err(|_| err_msg!(Unexpected, "Base64 encoding error"))?;
     output.buffer_write(&buf[..len])?;
     Ok(())
@@ -7,8 +7,8 @@ use core::{
 use arbitrary::Arbitrary;
 use base64::Engine;
 use serde::{
-    de::{Deserialize, Deserializer, MapAccess, Visitor},
-    ser::{Serialize, SerializeMap, Serializer},
+    de::{Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeMap, SerializeSeq, Serializer},
 };
 
 use super::ops::{KeyOps, KeyOpsSet};
@@ -36,22 +36,172 @@ pub struct JwkParts<'a> {
     pub k: OptAttr<'a>,
     /// Recognized key operations
     pub key_ops: Option<KeyOpsSet>,
+    /// X.509 certificate chain
+    pub x5c: Option<X5cChain<'a>>,
 }
 
 impl<'de> JwkParts<'de> {
     /// Parse a JWK from a string reference
+    ///
+    /// Unrecognized members are ignored, as required by RFC 7517.
     pub fn try_from_str(jwk: &'de str) -> Result<Self, Error> {
-        let (parts, _read) =
-            serde_json_core::from_str(jwk).map_err(err_map!(Invalid, "Error parsing JWK"))?;
-        Ok(parts)
+        Self::from_slice(jwk.as_bytes())
     }
 
     /// Parse a JWK from a byte slice
+    ///
+    /// Unrecognized members are ignored, as required by RFC 7517.
     pub fn from_slice(jwk: &'de [u8]) -> Result<Self, Error> {
         let (parts, _read) =
             serde_json_core::from_slice(jwk).map_err(err_map!(Invalid, "Error parsing JWK"))?;
         Ok(parts)
     }
+
+    /// Parse a JWK from a string reference, rejecting any members which are
+    /// not recognized by this parser
+    pub fn try_from_str_strict(jwk: &'de str) -> Result<Self, Error> {
+        Self::from_slice_strict(jwk.as_bytes())
+    }
+
+    /// Parse a JWK from a byte slice, rejecting any members which are not
+    /// recognized by this parser
+    pub fn from_slice_strict(jwk: &'de [u8]) -> Result<Self, Error> {
+        let (StrictJwkParts(parts), _read) =
+            serde_json_core::from_slice(jwk).map_err(err_map!(Invalid, "Error parsing JWK"))?;
+        Ok(parts)
+    }
+}
+
+/// The JWK members recognized by the parser
+const JWK_MEMBERS: &[&str] = &[
+    "kty", "kid", "alg", "crv", "x", "y", "d", "k", "use", "key_ops", "x5c",
+];
+
+/// The maximum number of certificates in a parsed `x5c` chain
+pub const X5C_MAX_CERTS: usize = 8;
+
+/// A borrowed X.509 certificate chain from the `x5c` member of a JWK
+///
+/// Each entry is the standard (padded) base64 encoding of a DER certificate,
+/// starting with the certificate containing the key. Askar does not parse
+/// or validate the certificates in any way: callers are responsible for
+/// validating the chain and checking that it matches the key.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct X5cChain<'a> {
+    certs: [&'a str; X5C_MAX_CERTS],
+    len: usize,
+}
+
+impl<'a> X5cChain<'a> {
+    /// Create a certificate chain from a list of base64-encoded certificates
+    pub fn new(certs: &[&'a str]) -> Result<Self, Error> {
+        let mut result = Self::default();
+        for cert in certs {
+            result.push(cert)?;
+        }
+        Ok(result)
+    }
+
+    fn push(&mut self, cert: &'a str) -> Result<(), Error> {
+        if self.len == X5C_MAX_CERTS {
+            return Err(err_msg!(Invalid, "Exceeded max length for x5c chain"));
+        }
+        self.certs[self.len] = cert;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Access the base64-encoded certificates
+    pub fn as_slice(&self) -> &[&'a str] {
+        &self.certs[..self.len]
+    }
+
+    /// Get the number of certificates in the chain
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the chain is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decode the DER bytes of the certificate at `index` into a buffer,
+    /// returning the decoded length
+    pub fn decode_cert(&self, index: usize, output: &mut [u8]) -> Result<usize, Error> {
+        let cert = self
+            .as_slice()
+            .get(index)
+            .ok_or_else(|| err_msg!(Usage, "Invalid x5c certificate index"))?;
+        base64::engine::general_purpose::STANDARD
+            .decode_slice(cert, output)
+            .map_err(|_| err_msg!(Invalid, "Base64 decoding error"))
+    }
+}
+
+impl Debug for X5cChain<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for X5cChain<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(0..=X5C_MAX_CERTS)?;
+        let mut result = Self::default();
+        for _ in 0..len {
+            result
+                .push(u.arbitrary()?)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        }
+        Ok(result)
+    }
+}
+
+impl<'de> Deserialize<'de> for X5cChain<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct X5cVisitor;
+
+        impl<'de> Visitor<'de> for X5cVisitor {
+            type Value = X5cChain<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an array of base64-encoded certificates")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut chain = X5cChain::default();
+                while let Some(cert) = seq.next_element()? {
+                    chain
+                        .push(cert)
+                        .map_err(|_| serde::de::Error::invalid_length(X5C_MAX_CERTS + 1, &self))?;
+                }
+                Ok(chain)
+            }
+        }
+
+        deserializer.deserialize_seq(X5cVisitor)
+    }
+}
+
+impl Serialize for X5cChain<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for cert in self.as_slice() {
+            seq.serialize_element(cert)?;
+        }
+        seq.end()
+    }
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq)]
@@ -130,7 +280,10 @@ impl PartialEq<&str> for OptAttr<'_> {
     }
 }
 
-struct JwkMapVisitor<'de>(PhantomData<&'de ()>);
+struct JwkMapVisitor<'de> {
+    strict: bool,
+    _pd: PhantomData<&'de ()>,
+}
 
 impl<'de> Visitor<'de> for JwkMapVisitor<'de> {
     type Value = JwkParts<'de>;
@@ -152,6 +305,7 @@ impl<'de> Visitor<'de> for JwkMapVisitor<'de> {
         let mut d = None;
         let mut k = None;
         let mut key_ops = None;
+        let mut x5c = None;
 
         while let Some(key) = access.next_key::<&str>()? {
             match key {
@@ -176,7 +330,13 @@ impl<'de> Visitor<'de> for JwkMapVisitor<'de> {
                     }
                 }
                 "key_ops" => key_ops = Some(access.next_value()?),
-                _ => (),
+                "x5c" => x5c = Some(access.next_value()?),
+                _ if self.strict => {
+                    return Err(serde::de::Error::unknown_field(key, JWK_MEMBERS));
+                }
+                _ => {
+                    access.next_value::<IgnoredAny>()?;
+                }
             }
         }
 
@@ -191,6 +351,7 @@ impl<'de> Visitor<'de> for JwkMapVisitor<'de> {
                 d: d.into(),
                 k: k.into(),
                 key_ops,
+                x5c,
             })
         } else {
             Err(serde::de::Error::missing_field("kty"))
@@ -203,7 +364,26 @@ impl<'de> Deserialize<'de> for JwkParts<'de> {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_map(JwkMapVisitor(PhantomData))
+        deserializer.deserialize_map(JwkMapVisitor {
+            strict: false,
+            _pd: PhantomData,
+        })
+    }
+}
+
+struct StrictJwkParts<'a>(JwkParts<'a>);
+
+impl<'de> Deserialize<'de> for StrictJwkParts<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_map(JwkMapVisitor {
+                strict: true,
+                _pd: PhantomData,
+            })
+            .map(StrictJwkParts)
     }
 }
 
@@ -238,6 +418,9 @@ impl Serialize for JwkParts<'_> {
         if let Some(ops) = self.key_ops {
             map.serialize_entry("key_ops", &ops)?;
         }
+        if let Some(x5c) = self.x5c.as_ref() {
+            map.serialize_entry("x5c", x5c)?;
+        }
         map.end()
     }
 }
@@ -275,4 +458,53 @@ mod tests {
         let parts_2 = JwkParts::from_slice(&buf[..len]).unwrap();
         assert_eq!(parts_2, parts);
     }
+
+    #[test]
+    fn parse_extra_members() {
+        let jwk = r#"{
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+            "x5c": ["MIIBszCCAVmgAwIBAgIJAOb3", "MIIBrDCCAVKgAwIBAgIJ"],
+            "x5t": "NjVBRjY5MDlCMUIwNzU4RTA2QzZFMDQ4QzQ2MDAyQjVDNjk1RTM2Qg",
+            "ext": true,
+            "vendor": {"nested": [1, 2.5, null, {"a": "b"}]}
+        }"#;
+        let parts = JwkParts::try_from_str(jwk).unwrap();
+        assert_eq!(parts.kty, "OKP");
+        assert_eq!(parts.crv, Some("Ed25519"));
+        assert_eq!(parts.x, Some("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"));
+        assert_eq!(
+            parts.x5c.unwrap().as_slice(),
+            ["MIIBszCCAVmgAwIBAgIJAOb3", "MIIBrDCCAVKgAwIBAgIJ"]
+        );
+        assert!(JwkParts::try_from_str_strict(jwk).is_err());
+
+        let known = r#"{"kty":"OKP","crv":"Ed25519","use":"sig","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+        assert_eq!(
+            JwkParts::try_from_str_strict(known).unwrap(),
+            JwkParts::try_from_str(known).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_x5c() {
+        let jwk = r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","x5c":["AQID","BAUG"]}"#;
+        let parts = JwkParts::try_from_str_strict(jwk).unwrap();
+        let x5c = parts.x5c.unwrap();
+        assert_eq!(x5c.len(), 2);
+        let mut cert = [0u8; 8];
+        assert_eq!(x5c.decode_cert(1, &mut cert).unwrap(), 3);
+        assert_eq!(cert[..3], [4, 5, 6]);
+        assert!(x5c.decode_cert(2, &mut cert).is_err());
+
+        // check serialization
+        let mut buf = [0u8; 512];
+        let len = serde_json_core::to_slice(&parts, &mut buf[..]).unwrap();
+        assert_eq!(JwkParts::from_slice(&buf[..len]).unwrap(), parts);
+
+        let long = r#"{"kty":"OKP","x5c":["A","A","A","A","A","A","A","A","A"]}"#;
+        assert!(JwkParts::try_from_str(long).is_err());
+        assert!(X5cChain::new(&["A"; X5C_MAX_CERTS + 1]).is_err());
+    }
 }
@@ -0,0 +1,342 @@
+//! JSON Web Signature (JWS) compact serialization support
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    alg::{EcCurves, HasKeyAlg, KeyAlg},
+    error::Error,
+    sign::{KeySigVerify, KeySign, SignatureType},
+};
+
+/// Protected header parameters for a JWS
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwsHeader<'h> {
+    /// The signature algorithm, determined from the key when signing
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<&'h str>,
+    /// The key identifier
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<&'h str>,
+    /// The media type of the complete JWS
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<&'h str>,
+    /// The content type of the payload
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub cty: Option<&'h str>,
+}
+
+/// Sign a payload, producing a JWS in compact serialization.
+///
+/// The `alg` header is selected according to the algorithm of the signing key.
+pub fn sign_compact<K: KeySign + HasKeyAlg + ?Sized>(
+    key: &K,
+    header: &JwsHeader<'_>,
+    payload: &[u8],
+) -> Result<String, Error> {
+    let (mut token, sig_len) = sign_input(key, header, payload)?;
+    token.reserve(sig_len);
+    let sig = create_signature(key, token.as_bytes())?;
+    token.push('.');
+    write_base64(&mut token, &sig)?;
+    Ok(token)
+}
+
+/// Sign a payload, producing a JWS in compact serialization with a
+/// detached payload (RFC 7515, Appendix F).
+///
+/// The payload is included in the signature but omitted from the output.
+pub fn sign_compact_detached<K: KeySign + HasKeyAlg + ?Sized>(
+    key: &K,
+    header: &JwsHeader<'_>,
+    payload: &[u8],
+) -> Result<String, Error> {
+    let (input, sig_len) = sign_input(key, header, payload)?;
+    let sig = create_signature(key, input.as_bytes())?;
+    let header_len = input.find('.').unwrap_or(input.len());
+    let mut token = String::with_capacity(header_len + 2 + sig_len);
+    token.push_str(&input[..header_len]);
+    token.push_str("..");
+    write_base64(&mut token, &sig)?;
+    Ok(token)
+}
+
+/// Verify the signature of a JWS in compact serialization.
+///
+/// The `alg` header must correspond to the algorithm of the verification
+/// key, otherwise an error is returned before the signature is checked.
+/// Tokens using the `none` algorithm are rejected.
+///
+/// An empty payload segment is verified as an empty payload. A token with a
+/// detached payload must be verified using [`verify_compact_detached`].
+pub fn verify_compact<K: KeySigVerify + HasKeyAlg + ?Sized>(
+    key: &K,
+    token: &str,
+) -> Result<bool, Error> {
+    let (header, payload, signature) = split_compact(token)?;
+    verify_parts(key, header, payload, signature)
+}
+
+/// Verify the signature of a JWS in compact serialization with a
+/// detached payload (RFC 7515, Appendix F).
+///
+/// The `alg` header is validated as for [`verify_compact`].
+pub fn verify_compact_detached<K: KeySigVerify + HasKeyAlg + ?Sized>(
+    key: &K,
+    token: &str,
+    payload: &[u8],
+) -> Result<bool, Error> {
+    let (header, enc_payload, signature) = split_compact(token)?;
+    if !enc_payload.is_empty() {
+        return Err(err_msg!(Invalid, "Expected a JWS with a detached payload"));
+    }
+    let mut enc_payload = String::new();
+    write_base64(&mut enc_payload, payload)?;
+    verify_parts(key, header, &enc_payload, signature)
+}
+
+/// Decode the payload of a JWS in compact serialization.
+///
+/// The signature is not verified.
+pub fn decode_compact_payload(token: &str) -> Result<Vec<u8>, Error> {
+    let (_, payload, _) = split_compact(token)?;
+    decode_base64(payload)
+}
+
+fn key_signature_type(alg: KeyAlg) -> Result<(SignatureType, &'static str), Error> {
+    match alg {
+        KeyAlg::Ed25519 => Ok((SignatureType::EdDSA, "EdDSA")),
+        KeyAlg::EcCurve(EcCurves::Secp256r1) => Ok((SignatureType::ES256, "ES256")),
+        KeyAlg::EcCurve(EcCurves::Secp256k1) => Ok((SignatureType::ES256K, "ES256K")),
+        KeyAlg::EcCurve(EcCurves::Secp384r1) => Ok((SignatureType::ES384, "ES384")),
+        _ => Err(err_msg!(Unsupported, "Unsupported key algorithm for JWS")),
+    }
+}
+
+fn header_signature_type(alg: &str) -> Result<(SignatureType, KeyAlg), Error> {
+    match alg {
+        "EdDSA" => Ok((SignatureType::EdDSA, KeyAlg::Ed25519)),
+        "ES256" => Ok((SignatureType::ES256, KeyAlg::EcCurve(EcCurves::Secp256r1))),
+        "ES256K" => Ok((SignatureType::ES256K, KeyAlg::EcCurve(EcCurves::Secp256k1))),
+        "ES384" => Ok((SignatureType::ES384, KeyAlg::EcCurve(EcCurves::Secp384r1))),
+        a if a.eq_ignore_ascii_case("none") => {
+            Err(err_msg!(Unsupported, "Unsecured JWS is not supported"))
+        }
+        _ => Err(err_msg!(Unsupported, "Unsupported JWS algorithm")),
+    }
+}
+
+fn sign_input<K: KeySign + HasKeyAlg + ?Sized>(
+    key: &K,
+    header: &JwsHeader<'_>,
+    payload: &[u8],
+) -> Result<(String, usize), Error> {
+    let (sig_type, alg) = key_signature_type(key.algorithm())?;
+    if let Some(hdr_alg) = header.alg {
+        if hdr_alg != alg {
+            return Err(err_msg!(
+                Usage,
+                "JWS algorithm does not match the signing key"
+            ));
+        }
+    }
+    let header = JwsHeader {
+        alg: Some(alg),
+        ..*header
+    };
+    let header = encode_header(&header)?;
+    let sig_len = encoded_len(sig_type.signature_length())? + 1;
+    let mut input =
+        String::with_capacity(encoded_len(header.len())? + encoded_len(payload.len())? + 1);
+    write_base64(&mut input, &header)?;
+    input.push('.');
+    write_base64(&mut input, payload)?;
+    Ok((input, sig_len))
+}
+
+fn create_signature<K: KeySign + HasKeyAlg + ?Sized>(
+    key: &K,
+    input: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let (sig_type, _) = key_signature_type(key.algorithm())?;
+    let mut sig = Vec::with_capacity(sig_type.signature_length());
+    key.write_signature(input, Some(sig_type), &mut sig)?;
+    Ok(sig)
+}
+
+fn verify_parts<K: KeySigVerify + HasKeyAlg + ?Sized>(
+    key: &K,
+    header: &str,
+    payload: &str,
+    signature: &str,
+) -> Result<bool, Error> {
+    let header_json = decode_base64(header)?;
+    let (parsed, _) = serde_json_core::from_slice::<JwsHeader<'_>>(&header_json)
+        .map_err(err_map!(Invalid, "Error parsing JWS header"))?;
+    let alg = parsed
+        .alg
+        .ok_or_else(|| err_msg!(Invalid, "Missing JWS algorithm"))?;
+    let (sig_type, key_alg) = header_signature_type(alg)?;
+    // prevent algorithm confusion: the header must match the verification key
+    if key_alg != key.algorithm() {
+        return Err(err_msg!(
+            Invalid,
+            "JWS algorithm does not match the verification key"
+        ));
+    }
+    let signature = decode_base64(signature)?;
+    let mut input = String::with_capacity(header.len() + payload.len() + 1);
+    input.push_str(header);
+    input.push('.');
+    input.push_str(payload);
+    key.verify_signature(input.as_bytes(), &signature, Some(sig_type))
+}
+
+fn split_compact(token: &str) -> Result<(&str, &str, &str), Error> {
+    let mut parts = token.splitn(4, '.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature), None)
+            if !header.is_empty() && !signature.is_empty() =>
+        {
+            Ok((header, payload, signature))
+        }
+        _ => Err(err_msg!(Invalid, "Invalid JWS compact serialization")),
+    }
+}
+
+fn encode_header(header: &JwsHeader<'_>) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; 128];
+    loop {
+        match serde_json_core::to_slice(header, &mut buf) {
+            Ok(len) => {
+                buf.truncate(len);
+                return Ok(buf);
+            }
+            // the only error produced is an insufficient buffer size
+            Err(_) if buf.len() < 4096 => {
+                let len = buf.len() * 2;
+                buf.resize(len, 0);
+            }
+            Err(_) => return Err(err_msg!(Unexpected, "Error encoding JWS header")),
+        }
+    }
+}
+
+#[inline]
+fn encoded_len(len: usize) -> Result<usize, Error> {
+    base64::encoded_len(len, false).ok_or_else(|| err_msg!(ExceededBuffer))
+}
+
+fn write_base64(out: &mut String, value: &[u8]) -> Result<(), Error> {
+    write!(
+        out,
+        "{}",
+        base64::display::Base64Display::new(
+            value,
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD
+        )
+    )
+    .map_err(|_| err_msg!(Unexpected, "Error encoding base64"))
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; base64::decoded_len_estimate(value.len())];
+    let len = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode_slice(value, &mut buf)
+        .map_err(|_| err_msg!(Invalid, "Base64 decoding error"))?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn sign_verify_compact_ed25519() {
+        use crate::{alg::ed25519::Ed25519KeyPair, repr::KeySecretBytes};
+
+        let kp = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+        ))
+        .unwrap();
+        let header = JwsHeader {
+            kid: Some("key-1"),
+            ..Default::default()
+        };
+        let payload = b"test payload";
+
+        let token = sign_compact(&kp, &header, payload).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+        assert!(verify_compact(&kp, &token).unwrap());
+        assert_eq!(decode_compact_payload(&token).unwrap(), payload);
+
+        let (hdr, _, sig) = split_compact(&token).unwrap();
+        let mut tampered = String::new();
+        tampered.push_str(hdr);
+        tampered.push('.');
+        write_base64(&mut tampered, b"other payload").unwrap();
+        tampered.push('.');
+        tampered.push_str(sig);
+        assert!(!verify_compact(&kp, &tampered).unwrap());
+
+        let detached = sign_compact_detached(&kp, &header, payload).unwrap();
+        assert!(detached.contains(".."));
+        assert!(verify_compact_detached(&kp, &detached, payload).unwrap());
+        assert!(!verify_compact_detached(&kp, &detached, b"other payload").unwrap());
+        assert!(!verify_compact(&kp, &detached).unwrap());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn sign_verify_compact_empty_payload() {
+        use crate::{alg::ed25519::Ed25519KeyPair, repr::KeyGen};
+
+        let kp = Ed25519KeyPair::random().unwrap();
+        let token = sign_compact(&kp, &JwsHeader::default(), b"").unwrap();
+        assert!(token.contains(".."));
+        assert!(verify_compact(&kp, &token).unwrap());
+        assert_eq!(decode_compact_payload(&token).unwrap(), b"");
+        assert!(verify_compact_detached(&kp, &token, b"").unwrap());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn reject_alg_none() {
+        use crate::{alg::ed25519::Ed25519KeyPair, repr::KeyGen};
+
+        let kp = Ed25519KeyPair::random().unwrap();
+        let mut token = String::new();
+        write_base64(&mut token, br#"{"alg":"none"}"#).unwrap();
+        token.push('.');
+        write_base64(&mut token, b"test payload").unwrap();
+        token.push_str(".AA");
+        let err = verify_compact(&kp, &token).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unsupported);
+
+        let header = JwsHeader {
+            alg: Some("none"),
+            ..Default::default()
+        };
+        assert!(sign_compact(&kp, &header, b"test payload").is_err());
+    }
+
+    #[cfg(all(feature = "ed25519", feature = "p256"))]
+    #[test]
+    fn reject_alg_mismatch() {
+        use crate::{alg::ed25519::Ed25519KeyPair, alg::p256::P256KeyPair, repr::KeyGen};
+
+        let ed_key = Ed25519KeyPair::random().unwrap();
+        let p256_key = P256KeyPair::random().unwrap();
+        let token = sign_compact(&p256_key, &JwsHeader::default(), b"test payload").unwrap();
+        assert!(verify_compact(&p256_key, &token).unwrap());
+        // the signature lengths are identical, but the header must be rejected
+        let err = verify_compact(&ed_key, &token).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+    }
+}
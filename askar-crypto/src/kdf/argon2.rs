@@ -1,8 +1,15 @@
 //! Argon2 key derivation from a password
 
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+
 pub use argon2::{Algorithm, Version};
 
-use super::KeyDerivation;
+use argon2::{password_hash::PasswordHash, PasswordVerifier};
+#[cfg(feature = "alloc")]
+use argon2::{password_hash::SaltString, PasswordHasher};
+
+use super::{derive_zeroize_on_error, KeyDerivation};
 use crate::{
     error::Error,
     generic_array::typenum::{Unsigned, U16},
@@ -66,25 +73,73 @@ impl<'a> Argon2<'a> {
             params,
         })
     }
-}
 
-impl KeyDerivation for Argon2<'_> {
-    fn derive_key_bytes(&mut self, key_output: &mut [u8]) -> Result<(), Error> {
-        if key_output.len() > u32::MAX as usize {
-            return Err(err_msg!(Usage, "Output length exceeds max for argon2 hash"));
-        }
+    fn hasher(&self, output_len: usize) -> Result<argon2::Argon2<'static>, Error> {
         let mut pbuild = argon2::ParamsBuilder::new();
         pbuild
             .p_cost(self.params.parallelism)
             .m_cost(self.params.mem_cost)
-            .t_cost(self.params.time_cost);
-        argon2::Argon2::new(
+            .t_cost(self.params.time_cost)
+            .output_len(output_len);
+        let params = pbuild
+            .build()
+            .map_err(|_| err_msg!(Usage, "Invalid parameters for argon2 hash"))?;
+        Ok(argon2::Argon2::new(
             self.params.alg,
             self.params.version,
-            pbuild.build().unwrap(),
-        )
-        .hash_password_into(self.password, self.salt, key_output)
-        .map_err(|_| err_msg!(Unexpected, "Error deriving key"))
+            params,
+        ))
+    }
+
+    /// Derive a hash of the password with the given output length, returning
+    /// it as an encoded string in PHC format, such as
+    /// `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`.
+    ///
+    /// The salt and hash are encoded using unpadded base64 per the PHC string
+    /// format specification. The result may be checked using [`verify`].
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn derive_encoded(&self, output_len: usize) -> Result<String, Error> {
+        let salt = SaltString::encode_b64(self.salt)
+            .map_err(|_| err_msg!(Usage, "Invalid salt for argon2 hash"))?;
+        let hash = self
+            .hasher(output_len)?
+            .hash_password(self.password, &salt)
+            .map_err(|_| err_msg!(Unexpected, "Error deriving key"))?;
+        Ok(hash.to_string())
+    }
+}
+
+impl KeyDerivation for Argon2<'_> {
+    fn derive_key_bytes(&mut self, key_output: &mut [u8]) -> Result<(), Error> {
+        if key_output.len() > u32::MAX as usize {
+            return Err(err_msg!(Usage, "Output length exceeds max for argon2 hash"));
+        }
+        let hasher = self.hasher(key_output.len())?;
+        derive_zeroize_on_error(key_output, |key_output| {
+            hasher
+                .hash_password_into(self.password, self.salt, key_output)
+                .map_err(|_| err_msg!(Unexpected, "Error deriving key"))
+        })
+    }
+}
+
+/// Verify a password against an encoded Argon2 hash in PHC string format,
+/// such as `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`.
+///
+/// The algorithm and parameters are taken from the encoded hash, and the
+/// derived hash is compared in constant time. Returns `Ok(false)` if the
+/// password does not match, or an error if the encoded hash is malformed.
+pub fn verify(password: &[u8], encoded_hash: &str) -> Result<bool, Error> {
+    let hash = PasswordHash::new(encoded_hash)
+        .map_err(|_| err_msg!(Invalid, "Invalid encoded argon2 hash"))?;
+    if Algorithm::try_from(hash.algorithm).is_err() || hash.salt.is_none() || hash.hash.is_none() {
+        return Err(err_msg!(Invalid, "Invalid encoded argon2 hash"));
+    }
+    match argon2::Argon2::default().verify_password(password, &hash) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(_) => Err(err_msg!(Invalid, "Invalid encoded argon2 hash")),
     }
 }
 
@@ -106,4 +161,49 @@ mod tests {
             hex!("9ef87bcf828c46c0136a0d1d9e391d713f75b327c6dc190455bd36c1bae33259")
         );
     }
+
+    #[test]
+    fn verify_encoded() {
+        use alloc::string::ToString;
+        use argon2::{password_hash::SaltString, PasswordHasher};
+
+        let salt = SaltString::encode_b64(b"long enough salt").unwrap();
+        let encoded = argon2::Argon2::default()
+            .hash_password(b"my password", &salt)
+            .unwrap()
+            .to_string();
+        assert!(encoded.starts_with("$argon2id$v=19$"));
+        assert!(verify(b"my password", &encoded).unwrap());
+        assert!(!verify(b"other password", &encoded).unwrap());
+    }
+
+    #[test]
+    fn verify_malformed() {
+        for encoded in [
+            "",
+            "argon2id",
+            "$argon2id$v=19$m=19456,t=2,p=1",
+            "$argon2id$v=19$m=x,t=2,p=1$bG9uZyBlbm91Z2ggc2FsdA$AAAA",
+            "$pbkdf2-sha256$i=1000$bG9uZyBlbm91Z2ggc2FsdA$AAAAAAAAAAAAAAAAAAAAAA",
+        ] {
+            let err = verify(b"my password", encoded).unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        }
+    }
+
+    #[test]
+    fn derive_encoded_expected() {
+        let pass = b"my password";
+        let salt = b"long enough salt";
+        let encoded = Argon2::new(pass, salt, PARAMS_INTERACTIVE)
+            .unwrap()
+            .derive_encoded(32)
+            .unwrap();
+        assert_eq!(
+            encoded,
+            "$argon2i$v=19$m=32768,t=4,p=1$bG9uZyBlbm91Z2ggc2FsdA\
+            $nvh7z4KMRsATag0dnjkdcT91syfG3BkEVb02wbrjMlk"
+        );
+        assert!(verify(pass, &encoded).unwrap());
+    }
 }
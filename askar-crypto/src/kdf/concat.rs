@@ -3,9 +3,11 @@
 use core::{fmt::Debug, marker::PhantomData};
 
 use digest::{Digest, FixedOutputReset};
+use zeroize::Zeroize;
 
 use crate::generic_array::{typenum::Unsigned, GenericArray};
 
+use super::derive_zeroize_on_error;
 use crate::{buffer::WriteBuffer, error::Error};
 
 /// A struct providing the key derivation for a particular hash function
@@ -35,25 +37,28 @@ where
     pub fn derive_key(
         message: &[u8],
         params: ConcatKDFParams<'_>,
-        mut output: &mut [u8],
+        output: &mut [u8],
     ) -> Result<(), Error> {
         let output_len = output.len();
         if output_len > H::OutputSize::USIZE * (u32::MAX as usize) - 1 {
             return Err(err_msg!(Usage, "Exceeded max output size for concat KDF"));
         }
-        let mut hasher = ConcatKDFHash::<H>::new();
-        let mut remain = output_len;
-        while remain > 0 {
-            hasher.start_pass();
-            hasher.hash_message(message);
-            hasher.hash_params(params);
-            let hashed = hasher.finish_pass();
-            let cp_size = hashed.len().min(remain);
-            output[..cp_size].copy_from_slice(&hashed[..cp_size]);
-            output = &mut output[cp_size..];
-            remain -= cp_size;
-        }
-        Ok(())
+        derive_zeroize_on_error(output, |mut output| {
+            let mut hasher = ConcatKDFHash::<H>::new();
+            let mut remain = output_len;
+            while remain > 0 {
+                hasher.start_pass();
+                hasher.hash_message(message);
+                hasher.hash_params(params);
+                let mut hashed = hasher.finish_pass();
+                let cp_size = hashed.len().min(remain);
+                output[..cp_size].copy_from_slice(&hashed[..cp_size]);
+                hashed.zeroize();
+                output = &mut output[cp_size..];
+                remain -= cp_size;
+            }
+            Ok(())
+        })
     }
 }
 
@@ -98,6 +103,9 @@ impl<H: Digest> ConcatKDFHash<H> {
     }
 
     /// Complete this pass of the key derivation, returning the result
+    ///
+    /// The hasher is reset for the next pass. The caller is responsible for
+    /// zeroizing the returned output.
     pub fn finish_pass(&mut self) -> GenericArray<u8, H::OutputSize>
     where
         H: FixedOutputReset,
@@ -92,6 +92,7 @@ impl<Key: KeyExchange + ?Sized> KeyDerivation for Ecdh1PU<'_, Key> {
             prv_info: &[],
         });
 
+        // the output is only written once the derivation can no longer fail
         let mut key = kdf.finish_pass();
         key_output.copy_from_slice(&key[..output_len]);
         key.zeroize();
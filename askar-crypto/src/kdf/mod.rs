@@ -2,7 +2,14 @@
 
 #[cfg(feature = "alloc")]
 use crate::buffer::SecretBytes;
-use crate::{buffer::WriteBuffer, error::Error};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use self::concat::{ConcatKDF, ConcatKDFParams};
+use crate::{
+    buffer::{WriteBuffer, Writer},
+    error::Error,
+};
 
 #[cfg(feature = "argon2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "argon2")))]
@@ -14,6 +21,10 @@ pub mod ecdh_1pu;
 
 pub mod ecdh_es;
 
+#[cfg(feature = "hmac")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hmac")))]
+pub mod pbkdf2;
+
 /// Trait for keys supporting Diffie-Helman key exchange
 pub trait KeyExchange<Rhs: ?Sized = Self> {
     /// Perform a key exchange, writing the result to the provided buffer.
@@ -27,6 +38,43 @@ pub trait KeyExchange<Rhs: ?Sized = Self> {
         self.write_key_exchange(other, &mut buf)?;
         Ok(buf)
     }
+
+    /// Perform a key exchange and derive a key filling the output buffer,
+    /// using the shared secret as the input to the Concat KDF with SHA-256.
+    ///
+    /// The raw shared secret is zeroized once the derivation is complete.
+    fn write_key_exchange_kdf(
+        &self,
+        other: &Rhs,
+        params: ConcatKDFParams<'_>,
+        output: &mut [u8],
+    ) -> Result<(), Error> {
+        // large enough for the shared secret of any supported curve
+        let mut secret = [0u8; 128];
+        let result = {
+            let mut buf = Writer::from_slice(&mut secret[..]);
+            self.write_key_exchange(other, &mut buf)
+                .map(|_| buf.position())
+        }
+        .and_then(|len| ConcatKDF::<Sha256>::derive_key(&secret[..len], params, output));
+        secret.zeroize();
+        result
+    }
+
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    /// Perform a key exchange and derive a new key of `output_len` bytes,
+    /// using the shared secret as the input to the Concat KDF with SHA-256.
+    fn key_exchange_with_kdf(
+        &self,
+        other: &Rhs,
+        params: ConcatKDFParams<'_>,
+        output_len: usize,
+    ) -> Result<SecretBytes, Error> {
+        let mut output = SecretBytes::new_with(output_len, |_| ());
+        self.write_key_exchange_kdf(other, params, output.as_mut())?;
+        Ok(output)
+    }
 }
 
 /// Trait for instantiation from a key exchange
@@ -41,6 +89,25 @@ pub trait KeyDerivation {
     fn derive_key_bytes(&mut self, key_output: &mut [u8]) -> Result<(), Error>;
 }
 
+/// Run a key derivation writing to `output`, wiping the output buffer if
+/// the derivation fails.
+///
+/// Key derivation implementations uphold the invariant that no derived key
+/// material outlives the call except in a successful output: intermediate
+/// block buffers are zeroized on completion and partial outputs are zeroized
+/// on error. Hasher and HMAC states from the `digest` crates do not support
+/// zeroization, so these are reset or dropped as soon as they are finished.
+pub(crate) fn derive_zeroize_on_error(
+    output: &mut [u8],
+    derive: impl FnOnce(&mut [u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let result = derive(output);
+    if result.is_err() {
+        output.zeroize();
+    }
+    result
+}
+
 /// Trait for instantiation from a key derivation
 pub trait FromKeyDerivation {
     /// Create a new instance of a key from a key derivation
@@ -48,3 +115,76 @@ pub trait FromKeyDerivation {
     where
         Self: Sized;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_partial_output_zeroized() {
+        let mut output = [0u8; 8];
+        derive_zeroize_on_error(&mut output, |out| {
+            out[..4].fill(0xff);
+            Err(err_msg!(Unexpected))
+        })
+        .unwrap_err();
+        assert_eq!(output, [0u8; 8]);
+
+        derive_zeroize_on_error(&mut output, |out| {
+            out.fill(0xff);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(output, [0xffu8; 8]);
+    }
+
+    #[cfg(all(feature = "alloc", feature = "ed25519"))]
+    #[test]
+    fn key_exchange_with_kdf_expected() {
+        use crate::{
+            alg::x25519::X25519KeyPair,
+            repr::{KeyPublicBytes, KeySecretBytes},
+        };
+
+        // keys from RFC 8037 appendix A.6
+        let ephem_sk = X25519KeyPair::from_secret_bytes(&hex!(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"
+        ))
+        .unwrap();
+        let bob_pk = X25519KeyPair::from_public_bytes(&hex!(
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        ))
+        .unwrap();
+        let params = ConcatKDFParams {
+            alg: b"A256GCM",
+            apu: b"Alice",
+            apv: b"Bob",
+            pub_info: &512u32.to_be_bytes(),
+            prv_info: &[],
+        };
+        let key = ephem_sk.key_exchange_with_kdf(&bob_pk, params, 64).unwrap();
+        assert_eq!(
+            key,
+            &hex!(
+                "b79f88d970330ca264ec741c1a43bb7a4c7a44bd36b1bf27fad337c61618d526"
+                "4cdb064ec2f0548e2a0e5dc8e024b6f6cc54508ed0e699968d424119e9397d7b"
+            )[..]
+        );
+
+        let mut output = [0u8; 32];
+        ephem_sk
+            .write_key_exchange_kdf(
+                &bob_pk,
+                ConcatKDFParams {
+                    pub_info: &256u32.to_be_bytes(),
+                    ..params
+                },
+                &mut output,
+            )
+            .unwrap();
+        assert_eq!(
+            output,
+            hex!("2f3636918ddb57fe0b3569113f19c4b6c518c2843f8930f05db25cd55dee53c1")
+        );
+    }
+}
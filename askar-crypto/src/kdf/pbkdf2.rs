@@ -0,0 +1,107 @@
+//! PBKDF2 password-based key derivation (RFC 8018)
+
+use core::{fmt::Debug, marker::PhantomData};
+
+use digest::{core_api::BlockSizeUser, Digest};
+use hmac::{Mac, SimpleHmac};
+use zeroize::Zeroize;
+
+use super::{derive_zeroize_on_error, KeyDerivation};
+use crate::error::Error;
+
+/// PBKDF2 key derivation using HMAC with a particular hash function
+#[derive(Debug)]
+pub struct Pbkdf2<'a, H> {
+    password: &'a [u8],
+    salt: &'a [u8],
+    rounds: u32,
+    _hash: PhantomData<H>,
+}
+
+impl<'a, H> Pbkdf2<'a, H> {
+    /// Create a new PBKDF2 key derivation instance
+    pub fn new(password: &'a [u8], salt: &'a [u8], rounds: u32) -> Self {
+        Self {
+            password,
+            salt,
+            rounds,
+            _hash: PhantomData,
+        }
+    }
+}
+
+impl<H> KeyDerivation for Pbkdf2<'_, H>
+where
+    H: Digest + BlockSizeUser + Clone,
+{
+    fn derive_key_bytes(&mut self, key_output: &mut [u8]) -> Result<(), Error> {
+        if self.rounds == 0 {
+            return Err(err_msg!(Usage, "PBKDF2 requires at least one round"));
+        }
+        let prf = <SimpleHmac<H> as Mac>::new_from_slice(self.password)
+            .map_err(|_| err_msg!(Unexpected, "Error creating HMAC"))?;
+        let block_len = <H as Digest>::output_size();
+        if key_output.len() / block_len >= u32::MAX as usize {
+            return Err(err_msg!(Usage, "Exceeded max output size for PBKDF2"));
+        }
+        derive_zeroize_on_error(key_output, |key_output| {
+            for (index, chunk) in key_output.chunks_mut(block_len).enumerate() {
+                let mut mac = prf.clone();
+                mac.update(self.salt);
+                mac.update(&(index as u32 + 1).to_be_bytes());
+                let mut block = mac.finalize().into_bytes();
+                let mut result = block.clone();
+                for _ in 1..self.rounds {
+                    let mut mac = prf.clone();
+                    mac.update(&block);
+                    block = mac.finalize().into_bytes();
+                    result
+                        .iter_mut()
+                        .zip(block.iter())
+                        .for_each(|(r, b)| *r ^= b);
+                }
+                chunk.copy_from_slice(&result[..chunk.len()]);
+                block.as_mut_slice().zeroize();
+                result.as_mut_slice().zeroize();
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Sha256, Sha512};
+
+    use super::*;
+
+    #[test]
+    fn expected_pbkdf2_sha256() {
+        let mut output = [0u8; 40];
+        Pbkdf2::<Sha256>::new(b"password", b"salt", 4096)
+            .derive_key_bytes(&mut output)
+            .unwrap();
+        assert_eq!(
+            output,
+            hex!(
+                "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+                "f7ad98c1b458ce3f"
+            )
+        );
+    }
+
+    #[test]
+    fn expected_pbkdf2_sha512() {
+        let mut output = [0u8; 32];
+        Pbkdf2::<Sha512>::new(b"password", b"salt", 2)
+            .derive_key_bytes(&mut output)
+            .unwrap();
+        assert_eq!(
+            output,
+            hex!("e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53c")
+        );
+        assert!(Pbkdf2::<Sha512>::new(b"password", b"salt", 0)
+            .derive_key_bytes(&mut output)
+            .is_err());
+    }
+}
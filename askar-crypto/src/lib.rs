@@ -29,10 +29,22 @@ pub mod backend;
 
 pub mod buffer;
 
+#[cfg(feature = "cose")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cose")))]
+pub mod cose;
+
+#[cfg(any(feature = "ed25519", feature = "ec_curves"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "ed25519", feature = "ec_curves"))))]
+pub mod derive;
+
 pub mod encrypt;
 
 pub mod jwk;
 
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod jws;
+
 pub mod kdf;
 
 pub mod random;
@@ -40,3 +52,5 @@ pub mod random;
 pub mod sign;
 
 pub mod repr;
+
+pub mod util;
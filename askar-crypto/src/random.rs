@@ -130,6 +130,46 @@ impl RandomDet {
     }
 }
 
+/// A generator for key material expanded from a seed using HKDF-SHA256
+///
+/// The seed is used as the input key material, with the salt
+/// `ASKAR-SEED-KEYGEN-SALT-`. Each read produces the output of
+/// HKDF-Expand with the info parameter followed by a 4-byte big-endian
+/// counter, which starts at zero and is incremented after each read.
+#[cfg(all(feature = "hkdf", any(feature = "ed25519", feature = "p256")))]
+pub(crate) struct SeedKeyGen {
+    hkdf: hkdf::Hkdf<sha2::Sha256>,
+    info: &'static [u8],
+    counter: u32,
+}
+
+#[cfg(all(feature = "hkdf", any(feature = "ed25519", feature = "p256")))]
+impl SeedKeyGen {
+    const SALT: &'static [u8] = b"ASKAR-SEED-KEYGEN-SALT-";
+
+    /// Construct a new `SeedKeyGen` from a seed value of at least 32 bytes
+    pub fn new(seed: &[u8], info: &'static [u8]) -> Result<Self, Error> {
+        if seed.len() < 32 {
+            return Err(err_msg!(Usage, "Insufficient length for seed"));
+        }
+        Ok(Self {
+            hkdf: hkdf::Hkdf::new(Some(Self::SALT), seed),
+            info,
+            counter: 0,
+        })
+    }
+}
+
+#[cfg(all(feature = "hkdf", any(feature = "ed25519", feature = "p256")))]
+impl KeyMaterial for SeedKeyGen {
+    fn read_okm(&mut self, buf: &mut [u8]) {
+        self.hkdf
+            .expand_multi_info(&[self.info, &self.counter.to_be_bytes()], buf)
+            .expect("HDKF expand failure");
+        self.counter += 1;
+    }
+}
+
 #[cfg(all(feature = "alloc", feature = "getrandom"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 /// Create a new `SecretBytes` instance with random data.
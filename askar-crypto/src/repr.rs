@@ -37,12 +37,18 @@ pub trait ToSecretBytes {
     /// Get the length of a secret key
     fn secret_bytes_length(&self) -> Result<usize, Error>;
 
+    /// Determine whether the key has secret key material. Public-only keys
+    /// return a `MissingSecretKey` error for operations requiring the secret.
+    fn has_secret(&self) -> bool;
+
     /// Write the key secret bytes to a buffer.
     fn write_secret_bytes(&self, out: &mut dyn WriteBuffer) -> Result<(), Error>;
 
     #[cfg(feature = "alloc")]
     #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     /// Write the key secret bytes to a new allocated buffer.
+    ///
+    /// The returned buffer is zeroized on drop.
     fn to_secret_bytes(&self) -> Result<SecretBytes, Error> {
         let mut buf = SecretBytes::with_capacity(128);
         self.write_secret_bytes(&mut buf)?;
@@ -58,6 +64,10 @@ where
         Ok(<Self as KeyMeta>::KeySize::USIZE)
     }
 
+    fn has_secret(&self) -> bool {
+        self.with_secret_bytes(|buf| buf.is_some())
+    }
+
     fn write_secret_bytes(&self, out: &mut dyn WriteBuffer) -> Result<(), Error> {
         self.with_secret_bytes(|buf| {
             if let Some(buf) = buf {
@@ -135,6 +145,8 @@ pub trait KeypairBytes {
     #[cfg(feature = "alloc")]
     #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     /// Write the keypair bytes to a new allocated buffer.
+    ///
+    /// The returned buffer is zeroized on drop.
     fn to_keypair_bytes(&self) -> Result<SecretBytes, Error> {
         let mut buf = SecretBytes::with_capacity(128);
         self.to_keypair_bytes_buffer(&mut buf)?;
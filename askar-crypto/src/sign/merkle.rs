@@ -0,0 +1,230 @@
+//! Signatures over a Merkle tree of messages
+//!
+//! The tree is built over SHA-256 hashes with domain separation between
+//! leaves and interior nodes, following RFC 6962: a leaf hash is computed as
+//! `SHA-256(0x00 || message)` and an interior node as
+//! `SHA-256(0x01 || left || right)`. When a level has an odd number of
+//! nodes, the last node is promoted to the next level unchanged rather
+//! than being paired with itself.
+
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use super::{KeySigVerify, KeySign, SignatureType};
+use crate::{buffer::SecretBytes, error::Error};
+
+/// The length of a Merkle tree hash
+pub const MERKLE_HASH_LENGTH: usize = 32;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// A Merkle tree hash value
+pub type MerkleHash = [u8; MERKLE_HASH_LENGTH];
+
+/// An inclusion proof for a single message in a Merkle tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// The index of the message in the list of leaves
+    pub index: usize,
+    /// The total number of leaves in the tree
+    pub leaf_count: usize,
+    /// The sibling hashes from the leaf level up to the root
+    pub path: Vec<MerkleHash>,
+}
+
+impl MerkleProof {
+    /// Create an inclusion proof for the message at `index`
+    pub fn new<M: AsRef<[u8]>>(messages: &[M], index: usize) -> Result<Self, Error> {
+        if index >= messages.len() {
+            return Err(err_msg!(Usage, "Merkle proof index out of range"));
+        }
+        let mut path = Vec::new();
+        let mut level = leaf_hashes(messages);
+        let mut pos = index;
+        while level.len() > 1 {
+            let sibling = pos ^ 1;
+            if sibling < level.len() {
+                path.push(level[sibling]);
+            }
+            level = next_level(&level);
+            pos /= 2;
+        }
+        Ok(Self {
+            index,
+            leaf_count: messages.len(),
+            path,
+        })
+    }
+
+    /// Compute the root hash implied by this proof for a message
+    pub fn root(&self, message: &[u8]) -> Result<MerkleHash, Error> {
+        if self.index >= self.leaf_count {
+            return Err(err_msg!(Invalid, "Invalid Merkle proof index"));
+        }
+        let mut hash = leaf_hash(message);
+        let mut path = self.path.iter();
+        let mut pos = self.index;
+        let mut width = self.leaf_count;
+        while width > 1 {
+            if pos % 2 == 1 {
+                let sibling = path.next().ok_or_else(merkle_proof_len_err)?;
+                hash = node_hash(sibling, &hash);
+            } else if pos + 1 < width {
+                let sibling = path.next().ok_or_else(merkle_proof_len_err)?;
+                hash = node_hash(&hash, sibling);
+            }
+            pos /= 2;
+            width = (width + 1) / 2;
+        }
+        if path.next().is_some() {
+            return Err(merkle_proof_len_err());
+        }
+        Ok(hash)
+    }
+}
+
+/// Compute the Merkle root hash of a non-empty list of messages
+pub fn merkle_root<M: AsRef<[u8]>>(messages: &[M]) -> Result<MerkleHash, Error> {
+    if messages.is_empty() {
+        return Err(err_msg!(
+            Usage,
+            "Cannot compute the Merkle root of no messages"
+        ));
+    }
+    let mut level = leaf_hashes(messages);
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    Ok(level[0])
+}
+
+/// Compute the Merkle root hash of a list of messages and sign it,
+/// returning the root hash and the signature
+pub fn sign_merkle_root<K: KeySign + ?Sized, M: AsRef<[u8]>>(
+    key: &K,
+    messages: &[M],
+    sig_type: Option<SignatureType>,
+) -> Result<(MerkleHash, SecretBytes), Error> {
+    let root = merkle_root(messages)?;
+    let signature = key.create_signature(&root, sig_type)?;
+    Ok((root, signature))
+}
+
+/// Verify that a message is included in a Merkle tree whose root hash was
+/// signed by the given key
+///
+/// The root hash is recomputed from the message and the inclusion proof,
+/// and the signature is checked against the recomputed root.
+pub fn verify_merkle_inclusion<K: KeySigVerify + ?Sized>(
+    key: &K,
+    message: &[u8],
+    proof: &MerkleProof,
+    signature: &[u8],
+    sig_type: Option<SignatureType>,
+) -> Result<bool, Error> {
+    let root = proof.root(message)?;
+    key.verify_signature(&root, signature, sig_type)
+}
+
+fn leaf_hash(message: &[u8]) -> MerkleHash {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(message)
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &MerkleHash, right: &MerkleHash) -> MerkleHash {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+fn leaf_hashes<M: AsRef<[u8]>>(messages: &[M]) -> Vec<MerkleHash> {
+    messages.iter().map(|m| leaf_hash(m.as_ref())).collect()
+}
+
+fn next_level(level: &[MerkleHash]) -> Vec<MerkleHash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn merkle_proof_len_err() -> Error {
+    err_msg!(Invalid, "Invalid Merkle proof length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merkle_root_expected() {
+        assert!(merkle_root::<&[u8]>(&[]).is_err());
+        // the root of a single leaf is the leaf hash
+        assert_eq!(
+            merkle_root(&[b""]).unwrap(),
+            hex!("6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d")
+        );
+        let messages = [&b"a"[..], b"b", b"c"];
+        let ab = node_hash(&leaf_hash(b"a"), &leaf_hash(b"b"));
+        assert_eq!(
+            merkle_root(&messages).unwrap(),
+            node_hash(&ab, &leaf_hash(b"c"))
+        );
+        // a duplicated final leaf changes the root
+        assert_ne!(
+            merkle_root(&messages).unwrap(),
+            merkle_root(&[&b"a"[..], b"b", b"c", b"c"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn merkle_proof_round_trip() {
+        let messages: Vec<Vec<u8>> = (0u8..11).map(|i| vec![i; i as usize]).collect();
+        for count in 1..=messages.len() {
+            let messages = &messages[..count];
+            let root = merkle_root(messages).unwrap();
+            for (index, message) in messages.iter().enumerate() {
+                let proof = MerkleProof::new(messages, index).unwrap();
+                assert_eq!(proof.root(message).unwrap(), root);
+                if count > 1 {
+                    assert_ne!(proof.root(b"other").unwrap(), root);
+                    let mut short = proof.clone();
+                    short.path.pop();
+                    assert!(short.root(message).is_err());
+                }
+                let mut long = proof.clone();
+                long.path.push([0u8; MERKLE_HASH_LENGTH]);
+                assert!(long.root(message).is_err());
+            }
+            assert!(MerkleProof::new(messages, count).is_err());
+        }
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn sign_verify_merkle_inclusion() {
+        use crate::{alg::ed25519::Ed25519KeyPair, repr::KeySecretBytes};
+
+        let key = Ed25519KeyPair::from_secret_bytes(&[1u8; 32]).unwrap();
+        let messages = [&b"first"[..], b"second", b"third"];
+        let (root, sig) = sign_merkle_root(&key, &messages, None).unwrap();
+        assert_eq!(root, merkle_root(&messages).unwrap());
+        let proof = MerkleProof::new(&messages, 1).unwrap();
+        assert!(verify_merkle_inclusion(&key, b"second", &proof, &sig, None).unwrap());
+        assert!(!verify_merkle_inclusion(&key, b"third", &proof, &sig, None).unwrap());
+        let other = Ed25519KeyPair::from_secret_bytes(&[2u8; 32]).unwrap();
+        assert!(!verify_merkle_inclusion(&other, b"second", &proof, &sig, None).unwrap());
+    }
+}
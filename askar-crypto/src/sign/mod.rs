@@ -0,0 +1,258 @@
+//! Signature traits and parameters
+//!
+//! Signing keys which are not held in memory, such as keys stored in a
+//! hardware security module or secure element, may be used with the JWS and
+//! COSE helpers by implementing [`KeySign`], [`KeySigVerify`] and
+//! [`HasKeyAlg`](crate::alg::HasKeyAlg). Only `write_signature`,
+//! `verify_signature` and `algorithm` must be provided. Such keys may be
+//! passed to the helpers directly or as a [`KeySigner`] or [`KeyVerifier`]
+//! trait object.
+
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use crate::buffer::SecretBytes;
+use crate::{
+    alg::{normalize_alg, HasKeyAlg},
+    buffer::WriteBuffer,
+    error::{Error, ErrorKind},
+};
+
+/// Signature creation operations
+pub trait KeySign: KeySigVerify {
+    /// Create a signature of the requested type and write it to the
+    /// provided buffer.
+    fn write_signature(
+        &self,
+        message: &[u8],
+        sig_type: Option<SignatureType>,
+        out: &mut dyn WriteBuffer,
+    ) -> Result<(), Error>;
+
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    /// Create a signature of the requested type and return an allocated
+    /// buffer.
+    fn create_signature(
+        &self,
+        message: &[u8],
+        sig_type: Option<SignatureType>,
+    ) -> Result<SecretBytes, Error> {
+        let mut buf = SecretBytes::with_capacity(128);
+        self.write_signature(message, sig_type, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Signature verification operations
+pub trait KeySigVerify {
+    /// Check the validity of signature over a message with the
+    /// specified signature type.
+    fn verify_signature(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        sig_type: Option<SignatureType>,
+    ) -> Result<bool, Error>;
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod merkle;
+
+/// A signing key usable as a trait object
+///
+/// This trait is implemented for every type supporting [`KeySign`] and
+/// [`HasKeyAlg`], allowing keys of different types, including externally
+/// held keys, to be passed to the signing helpers as `&dyn KeySigner`.
+pub trait KeySigner: KeySign + HasKeyAlg {}
+
+impl<K: KeySign + HasKeyAlg + ?Sized> KeySigner for K {}
+
+/// A verification key usable as a trait object
+///
+/// This trait is implemented for every type supporting [`KeySigVerify`] and
+/// [`HasKeyAlg`], allowing keys to be passed to the verification helpers as
+/// `&dyn KeyVerifier`.
+pub trait KeyVerifier: KeySigVerify + HasKeyAlg {}
+
+impl<K: KeySigVerify + HasKeyAlg + ?Sized> KeyVerifier for K {}
+
+/// Verify a signature whose type is not known in advance, returning the
+/// first supported signature type for which the signature is valid.
+///
+/// Signature types over the full message are tried before the pre-hashed
+/// variants. Note that accepting a pre-hashed signature allows a signature
+/// over any message with the same digest to be presented with the digest as
+/// the message, so this method should only be used in verification contexts
+/// where the signature type is genuinely unknown. When the type is known, it
+/// should always be passed to [`KeySigVerify::verify_signature`] instead.
+pub fn verify_signature_any<K: KeySigVerify + ?Sized>(
+    key: &K,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<Option<SignatureType>, Error> {
+    for sig_type in [
+        SignatureType::EdDSA,
+        SignatureType::ES256,
+        SignatureType::ES256K,
+        SignatureType::ES384,
+        SignatureType::ES256ph,
+        SignatureType::ES256Kph,
+        SignatureType::ES384ph,
+    ] {
+        if signature.len() != sig_type.signature_length() {
+            continue;
+        }
+        match key.verify_signature(message, signature, Some(sig_type)) {
+            Ok(true) => return Ok(Some(sig_type)),
+            Ok(false) => (),
+            Err(err) if err.kind() == ErrorKind::Unsupported => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(None)
+}
+
+/// Supported signature types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SignatureType {
+    /// Standard signature output for ed25519
+    EdDSA,
+    /// Elliptic curve DSA using P-256 and SHA-256
+    ES256,
+    /// Elliptic curve DSA using P-256 and pre-hashed input
+    ES256ph,
+    /// Elliptic curve DSA using K-256 and SHA-256
+    ES256K,
+    /// Elliptic curve DSA using K-256 and pre-hashed input
+    ES256Kph,
+    /// Elliptic curve DSA using P-384 and SHA-384
+    ES384,
+    /// Elliptic curve DSA using P-384 and pre-hashed input
+    ES384ph,
+}
+
+impl FromStr for SignatureType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalize_alg(s)? {
+            a if a == "eddsa" => Ok(Self::EdDSA),
+            a if a == "es256" => Ok(Self::ES256),
+            a if a == "es256ph" => Ok(Self::ES256ph),
+            a if a == "es256k" => Ok(Self::ES256K),
+            a if a == "es256kph" => Ok(Self::ES256Kph),
+            a if a == "es384" => Ok(Self::ES384),
+            a if a == "es384ph" => Ok(Self::ES384ph),
+            _ => Err(err_msg!(Unsupported, "Unknown signature algorithm")),
+        }
+    }
+}
+
+impl SignatureType {
+    /// Get the JOSE algorithm identifier for the signature type, if defined.
+    /// The pre-hashed signature types have no registered identifier.
+    pub const fn jose_alg(&self) -> Option<&'static str> {
+        match self {
+            Self::EdDSA => Some("EdDSA"),
+            Self::ES256 => Some("ES256"),
+            Self::ES256K => Some("ES256K"),
+            Self::ES384 => Some("ES384"),
+            Self::ES256ph | Self::ES256Kph | Self::ES384ph => None,
+        }
+    }
+
+    /// Get the length of the signature output.
+    pub const fn signature_length(&self) -> usize {
+        match self {
+            Self::EdDSA | Self::ES256 | Self::ES256ph | Self::ES256K | Self::ES256Kph => 64,
+            Self::ES384 | Self::ES384ph => 96,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ec_curves", feature = "getrandom"))]
+mod tests {
+    use super::*;
+    use crate::{alg::p256::P256KeyPair, repr::KeyGen};
+
+    #[test]
+    fn verify_any_p256() {
+        let kp = P256KeyPair::random().unwrap();
+        let message = b"message";
+        let sig = kp.sign(message).unwrap();
+        assert_eq!(
+            verify_signature_any(&kp, message, &sig).unwrap(),
+            Some(SignatureType::ES256)
+        );
+        let hashed = <sha2::Sha256 as sha2::Digest>::digest(message);
+        let sig = kp.sign_prehashed(&hashed).unwrap();
+        assert_eq!(
+            verify_signature_any(&kp, &hashed, &sig).unwrap(),
+            Some(SignatureType::ES256ph)
+        );
+        assert_eq!(verify_signature_any(&kp, b"other", &sig).unwrap(), None);
+        assert_eq!(verify_signature_any(&kp, message, &sig[1..]).unwrap(), None);
+    }
+}
+
+#[cfg(all(test, feature = "alloc", feature = "ed25519"))]
+mod signer_tests {
+    use super::*;
+    use crate::{
+        alg::{ed25519::Ed25519KeyPair, KeyAlg},
+        jws::{sign_compact, verify_compact, JwsHeader},
+        repr::KeySecretBytes,
+    };
+
+    /// A signer which does not expose its secret key, standing in for a
+    /// key held by a hardware security module
+    #[derive(Debug)]
+    struct ExternalSigner(Ed25519KeyPair);
+
+    impl HasKeyAlg for ExternalSigner {
+        fn algorithm(&self) -> KeyAlg {
+            KeyAlg::Ed25519
+        }
+    }
+
+    impl KeySign for ExternalSigner {
+        fn write_signature(
+            &self,
+            message: &[u8],
+            sig_type: Option<SignatureType>,
+            out: &mut dyn WriteBuffer,
+        ) -> Result<(), Error> {
+            KeySign::write_signature(&self.0, message, sig_type, out)
+        }
+    }
+
+    impl KeySigVerify for ExternalSigner {
+        fn verify_signature(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            sig_type: Option<SignatureType>,
+        ) -> Result<bool, Error> {
+            KeySigVerify::verify_signature(&self.0, message, signature, sig_type)
+        }
+    }
+
+    #[test]
+    fn external_signer_jws() {
+        let key = Ed25519KeyPair::from_secret_bytes(&hex!(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+        ))
+        .unwrap();
+        let signer = ExternalSigner(key.clone());
+        let signers: [&dyn KeySigner; 2] = [&signer, &key];
+        let header = JwsHeader::default();
+        let tokens = signers.map(|s| sign_compact(s, &header, b"payload").unwrap());
+        // Ed25519 signatures are deterministic
+        assert_eq!(tokens[0], tokens[1]);
+        let verifier: &dyn KeyVerifier = &signer;
+        assert!(verify_compact(verifier, &tokens[0]).unwrap());
+        assert!(verify_compact(&key, &tokens[0]).unwrap());
+    }
+}
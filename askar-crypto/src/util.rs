@@ -0,0 +1,53 @@
+//! Constant-time comparison and selection helpers
+//!
+//! These helpers wrap the operations of the `subtle` crate used throughout
+//! the key implementations, so that secret-dependent comparisons are
+//! performed consistently.
+
+pub use subtle::Choice;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+/// Compare two byte slices in constant time.
+///
+/// The running time depends only on the lengths of the inputs, which are
+/// not considered secret. Slices of different lengths are never equal.
+/// The result is returned as a [`Choice`] so that it may be combined with
+/// other checks before branching; it converts into a `bool` with `into()`.
+#[inline]
+pub fn ct_eq_slices(a: &[u8], b: &[u8]) -> Choice {
+    a.ct_eq(b)
+}
+
+/// Select between two values in constant time, returning `a` when `choice`
+/// is unset and `b` when it is set.
+#[inline]
+pub fn ct_select<T: ConditionallySelectable>(a: &T, b: &T, choice: Choice) -> T {
+    T::conditional_select(a, b, choice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eq_slices() {
+        assert!(bool::from(ct_eq_slices(b"", b"")));
+        assert!(bool::from(ct_eq_slices(b"abc", b"abc")));
+        assert!(!bool::from(ct_eq_slices(b"abc", b"abd")));
+        assert!(!bool::from(ct_eq_slices(b"abc", b"ab")));
+        assert!(!bool::from(ct_eq_slices(b"", b"a")));
+        assert!(bool::from(
+            ct_eq_slices(b"a", b"a") & ct_eq_slices(b"b", b"b")
+        ));
+        assert!(!bool::from(
+            ct_eq_slices(b"a", b"a") & ct_eq_slices(b"b", b"c")
+        ));
+    }
+
+    #[test]
+    fn select() {
+        assert_eq!(ct_select(&1u8, &2u8, Choice::from(0)), 1);
+        assert_eq!(ct_select(&1u8, &2u8, Choice::from(1)), 2);
+        assert_eq!(ct_select(&1u64, &2u64, ct_eq_slices(b"a", b"b")), 1);
+    }
+}
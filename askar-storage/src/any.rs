@@ -2,14 +2,15 @@
 
 use std::{fmt::Debug, sync::Arc};
 
-use super::{Backend, BackendSession, ManageBackend};
+use super::{Backend, BackendKind, BackendSession, ManageBackend, StoreValidation};
 use crate::{
-    backend::OrderBy,
-    entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter},
+    backend::{OrderBy, ProfileStats},
+    crypto::buffer::SecretBytes,
+    entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter, TagMode},
     error::Error,
     future::BoxFuture,
     options::IntoOptions,
-    protect::{PassKey, StoreKeyMethod},
+    protect::{PassKey, StoreKeyMethod, TagHasher},
 };
 
 #[cfg(feature = "postgres")]
@@ -39,6 +40,28 @@ impl<B: Backend> Backend for WrapBackend<B> {
         self.0.create_profile(name)
     }
 
+    #[inline]
+    fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> BoxFuture<'_, Result<String, Error>> {
+        self.0.create_profile_with_plaintext_names(name, categories)
+    }
+
+    #[inline]
+    fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> BoxFuture<'_, Result<Vec<String>, Error>> {
+        self.0.plaintext_name_categories(profile)
+    }
+
+    #[inline]
+    fn kind(&self) -> BackendKind {
+        self.0.kind()
+    }
+
     #[inline]
     fn get_active_profile(&self) -> String {
         self.0.get_active_profile()
@@ -59,6 +82,20 @@ impl<B: Backend> Backend for WrapBackend<B> {
         self.0.list_profiles()
     }
 
+    #[inline]
+    fn profile_stats(&self, profile: Option<String>) -> BoxFuture<'_, Result<ProfileStats, Error>> {
+        self.0.profile_stats(profile)
+    }
+
+    #[inline]
+    fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: String,
+    ) -> BoxFuture<'_, Result<(), Error>> {
+        self.0.create_tag_index(profile, tag_name)
+    }
+
     #[inline]
     fn remove_profile(&self, name: String) -> BoxFuture<'_, Result<bool, Error>> {
         self.0.remove_profile(name)
@@ -79,22 +116,44 @@ impl<B: Backend> Backend for WrapBackend<B> {
         profile: Option<String>,
         kind: Option<EntryKind>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
     ) -> BoxFuture<'_, Result<Scan<'static, Entry>, Error>> {
         self.0.scan(
-            profile, kind, category, tag_filter, offset, limit, order_by, descending,
+            profile,
+            kind,
+            category,
+            name_prefix,
+            tag_filter,
+            tag_mode,
+            offset,
+            limit,
+            order_by,
+            descending,
         )
     }
 
     #[inline]
     fn session(&self, profile: Option<String>, transaction: bool) -> Result<Self::Session, Error> {
-        Ok(AnyBackendSession(Box::new(
-            self.0.session(profile, transaction)?,
-        )))
+        Ok(AnyBackendSession {
+            inner: Box::new(self.0.session(profile, transaction)?),
+            transaction,
+        })
+    }
+
+    #[inline]
+    fn schema_version(&self) -> BoxFuture<'_, Result<u32, Error>> {
+        self.0.schema_version()
+    }
+
+    #[inline]
+    fn migrate(&self) -> BoxFuture<'_, Result<(), Error>> {
+        self.0.migrate()
     }
 
     #[inline]
@@ -118,7 +177,37 @@ impl Backend for AnyBackend {
 
     #[inline]
     fn create_profile(&self, name: Option<String>) -> BoxFuture<'_, Result<String, Error>> {
-        self.0.create_profile(name)
+        traced!(self.0.create_profile(name), |_| None, "create_profile")
+    }
+
+    #[inline]
+    fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> BoxFuture<'_, Result<String, Error>> {
+        traced!(
+            self.0.create_profile_with_plaintext_names(name, categories),
+            |_| None,
+            "create_profile_with_plaintext_names"
+        )
+    }
+
+    #[inline]
+    fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> BoxFuture<'_, Result<Vec<String>, Error>> {
+        traced!(
+            self.0.plaintext_name_categories(profile),
+            |_| None,
+            "plaintext_name_categories"
+        )
+    }
+
+    #[inline]
+    fn kind(&self) -> BackendKind {
+        self.0.kind()
     }
 
     #[inline]
@@ -128,22 +217,52 @@ impl Backend for AnyBackend {
 
     #[inline]
     fn get_default_profile(&self) -> BoxFuture<'_, Result<String, Error>> {
-        self.0.get_default_profile()
+        traced!(
+            self.0.get_default_profile(),
+            |_| None,
+            "get_default_profile"
+        )
     }
 
     #[inline]
     fn set_default_profile(&self, profile: String) -> BoxFuture<'_, Result<(), Error>> {
-        self.0.set_default_profile(profile)
+        traced!(
+            self.0.set_default_profile(profile),
+            |_| None,
+            "set_default_profile"
+        )
     }
 
     #[inline]
     fn list_profiles(&self) -> BoxFuture<'_, Result<Vec<String>, Error>> {
-        self.0.list_profiles()
+        traced!(
+            self.0.list_profiles(),
+            |profiles| Some(profiles.len()),
+            "list_profiles"
+        )
+    }
+
+    #[inline]
+    fn profile_stats(&self, profile: Option<String>) -> BoxFuture<'_, Result<ProfileStats, Error>> {
+        traced!(self.0.profile_stats(profile), |_| None, "profile_stats")
+    }
+
+    #[inline]
+    fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: String,
+    ) -> BoxFuture<'_, Result<(), Error>> {
+        traced!(
+            self.0.create_tag_index(profile, tag_name),
+            |_| None,
+            "create_tag_index"
+        )
     }
 
     #[inline]
     fn remove_profile(&self, name: String) -> BoxFuture<'_, Result<bool, Error>> {
-        self.0.remove_profile(name)
+        traced!(self.0.remove_profile(name), |_| None, "remove_profile")
     }
 
     #[inline]
@@ -152,7 +271,11 @@ impl Backend for AnyBackend {
         from_name: String,
         to_name: String,
     ) -> BoxFuture<'_, Result<bool, Error>> {
-        self.0.rename_profile(from_name, to_name)
+        traced!(
+            self.0.rename_profile(from_name, to_name),
+            |_| None,
+            "rename_profile"
+        )
     }
 
     #[inline]
@@ -161,22 +284,50 @@ impl Backend for AnyBackend {
         profile: Option<String>,
         kind: Option<EntryKind>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
     ) -> BoxFuture<'_, Result<Scan<'static, Entry>, Error>> {
-        self.0.scan(
-            profile, kind, category, tag_filter, offset, limit, order_by, descending,
+        traced!(
+            self.0.scan(
+                profile,
+                kind,
+                category,
+                name_prefix,
+                tag_filter,
+                tag_mode,
+                offset,
+                limit,
+                order_by,
+                descending,
+            ),
+            |_| None,
+            "scan",
+            ?kind,
+            category
         )
     }
 
     #[inline]
     fn session(&self, profile: Option<String>, transaction: bool) -> Result<Self::Session, Error> {
-        Ok(AnyBackendSession(Box::new(
-            self.0.session(profile, transaction)?,
-        )))
+        // the transaction itself is started by the first operation on the session
+        #[cfg(feature = "tracing")]
+        let _span = transaction.then(|| tracing::debug_span!("begin").entered());
+        self.0.session(profile, transaction)
+    }
+
+    #[inline]
+    fn schema_version(&self) -> BoxFuture<'_, Result<u32, Error>> {
+        traced!(self.0.schema_version(), |_| None, "schema_version")
+    }
+
+    #[inline]
+    fn migrate(&self) -> BoxFuture<'_, Result<(), Error>> {
+        traced!(self.0.migrate(), |_| None, "migrate")
     }
 
     #[inline]
@@ -185,23 +336,27 @@ impl Backend for AnyBackend {
         method: StoreKeyMethod,
         key: PassKey<'_>,
     ) -> BoxFuture<'_, Result<(), Error>> {
-        match Arc::get_mut(&mut self.0) {
+        let fut: BoxFuture<'_, Result<(), Error>> = match Arc::get_mut(&mut self.0) {
             Some(inner) => inner.rekey(method, key),
             None => Box::pin(std::future::ready(Err(err_msg!(
                 "Cannot re-key a store with multiple references"
             )))),
-        }
+        };
+        traced!(fut, |_| None, "rekey")
     }
 
     #[inline]
     fn close(&self) -> BoxFuture<'_, Result<(), Error>> {
-        self.0.close()
+        traced!(self.0.close(), |_| None, "close_store")
     }
 }
 
 /// A dynamic store session instance
 #[derive(Debug)]
-pub struct AnyBackendSession(Box<dyn BackendSession>);
+pub struct AnyBackendSession {
+    inner: Box<dyn BackendSession>,
+    transaction: bool,
+}
 
 impl BackendSession for AnyBackendSession {
     /// Count the number of matching records in the store
@@ -211,7 +366,29 @@ impl BackendSession for AnyBackendSession {
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
     ) -> BoxFuture<'q, Result<i64, Error>> {
-        self.0.count(kind, category, tag_filter)
+        traced!(
+            self.inner.count(kind, category, tag_filter),
+            |count| Some(*count as usize),
+            "count",
+            ?kind,
+            category
+        )
+    }
+
+    /// Estimate the number of matching records in the store
+    fn count_estimate<'q>(
+        &'q mut self,
+        kind: Option<EntryKind>,
+        category: Option<&'q str>,
+        tag_filter: Option<TagFilter>,
+    ) -> BoxFuture<'q, Result<i64, Error>> {
+        traced!(
+            self.inner.count_estimate(kind, category, tag_filter),
+            |count| Some(*count as usize),
+            "count_estimate",
+            ?kind,
+            category
+        )
     }
 
     /// Fetch a single record from the store by category and name
@@ -220,9 +397,16 @@ impl BackendSession for AnyBackendSession {
         kind: EntryKind,
         category: &'q str,
         name: &'q str,
+        tag_mode: TagMode,
         for_update: bool,
     ) -> BoxFuture<'q, Result<Option<Entry>, Error>> {
-        self.0.fetch(kind, category, name, for_update)
+        traced!(
+            self.inner.fetch(kind, category, name, tag_mode, for_update),
+            |row| Some(row.is_some() as usize),
+            "fetch",
+            ?kind,
+            category
+        )
     }
 
     /// Fetch all matching records from the store
@@ -231,13 +415,20 @@ impl BackendSession for AnyBackendSession {
         kind: Option<EntryKind>,
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
         for_update: bool,
     ) -> BoxFuture<'q, Result<Vec<Entry>, Error>> {
-        self.0.fetch_all(
-            kind, category, tag_filter, limit, order_by, descending, for_update,
+        traced!(
+            self.inner.fetch_all(
+                kind, category, tag_filter, tag_mode, limit, order_by, descending, for_update,
+            ),
+            |rows| Some(rows.len()),
+            "fetch_all",
+            ?kind,
+            category
         )
     }
 
@@ -248,7 +439,13 @@ impl BackendSession for AnyBackendSession {
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
     ) -> BoxFuture<'q, Result<i64, Error>> {
-        self.0.remove_all(kind, category, tag_filter)
+        traced!(
+            self.inner.remove_all(kind, category, tag_filter),
+            |count| Some(*count as usize),
+            "remove_all",
+            ?kind,
+            category
+        )
     }
 
     /// Insert or replace a record in the store
@@ -263,18 +460,68 @@ impl BackendSession for AnyBackendSession {
         tags: Option<&'q [EntryTag]>,
         expiry_ms: Option<i64>,
     ) -> BoxFuture<'q, Result<(), Error>> {
-        self.0
-            .update(kind, operation, category, name, value, tags, expiry_ms)
+        traced!(
+            self.inner
+                .update(kind, operation, category, name, value, tags, expiry_ms),
+            |_| Some(1),
+            "update",
+            ?kind,
+            ?operation,
+            category
+        )
+    }
+
+    /// Insert or replace a record in the store
+    fn upsert<'q>(
+        &'q mut self,
+        kind: EntryKind,
+        category: &'q str,
+        name: &'q str,
+        value: &'q [u8],
+        tags: Option<&'q [EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> BoxFuture<'q, Result<bool, Error>> {
+        traced!(
+            self.inner
+                .upsert(kind, category, name, value, tags, expiry_ms),
+            |_| Some(1),
+            "upsert",
+            ?kind,
+            category
+        )
+    }
+
+    /// Encrypt an arbitrary value using the profile key of the session
+    fn seal<'q>(&'q mut self, value: &'q [u8]) -> BoxFuture<'q, Result<Vec<u8>, Error>> {
+        traced!(self.inner.seal(value), |_| None, "seal")
+    }
+
+    /// Decrypt a value produced by `seal`
+    fn unseal<'q>(&'q mut self, sealed: &'q [u8]) -> BoxFuture<'q, Result<SecretBytes, Error>> {
+        traced!(self.inner.unseal(sealed), |_| None, "unseal")
+    }
+
+    /// Export the hasher for encrypted tag tokens of the session profile
+    fn export_tag_hasher(&mut self) -> BoxFuture<'_, Result<TagHasher, Error>> {
+        traced!(
+            self.inner.export_tag_hasher(),
+            |_| None,
+            "export_tag_hasher"
+        )
     }
 
     /// Test the connection to the store
     fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
-        self.0.ping()
+        traced!(self.inner.ping(), |_| None, "ping")
     }
 
     /// Close the current store session
     fn close(&mut self, commit: bool) -> BoxFuture<'_, Result<(), Error>> {
-        self.0.close(commit)
+        match (self.transaction, commit) {
+            (true, true) => traced!(self.inner.close(true), |_| None, "commit"),
+            (true, false) => traced!(self.inner.close(false), |_| None, "rollback"),
+            (false, _) => traced!(self.inner.close(commit), |_| None, "close", commit),
+        }
     }
 }
 
@@ -350,6 +597,45 @@ impl<'a> ManageBackend<'a> for &'a str {
         })
     }
 
+    fn open_or_provision_backend(
+        self,
+        method: StoreKeyMethod,
+        pass_key: PassKey<'a>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> BoxFuture<'a, Result<(Self::Backend, bool), Error>> {
+        Box::pin(async move {
+            let opts = self.into_options()?;
+            debug!("Open or provision store with options: {:?}", &opts);
+
+            match opts.scheme.as_ref() {
+                #[cfg(feature = "postgres")]
+                "postgres" => {
+                    let opts = postgres::PostgresStoreOptions::new(opts)?;
+                    let (mgr, created) = opts
+                        .open_or_provision(method, pass_key, profile, recreate)
+                        .await?;
+                    Ok((into_any_backend(mgr), created))
+                }
+
+                #[cfg(feature = "sqlite")]
+                "sqlite" => {
+                    let opts = sqlite::SqliteStoreOptions::new(opts)?;
+                    let (mgr, created) = opts
+                        .open_or_provision(method, pass_key, profile, recreate)
+                        .await?;
+                    Ok((into_any_backend(mgr), created))
+                }
+
+                _ => Err(err_msg!(
+                    Unsupported,
+                    "Unsupported backend: {}",
+                    &opts.scheme
+                )),
+            }
+        })
+    }
+
     fn remove_backend(self) -> BoxFuture<'a, Result<bool, Error>> {
         Box::pin(async move {
             let opts = self.into_options()?;
@@ -376,4 +662,31 @@ impl<'a> ManageBackend<'a> for &'a str {
             }
         })
     }
+
+    fn validate_backend(self) -> BoxFuture<'a, Result<StoreValidation, Error>> {
+        Box::pin(async move {
+            let opts = self.into_options()?;
+            debug!("Validate store with options: {:?}", &opts);
+
+            match opts.scheme.as_ref() {
+                #[cfg(feature = "postgres")]
+                "postgres" => {
+                    let opts = postgres::PostgresStoreOptions::new(opts)?;
+                    Ok(opts.validate_provision().await?)
+                }
+
+                #[cfg(feature = "sqlite")]
+                "sqlite" => {
+                    let opts = sqlite::SqliteStoreOptions::new(opts)?;
+                    Ok(opts.validate_provision().await?)
+                }
+
+                _ => Err(err_msg!(
+                    Unsupported,
+                    "Unsupported backend: {}",
+                    &opts.scheme
+                )),
+            }
+        })
+    }
 }
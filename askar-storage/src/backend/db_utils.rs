@@ -8,7 +8,7 @@ use sqlx::{
 };
 
 use crate::{
-    entry::{EncEntryTag, Entry, EntryKind, EntryTag, TagFilter},
+    entry::{EncEntryTag, Entry, EntryKind, EntryTag, TagFilter, TagMode},
     error::Error,
     future::BoxFuture,
     protect::{EntryEncryptor, KeyCache, PassKey, ProfileId, ProfileKey, StoreKey, StoreKeyMethod},
@@ -18,11 +18,14 @@ use crate::{
     },
 };
 
-use super::OrderBy;
+use super::{OrderBy, SCHEMA_VERSION};
 
 /// cbindgen:ignore
 pub const PAGE_SIZE: usize = 32;
 
+// the condition of the tags subquery in fetch and scan queries
+const TAG_SELECT_CONDITION: &str = "WHERE it.item_id = i.id";
+
 pub type Expiry = chrono::DateTime<chrono::Utc>;
 
 pub(crate) type Connection<DB> = <DB as Database>::Connection;
@@ -41,6 +44,7 @@ pub struct DbSession<DB: ExtDatabase> {
     profile_key: DbSessionKey,
     state: DbSessionState<DB>,
     txn_depth: usize,
+    max_value_size: usize,
 }
 
 impl<DB: ExtDatabase> DbSession<DB> {
@@ -49,6 +53,7 @@ impl<DB: ExtDatabase> DbSession<DB> {
         cache: Arc<KeyCache>,
         profile: String,
         transaction: bool,
+        max_value_size: usize,
     ) -> Self
     where
         DB: Database,
@@ -57,6 +62,20 @@ impl<DB: ExtDatabase> DbSession<DB> {
             profile_key: DbSessionKey::Pending { cache, profile },
             state: DbSessionState::Pending { pool, transaction },
             txn_depth: 0,
+            max_value_size,
+        }
+    }
+
+    /// Ensure that an entry value does not exceed the configured size limit
+    pub(crate) fn check_value_size(&self, len: usize) -> Result<(), Error> {
+        if len > self.max_value_size {
+            Err(err_msg!(
+                Input,
+                "Entry value of {len} bytes exceeds the maximum size of {} bytes",
+                self.max_value_size
+            ))
+        } else {
+            Ok(())
         }
     }
 
@@ -556,7 +575,7 @@ pub fn decrypt_scan_entry(
         Some(c) => c.to_owned(),
         None => key.decrypt_entry_category(enc_entry.category)?,
     };
-    let name = key.decrypt_entry_name(enc_entry.name)?;
+    let name = key.decrypt_entry_name(category.as_bytes(), enc_entry.name)?;
     let value = key.decrypt_entry_value(category.as_bytes(), name.as_bytes(), enc_entry.value)?;
     let tags = key.decrypt_entry_tags(
         decode_tags(enc_entry.tags).map_err(|_| err_msg!(Unexpected, "Error decoding tags"))?,
@@ -564,6 +583,43 @@ pub fn decrypt_scan_entry(
     Ok(Entry::new(enc_entry.kind, category, name, value, tags))
 }
 
+/// An inclusive lower and exclusive upper bound on stored entry names
+pub type NameRange = (Vec<u8>, Vec<u8>);
+
+/// Determine the range of stored entry names matching a name prefix filter
+///
+/// Only names which are stored as plaintext preserve their ordering, so the
+/// names in the category must not be encrypted.
+pub fn name_prefix_range(
+    category: Option<&str>,
+    name_prefix: Option<String>,
+    key: &ProfileKey,
+) -> Result<Option<NameRange>, Error> {
+    let Some(prefix) = name_prefix else {
+        return Ok(None);
+    };
+    let Some(category) = category else {
+        return Err(err_msg!(
+            Input,
+            "A category is required when filtering by name prefix"
+        ));
+    };
+    if !key.has_plaintext_names(category.as_bytes()) {
+        return Err(err_msg!(
+            Unsupported,
+            "Entry names in this category are encrypted and cannot be filtered by prefix"
+        ));
+    }
+    let start = prefix.into_bytes();
+    let mut end = start.clone();
+    // the final byte of a UTF-8 string is never 0xFF, so this cannot overflow
+    match end.last_mut() {
+        Some(last) => *last += 1,
+        None => return Ok(None),
+    }
+    Ok(Some((start, end)))
+}
+
 pub fn expiry_timestamp(expire_ms: i64) -> Result<Expiry, Error> {
     chrono::Utc::now()
         .checked_add_signed(chrono::Duration::milliseconds(expire_ms))
@@ -593,6 +649,39 @@ pub fn encode_tag_filter<Q: QueryPrepare>(
     }
 }
 
+pub fn encode_tag_names(
+    tag_mode: &TagMode,
+    key: &ProfileKey,
+) -> Result<Option<Vec<Vec<u8>>>, Error> {
+    match tag_mode {
+        TagMode::All => Ok(None),
+        TagMode::None => Ok(Some(Vec::new())),
+        TagMode::Names(names) => names
+            .iter()
+            .map(|name| {
+                let name = name.strip_prefix('~').unwrap_or(name);
+                key.encrypt_tag_name(ProfileKey::prepare_input(name.as_bytes()))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map(Some),
+    }
+}
+
+/// Encrypt a plaintext tag name for use in a tag index, returning the index name
+pub fn encode_tag_index(tag_name: &str, key: &ProfileKey) -> Result<(String, Vec<u8>), Error> {
+    let tag_name = tag_name.strip_prefix('~').unwrap_or(tag_name);
+    if tag_name.is_empty() {
+        return Err(err_msg!(Input, "Tag name must not be empty"));
+    }
+    let enc_name = key.encrypt_tag_name(ProfileKey::prepare_input(tag_name.as_bytes()))?;
+    // the leading nonce is derived from the tag name and profile key
+    let index_name = format!(
+        "ix_items_tags_plain_{}",
+        hex::encode(&enc_name[..enc_name.len().min(12)])
+    );
+    Ok((index_name, enc_name))
+}
+
 // allocate a String while ensuring there is sufficient capacity to reuse during encryption
 fn _prepare_string(value: &str) -> String {
     let buf = ProfileKey::prepare_input(value.as_bytes()).into_vec();
@@ -616,6 +705,55 @@ pub fn prepare_tags(tags: &[EntryTag]) -> Result<Vec<EntryTag>, Error> {
     Ok(result)
 }
 
+// restrict the tags subquery of a fetch or scan query to a set of encrypted tag names
+pub fn extend_tag_select<'q, Q: QueryPrepare>(
+    query: &str,
+    args: &mut QueryParams<'q, Q::DB>,
+    tag_names: Option<Vec<Vec<u8>>>,
+) -> String
+where
+    Vec<u8>: for<'e> Encode<'e, Q::DB> + Type<Q::DB>,
+{
+    let Some(tag_names) = tag_names else {
+        return query.to_string();
+    };
+    debug_assert!(query.contains(TAG_SELECT_CONDITION));
+    let condition = if tag_names.is_empty() {
+        // a constant false condition lets the database skip the tags table entirely
+        format!("{TAG_SELECT_CONDITION} AND 0 = 1")
+    } else {
+        let start_index = (args.len() + 1) as i64;
+        let placeholders = vec!["$$"; tag_names.len()].join(", ");
+        args.extend(tag_names);
+        replace_arg_placeholders::<Q>(
+            &format!("{TAG_SELECT_CONDITION} AND it.name IN ({placeholders})"),
+            start_index,
+        )
+    };
+    query.replacen(TAG_SELECT_CONDITION, &condition, 1)
+}
+
+/// Restrict a query to the entries whose names fall within a range
+pub fn extend_name_range<'q, Q: QueryPrepare>(
+    query: &str,
+    args: &mut QueryParams<'q, Q::DB>,
+    name_range: Option<NameRange>,
+) -> String
+where
+    Vec<u8>: for<'e> Encode<'e, Q::DB> + Type<Q::DB>,
+{
+    let mut query = query.to_string();
+    if let Some((start, end)) = name_range {
+        query.push_str(&replace_arg_placeholders::<Q>(
+            " AND i.name >= $$ AND i.name < $$",
+            (args.len() + 1) as i64,
+        ));
+        args.push(start);
+        args.push(end);
+    }
+    query
+}
+
 pub fn extend_query<'q, Q: QueryPrepare>(
     query: &str,
     args: &mut QueryParams<'q, Q::DB>,
@@ -677,6 +815,22 @@ pub fn encode_profile_key(
     store_key.wrap_data(profile_key.to_bytes()?)
 }
 
+/// Parse a stored schema version, rejecting versions newer than supported
+pub fn parse_schema_version(version: &str) -> Result<u32, Error> {
+    let version: u32 = version
+        .parse()
+        .map_err(err_map!(Unsupported, "Invalid store version"))?;
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(err_msg!(
+            Unsupported,
+            "Store schema version {} is not supported by this library (expected {} or earlier)",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(version)
+}
+
 #[inline]
 pub fn random_profile_name() -> String {
     uuid::Uuid::new_v4().to_string()
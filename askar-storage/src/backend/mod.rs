@@ -1,12 +1,13 @@
 //! Storage backends supported by askar
 
-use std::fmt::Debug;
+use std::{collections::BTreeMap, fmt::Debug};
 
 use crate::{
-    entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter},
+    crypto::buffer::SecretBytes,
+    entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter, TagMode},
     error::{Error, ErrorKind},
     future::BoxFuture,
-    protect::{PassKey, StoreKeyMethod},
+    protect::{PassKey, StoreKeyMethod, TagHasher},
 };
 
 #[cfg(any(feature = "postgres", feature = "sqlite"))]
@@ -22,6 +23,25 @@ pub mod postgres;
 /// Sqlite database support
 pub mod sqlite;
 
+/// The database schema version created and supported by the store backends
+///
+/// Schema versions:
+/// - `1`: the initial schema, consisting of the `config`, `profiles`, `items`
+///   and `items_tags` tables
+///
+/// Stores using an older schema version may be opened, but must be upgraded
+/// using [`Backend::migrate`] before use. Opening a store with a newer schema
+/// version than supported results in an `Unsupported` error, as its contents
+/// may not be understood by this library.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The default maximum size in bytes of an entry value
+///
+/// Inserting or replacing an entry with a larger value fails with an `Input`
+/// error before the value is encrypted. The limit may be raised using the
+/// `max_value_size` store option where larger values are legitimately needed.
+pub const DEFAULT_MAX_VALUE_SIZE: usize = 64 * 1024 * 1024;
+
 /// Enum to support custom ordering in record queries
 #[derive(Debug, Default)]
 pub enum OrderBy {
@@ -30,27 +50,128 @@ pub enum OrderBy {
     Id,
 }
 
+/// Entry counts for a store profile
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileStats {
+    /// The number of unexpired item entries in each category
+    pub items: BTreeMap<String, i64>,
+    /// The number of unexpired key entries in each category
+    pub keys: BTreeMap<String, i64>,
+}
+
+impl ProfileStats {
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    pub(crate) fn add(&mut self, kind: EntryKind, category: String, count: i64) {
+        let counts = match kind {
+            EntryKind::Item => &mut self.items,
+            EntryKind::Kms => &mut self.keys,
+        };
+        *counts.entry(category).or_default() += count;
+    }
+}
+
+/// The database engine underlying a backend implementation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BackendKind {
+    /// A PostgreSQL database
+    Postgres,
+    /// A SQLite database
+    Sqlite,
+}
+
+impl BackendKind {
+    /// Convert the backend kind to a string reference
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// The outcome of validating a store location without provisioning it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StoreValidation {
+    /// The database engine which would be used for the store
+    pub kind: BackendKind,
+    /// Whether provisioning would create the database itself, such as a new
+    /// SQLite file or a missing Postgres database
+    pub create_database: bool,
+    /// Whether an existing store was found. Provisioning opens this store
+    /// unless `recreate` is set, in which case it is replaced.
+    pub store_exists: bool,
+}
+
 /// Represents a generic backend implementation
 pub trait Backend: Debug + Send + Sync {
     /// The type of session managed by this backend
     type Session: BackendSession + 'static;
 
+    /// Get the database engine used by this backend
+    fn kind(&self) -> BackendKind;
+
     /// Create a new profile
     fn create_profile(&self, name: Option<String>) -> BoxFuture<'_, Result<String, Error>>;
 
+    /// Create a new profile which stores the names of entries in the given
+    /// categories as plaintext
+    ///
+    /// Entry values and tags remain encrypted, but the names of entries in
+    /// these categories are visible to anyone with access to the database.
+    /// In exchange, the names preserve their ordering and may be matched by a
+    /// name prefix filter when scanning. The categories are fixed when the
+    /// profile is created, and names in all other categories are encrypted.
+    fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> BoxFuture<'_, Result<String, Error>>;
+
+    /// Get the categories whose entry names are stored as plaintext for a
+    /// profile, defaulting to the active profile
+    fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> BoxFuture<'_, Result<Vec<String>, Error>>;
+
     /// Get the name of the active profile
     fn get_active_profile(&self) -> String;
 
     /// Get the name of the default profile
     fn get_default_profile(&self) -> BoxFuture<'_, Result<String, Error>>;
 
-    /// Set the the default profile
+    /// Set the default profile, which is stored with the database and used as
+    /// the active profile when the store is next opened without a profile name
+    ///
+    /// A `NotFound` error is returned if the profile does not exist.
     fn set_default_profile(&self, profile: String) -> BoxFuture<'_, Result<(), Error>>;
 
     /// Get the details of all store profiles
     fn list_profiles(&self) -> BoxFuture<'_, Result<Vec<String>, Error>>;
 
-    /// Remove an existing profile
+    /// Get the number of entries in each category for a profile, defaulting
+    /// to the active profile
+    fn profile_stats(&self, profile: Option<String>) -> BoxFuture<'_, Result<ProfileStats, Error>>;
+
+    /// Create an index on the values of a plaintext tag for a profile, defaulting
+    /// to the active profile
+    ///
+    /// The index persists in the database and is used by the query planner for
+    /// WQL filters on the tag. Creating an existing index has no effect.
+    /// Encrypted tag values are only indexed by their deterministic nonce, as
+    /// their ciphertexts do not preserve ordering, so encrypted tags support
+    /// efficient equality lookups only.
+    fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: String,
+    ) -> BoxFuture<'_, Result<(), Error>>;
+
+    /// Remove an existing profile along with all of its entries
+    ///
+    /// Returns `false` if the profile does not exist. The active profile and the
+    /// default profile cannot be removed.
     fn remove_profile(&self, name: String) -> BoxFuture<'_, Result<bool, Error>>;
 
     /// Change the name of an existing profile
@@ -60,14 +181,30 @@ pub trait Backend: Debug + Send + Sync {
         to_name: String,
     ) -> BoxFuture<'_, Result<bool, Error>>;
 
+    /// Get the database schema version of the store
+    fn schema_version(&self) -> BoxFuture<'_, Result<u32, Error>>;
+
+    /// Upgrade the database schema to the current [`SCHEMA_VERSION`]
+    ///
+    /// Each migration step is applied within a single transaction. No changes
+    /// are made if the schema is already current.
+    fn migrate(&self) -> BoxFuture<'_, Result<(), Error>>;
+
     /// Create a [`Scan`] against the store
+    ///
+    /// A `name_prefix` restricts the results to entries whose names begin with
+    /// the prefix, and requires a `category`. Encrypted names do not preserve
+    /// their ordering, so an `Unsupported` error is returned unless the names
+    /// in the category are stored as plaintext.
     #[allow(clippy::too_many_arguments)]
     fn scan(
         &self,
         profile: Option<String>,
         kind: Option<EntryKind>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
@@ -110,13 +247,32 @@ pub trait ManageBackend<'a> {
         recreate: bool,
     ) -> BoxFuture<'a, Result<Self::Backend, Error>>;
 
+    /// Open an existing store, or provision a new store if none exists
+    ///
+    /// The returned flag is `true` if a new store was provisioned. An existing
+    /// store is only replaced when `recreate` is set.
+    fn open_or_provision_backend(
+        self,
+        method: StoreKeyMethod,
+        pass_key: PassKey<'a>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> BoxFuture<'a, Result<(Self::Backend, bool), Error>>;
+
     /// Remove an existing store
     fn remove_backend(self) -> BoxFuture<'a, Result<bool, Error>>;
+
+    /// Check that a store could be provisioned, without creating the database
+    /// or the store schema
+    fn validate_backend(self) -> BoxFuture<'a, Result<StoreValidation, Error>>;
 }
 
 /// Query from a generic backend implementation
 pub trait BackendSession: Debug + Send {
     /// Count the number of matching records in the store
+    ///
+    /// Entry tags are only consulted when a tag filter is provided, and are
+    /// never decrypted.
     fn count<'q>(
         &'q mut self,
         kind: Option<EntryKind>,
@@ -124,12 +280,27 @@ pub trait BackendSession: Debug + Send {
         tag_filter: Option<TagFilter>,
     ) -> BoxFuture<'q, Result<i64, Error>>;
 
+    /// Estimate the number of matching records in the store
+    ///
+    /// Backends which expose query planner statistics return the planner's row
+    /// estimate, which may be substantially off from the actual count. Other
+    /// backends perform an exact count.
+    fn count_estimate<'q>(
+        &'q mut self,
+        kind: Option<EntryKind>,
+        category: Option<&'q str>,
+        tag_filter: Option<TagFilter>,
+    ) -> BoxFuture<'q, Result<i64, Error>> {
+        self.count(kind, category, tag_filter)
+    }
+
     /// Fetch a single record from the store by category and name
     fn fetch<'q>(
         &'q mut self,
         kind: EntryKind,
         category: &'q str,
         name: &'q str,
+        tag_mode: TagMode,
         for_update: bool,
     ) -> BoxFuture<'q, Result<Option<Entry>, Error>>;
 
@@ -140,6 +311,7 @@ pub trait BackendSession: Debug + Send {
         kind: Option<EntryKind>,
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
@@ -191,6 +363,33 @@ pub trait BackendSession: Debug + Send {
         expiry_ms: Option<i64>,
     ) -> BoxFuture<'q, Result<(), Error>>;
 
+    /// Insert a record, or replace the value and tags of an existing record
+    ///
+    /// Returns `true` if a new record was inserted, or `false` if an existing
+    /// record was replaced.
+    fn upsert<'q>(
+        &'q mut self,
+        kind: EntryKind,
+        category: &'q str,
+        name: &'q str,
+        value: &'q [u8],
+        tags: Option<&'q [EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> BoxFuture<'q, Result<bool, Error>>;
+
+    /// Encrypt an arbitrary value using the profile key of the session
+    ///
+    /// The sealed value may only be decrypted by a session on the same profile.
+    fn seal<'q>(&'q mut self, value: &'q [u8]) -> BoxFuture<'q, Result<Vec<u8>, Error>>;
+
+    /// Decrypt a value produced by `seal` using the profile key of the session
+    fn unseal<'q>(&'q mut self, sealed: &'q [u8]) -> BoxFuture<'q, Result<SecretBytes, Error>>;
+
+    /// Export the hasher for encrypted tag tokens of the session profile
+    ///
+    /// See [`TagHasher`] for the security implications of sharing it.
+    fn export_tag_hasher(&mut self) -> BoxFuture<'_, Result<TagHasher, Error>>;
+
     /// Test the connection to the store
     fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>>;
 
@@ -212,12 +411,20 @@ pub async fn copy_profile<A: Backend, B: Backend>(
             None,
             None,
             None,
+            TagMode::All,
+            None,
             None,
             None,
             false,
         )
         .await?;
-    if let Err(e) = to_backend.create_profile(Some(to_profile.into())).await {
+    let plaintext_names = from_backend
+        .plaintext_name_categories(Some(from_profile.into()))
+        .await?;
+    if let Err(e) = to_backend
+        .create_profile_with_plaintext_names(Some(to_profile.into()), plaintext_names)
+        .await
+    {
         if e.kind() != ErrorKind::Duplicate {
             return Err(e);
         }
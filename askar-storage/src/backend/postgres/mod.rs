@@ -17,19 +17,23 @@ use sqlx::{
 
 use super::{
     db_utils::{
-        decode_tags, decrypt_scan_batch, encode_profile_key, encode_tag_filter, expiry_timestamp,
-        extend_query, prepare_tags, random_profile_name, replace_arg_placeholders, DbSession,
-        DbSessionActive, DbSessionRef, DbSessionTxn, EncScanEntry, ExtDatabase, QueryParams,
-        QueryPrepare, PAGE_SIZE,
+        decode_tags, decrypt_scan_batch, encode_profile_key, encode_tag_filter, encode_tag_index,
+        encode_tag_names, expiry_timestamp, extend_name_range, extend_query, extend_tag_select,
+        name_prefix_range, parse_schema_version, prepare_tags, random_profile_name,
+        replace_arg_placeholders, DbSession, DbSessionActive, DbSessionRef, DbSessionTxn,
+        EncScanEntry, ExtDatabase, NameRange, QueryParams, QueryPrepare, PAGE_SIZE,
     },
-    Backend, BackendSession,
+    Backend, BackendKind, BackendSession, SCHEMA_VERSION,
 };
 use crate::{
-    backend::OrderBy,
-    entry::{EncEntryTag, Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter},
+    backend::{OrderBy, ProfileStats},
+    crypto::buffer::SecretBytes,
+    entry::{EncEntryTag, Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter, TagMode},
     error::Error,
     future::{unblock, BoxFuture},
-    protect::{EntryEncryptor, KeyCache, PassKey, ProfileId, ProfileKey, StoreKeyMethod},
+    protect::{
+        EntryEncryptor, KeyCache, PassKey, ProfileId, ProfileKey, StoreKeyMethod, TagHasher,
+    },
 };
 
 mod provision;
@@ -43,11 +47,18 @@ pub use self::test_db::TestDB;
 const CONFIG_FETCH_QUERY: &str = "SELECT value FROM config WHERE name = $1";
 const CONFIG_UPDATE_QUERY: &str = "INSERT INTO config (name, value) VALUES ($1, $2)
     ON CONFLICT(name) DO UPDATE SET value = excluded.value";
+// the statements upgrading the schema from each version to the next, starting from version 1
+const MIGRATIONS: &[&str] = &[];
 const COUNT_QUERY: &str = "SELECT COUNT(*) FROM items i
     WHERE profile_id = $1
     AND (kind = $2 OR $2 IS NULL)
     AND (category = $3 OR $3 IS NULL)
     AND (expiry IS NULL OR expiry > CURRENT_TIMESTAMP)";
+const ESTIMATE_QUERY: &str = "EXPLAIN SELECT id FROM items i
+    WHERE profile_id = $1
+    AND (kind = $2 OR $2 IS NULL)
+    AND (category = $3 OR $3 IS NULL)
+    AND (expiry IS NULL OR expiry > CURRENT_TIMESTAMP)";
 const DELETE_QUERY: &str = "DELETE FROM items
     WHERE profile_id = $1 AND kind = $2 AND category = $3 AND name = $4";
 const FETCH_QUERY: &str = "SELECT id, value,
@@ -70,6 +81,12 @@ const INSERT_QUERY: &str = "INSERT INTO items (profile_id, kind, category, name,
 const UPDATE_QUERY: &str = "UPDATE items SET value=$5, expiry=$6
     WHERE profile_id=$1 AND kind=$2 AND category=$3 AND name=$4
     RETURNING id";
+// xmax is only zero for a newly inserted row version
+const UPSERT_QUERY: &str = "INSERT INTO items (profile_id, kind, category, name, value, expiry)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (profile_id, kind, category, name)
+    DO UPDATE SET value = excluded.value, expiry = excluded.expiry
+    RETURNING id, (xmax = 0) AS inserted";
 const SCAN_QUERY: &str = "SELECT id, kind, category, name, value,
     (SELECT ARRAY_TO_STRING(ARRAY_AGG(it.plaintext || ':'
         || ENCODE(it.name, 'hex') || ':' || ENCODE(it.value, 'hex')), ',')
@@ -82,6 +99,10 @@ const DELETE_ALL_QUERY: &str = "DELETE FROM items i
     WHERE profile_id = $1
     AND (kind = $2 OR $2 IS NULL)
     AND (category = $3 OR $3 IS NULL)";
+const STATS_QUERY: &str = "SELECT kind, category, COUNT(*) FROM items
+    WHERE profile_id = $1
+    AND (expiry IS NULL OR expiry > CURRENT_TIMESTAMP)
+    GROUP BY kind, category";
 const TAG_INSERT_QUERY: &str = "INSERT INTO items_tags
     (item_id, name, value, plaintext) VALUES ($1, $2, $3, $4)";
 const TAG_DELETE_QUERY: &str = "DELETE FROM items_tags
@@ -92,6 +113,7 @@ pub struct PostgresBackend {
     conn_pool: PgPool,
     active_profile: String,
     key_cache: Arc<KeyCache>,
+    max_value_size: usize,
     host: String,
     name: String,
 }
@@ -103,11 +125,13 @@ impl PostgresBackend {
         key_cache: KeyCache,
         host: String,
         name: String,
+        max_value_size: usize,
     ) -> Self {
         Self {
             conn_pool,
             active_profile,
             key_cache: Arc::new(key_cache),
+            max_value_size,
             host,
             name,
         }
@@ -118,11 +142,19 @@ impl Backend for PostgresBackend {
     type Session = DbSession<Postgres>;
 
     fn create_profile(&self, name: Option<String>) -> BoxFuture<'_, Result<String, Error>> {
+        self.create_profile_with_plaintext_names(name, Vec::new())
+    }
+
+    fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> BoxFuture<'_, Result<String, Error>> {
         let name = name.unwrap_or_else(random_profile_name);
         Box::pin(async move {
             let store_key = self.key_cache.store_key.clone();
             let (profile_key, enc_key) = unblock(move || {
-                let profile_key = ProfileKey::new()?;
+                let profile_key = ProfileKey::new()?.with_plaintext_names(categories);
                 let enc_key = encode_profile_key(&profile_key, &store_key)?;
                 Result::<_, Error>::Ok((profile_key, enc_key))
             })
@@ -148,6 +180,10 @@ impl Backend for PostgresBackend {
         })
     }
 
+    fn kind(&self) -> BackendKind {
+        BackendKind::Postgres
+    }
+
     fn get_active_profile(&self) -> String {
         self.active_profile.clone()
     }
@@ -168,6 +204,14 @@ impl Backend for PostgresBackend {
     fn set_default_profile(&self, profile: String) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async move {
             let mut conn = self.conn_pool.acquire().await?;
+            let found: Option<i64> = sqlx::query_scalar("SELECT id FROM profiles WHERE name = $1")
+                .bind(&profile)
+                .fetch_optional(conn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching profile"))?;
+            if found.is_none() {
+                return Err(err_msg!(NotFound, "Profile not found"));
+            }
             sqlx::query(CONFIG_UPDATE_QUERY)
                 .bind("default_profile")
                 .bind(profile)
@@ -192,8 +236,79 @@ impl Backend for PostgresBackend {
         })
     }
 
+    fn profile_stats(&self, profile: Option<String>) -> BoxFuture<'_, Result<ProfileStats, Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (profile_id, key) = acquire_key(&mut session).await?;
+            let mut active = acquire_session(&mut session).await?;
+            let rows = sqlx::query(STATS_QUERY)
+                .bind(profile_id)
+                .fetch_all(active.connection_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching profile stats"))?;
+            session.close(false).await?;
+            let mut counts = Vec::with_capacity(rows.len());
+            for row in rows {
+                let kind: i16 = row.try_get(0)?;
+                let kind = EntryKind::try_from(kind as usize)?;
+                let enc_category: Vec<u8> = row.try_get(1)?;
+                let count: i64 = row.try_get(2)?;
+                counts.push((kind, enc_category, count));
+            }
+            unblock(move || {
+                let mut stats = ProfileStats::default();
+                for (kind, enc_category, count) in counts {
+                    stats.add(kind, key.decrypt_entry_category(enc_category)?, count);
+                }
+                Ok(stats)
+            })
+            .await
+        })
+    }
+
+    fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> BoxFuture<'_, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (_, key) = acquire_key(&mut session).await?;
+            session.close(false).await?;
+            Ok(key.plaintext_name_categories.clone())
+        })
+    }
+
+    fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: String,
+    ) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (_, key) = acquire_key(&mut session).await?;
+            let (index_name, enc_name) = unblock(move || encode_tag_index(&tag_name, &key)).await?;
+            let mut active = acquire_session(&mut session).await?;
+            sqlx::query(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON items_tags (value, item_id)
+                WHERE name = '\\x{}'::bytea AND plaintext = 1",
+                index_name,
+                hex::encode(enc_name)
+            ))
+            .execute(active.connection_mut())
+            .await
+            .map_err(err_map!(Backend, "Error creating tag index"))?;
+            session.close(false).await
+        })
+    }
+
     fn remove_profile(&self, name: String) -> BoxFuture<'_, Result<bool, Error>> {
         Box::pin(async move {
+            if name == self.active_profile || name == self.get_default_profile().await? {
+                return Err(err_msg!(
+                    Input,
+                    "Cannot remove the active or default profile"
+                ));
+            }
             let mut conn = self.conn_pool.acquire().await?;
             let ret = sqlx::query("DELETE FROM profiles WHERE name=$1")
                 .bind(&name)
@@ -229,6 +344,58 @@ impl Backend for PostgresBackend {
         })
     }
 
+    fn schema_version(&self) -> BoxFuture<'_, Result<u32, Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn_pool.acquire().await?;
+            let version: Option<String> = sqlx::query_scalar(CONFIG_FETCH_QUERY)
+                .bind("version")
+                .fetch_one(conn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching store version"))?;
+            conn.return_to_pool().await;
+            parse_schema_version(
+                version
+                    .as_deref()
+                    .ok_or_else(|| err_msg!(Unsupported, "Store version not found"))?,
+            )
+        })
+    }
+
+    fn migrate(&self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn_pool.acquire().await?;
+            let mut txn = conn.begin().await?;
+            let version: Option<String> = sqlx::query_scalar(CONFIG_FETCH_QUERY)
+                .bind("version")
+                .fetch_one(txn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching store version"))?;
+            let version = parse_schema_version(
+                version
+                    .as_deref()
+                    .ok_or_else(|| err_msg!(Unsupported, "Store version not found"))?,
+            )?;
+            if version == SCHEMA_VERSION {
+                return Ok(());
+            }
+            for step in &MIGRATIONS[(version - 1) as usize..] {
+                sqlx::query(step)
+                    .execute(txn.as_mut())
+                    .await
+                    .map_err(err_map!(Backend, "Error migrating store schema"))?;
+            }
+            sqlx::query(CONFIG_UPDATE_QUERY)
+                .bind("version")
+                .bind(SCHEMA_VERSION.to_string())
+                .execute(txn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error updating store version"))?;
+            txn.commit().await?;
+            conn.return_to_pool().await;
+            Ok(())
+        })
+    }
+
     fn rekey(
         &mut self,
         method: StoreKeyMethod,
@@ -288,7 +455,9 @@ impl Backend for PostgresBackend {
         profile: Option<String>,
         kind: Option<EntryKind>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
@@ -298,13 +467,16 @@ impl Backend for PostgresBackend {
             let session = self.session(profile, false)?;
             let mut active = session.owned_ref();
             let (profile_id, key) = acquire_key(&mut active).await?;
+            let name_range = name_prefix_range(category.as_deref(), name_prefix, &key)?;
             let scan = perform_scan(
                 active,
                 profile_id,
                 key.clone(),
                 kind,
                 category.clone(),
+                name_range,
                 tag_filter,
+                tag_mode,
                 offset,
                 limit,
                 order_by,
@@ -326,6 +498,7 @@ impl Backend for PostgresBackend {
             self.key_cache.clone(),
             profile.unwrap_or_else(|| self.active_profile.clone()),
             transaction,
+            self.max_value_size,
         ))
     }
 
@@ -392,11 +565,57 @@ impl BackendSession for DbSession<Postgres> {
         })
     }
 
+    fn count_estimate<'q>(
+        &'q mut self,
+        kind: Option<EntryKind>,
+        category: Option<&'q str>,
+        tag_filter: Option<TagFilter>,
+    ) -> BoxFuture<'q, Result<i64, Error>> {
+        let enc_category = category.map(|c| ProfileKey::prepare_input(c.as_bytes()));
+
+        Box::pin(async move {
+            let (profile_id, key) = acquire_key(&mut *self).await?;
+            let mut params = QueryParams::new();
+            params.push(profile_id);
+            params.push(kind.map(|k| k as i16));
+            let (enc_category, tag_filter) = unblock({
+                let params_len = params.len() + 1; // plus category
+                move || {
+                    Result::<_, Error>::Ok((
+                        enc_category
+                            .map(|c| key.encrypt_entry_category(c))
+                            .transpose()?,
+                        encode_tag_filter::<PostgresBackend>(tag_filter, &key, params_len)?,
+                    ))
+                }
+            })
+            .await?;
+            params.push(enc_category);
+            let query = extend_query::<PostgresBackend>(
+                ESTIMATE_QUERY,
+                &mut params,
+                tag_filter,
+                None,
+                None,
+                None,
+                false,
+            )?;
+            let mut active = acquire_session(&mut *self).await?;
+            let plan: String = sqlx::query_scalar_with(query.as_str(), params)
+                .fetch_one(active.connection_mut())
+                .await
+                .map_err(err_map!(Backend, "Error performing count estimate query"))?;
+            parse_plan_rows(&plan)
+                .ok_or_else(|| err_msg!(Unexpected, "Error parsing query plan row estimate"))
+        })
+    }
+
     fn fetch(
         &mut self,
         kind: EntryKind,
         category: &str,
         name: &str,
+        tag_mode: TagMode,
         for_update: bool,
     ) -> BoxFuture<'_, Result<Option<Entry>, Error>> {
         let category = category.to_string();
@@ -404,31 +623,39 @@ impl BackendSession for DbSession<Postgres> {
 
         Box::pin(async move {
             let (profile_id, key) = acquire_key(&mut *self).await?;
-            let (enc_category, enc_name) = unblock({
+            let (enc_category, enc_name, tag_names) = unblock({
                 let key = key.clone();
                 let category = ProfileKey::prepare_input(category.as_bytes());
                 let name = ProfileKey::prepare_input(name.as_bytes());
                 move || {
+                    let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
                     Result::<_, Error>::Ok((
                         key.encrypt_entry_category(category)?,
-                        key.encrypt_entry_name(name)?,
+                        enc_name,
+                        encode_tag_names(&tag_mode, &key)?,
                     ))
                 }
             })
             .await?;
             let mut active = acquire_session(&mut *self).await?;
-            if let Some(row) = sqlx::query(if for_update && active.in_transaction() {
-                FETCH_QUERY_UPDATE
-            } else {
-                FETCH_QUERY
-            })
-            .bind(profile_id)
-            .bind(kind as i16)
-            .bind(enc_category)
-            .bind(enc_name)
-            .fetch_optional(active.connection_mut())
-            .await
-            .map_err(err_map!(Backend, "Error performing fetch query"))?
+            let mut params = QueryParams::new();
+            params.push(profile_id);
+            params.push(kind as i16);
+            params.push(enc_category);
+            params.push(enc_name);
+            let query = extend_tag_select::<PostgresBackend>(
+                if for_update && active.in_transaction() {
+                    FETCH_QUERY_UPDATE
+                } else {
+                    FETCH_QUERY
+                },
+                &mut params,
+                tag_names,
+            );
+            if let Some(row) = sqlx::query_with(query.as_str(), params)
+                .fetch_optional(active.connection_mut())
+                .await
+                .map_err(err_map!(Backend, "Error performing fetch query"))?
             {
                 let value = row.try_get(1)?;
                 let tags = row.try_get::<Option<String>, _>(2)?.map(String::into_bytes);
@@ -457,6 +684,7 @@ impl BackendSession for DbSession<Postgres> {
         kind: Option<EntryKind>,
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
@@ -473,7 +701,9 @@ impl BackendSession for DbSession<Postgres> {
                 key.clone(),
                 kind,
                 category.clone(),
+                None,
                 tag_filter,
+                tag_mode,
                 None,
                 limit,
                 order_by,
@@ -552,13 +782,15 @@ impl BackendSession for DbSession<Postgres> {
                 let value = ProfileKey::prepare_input(value.unwrap_or_default());
                 let tags = tags.map(prepare_tags);
                 Box::pin(async move {
+                    self.check_value_size(value.len())?;
                     let (_, key) = acquire_key(&mut *self).await?;
                     let (enc_category, enc_name, enc_value, enc_tags) = unblock(move || {
                         let enc_value =
                             key.encrypt_entry_value(category.as_ref(), name.as_ref(), value)?;
+                        let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
                         Result::<_, Error>::Ok((
                             key.encrypt_entry_category(category)?,
-                            key.encrypt_entry_name(name)?,
+                            enc_name,
                             enc_value,
                             tags.transpose()?
                                 .map(|t| key.encrypt_entry_tags(t))
@@ -587,10 +819,8 @@ impl BackendSession for DbSession<Postgres> {
             EntryOperation::Remove => Box::pin(async move {
                 let (_, key) = acquire_key(&mut *self).await?;
                 let (enc_category, enc_name) = unblock(move || {
-                    Result::<_, Error>::Ok((
-                        key.encrypt_entry_category(category)?,
-                        key.encrypt_entry_name(name)?,
-                    ))
+                    let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
+                    Result::<_, Error>::Ok((key.encrypt_entry_category(category)?, enc_name))
                 })
                 .await?;
                 let mut active = acquire_session(&mut *self).await?;
@@ -599,6 +829,75 @@ impl BackendSession for DbSession<Postgres> {
         }
     }
 
+    fn upsert<'q>(
+        &'q mut self,
+        kind: EntryKind,
+        category: &'q str,
+        name: &'q str,
+        value: &'q [u8],
+        tags: Option<&'q [EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> BoxFuture<'q, Result<bool, Error>> {
+        let category = ProfileKey::prepare_input(category.as_bytes());
+        let name = ProfileKey::prepare_input(name.as_bytes());
+        let value = ProfileKey::prepare_input(value);
+        let tags = tags.map(prepare_tags);
+        Box::pin(async move {
+            self.check_value_size(value.len())?;
+            let (_, key) = acquire_key(&mut *self).await?;
+            let (enc_category, enc_name, enc_value, enc_tags) = unblock(move || {
+                let enc_value = key.encrypt_entry_value(category.as_ref(), name.as_ref(), value)?;
+                let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
+                Result::<_, Error>::Ok((
+                    key.encrypt_entry_category(category)?,
+                    enc_name,
+                    enc_value,
+                    tags.transpose()?
+                        .map(|t| key.encrypt_entry_tags(t))
+                        .transpose()?,
+                ))
+            })
+            .await?;
+            let mut active = acquire_session(&mut *self).await?;
+            let mut txn = active.as_transaction().await?;
+            let inserted = perform_upsert(
+                &mut txn,
+                kind,
+                &enc_category,
+                &enc_name,
+                &enc_value,
+                enc_tags,
+                expiry_ms,
+            )
+            .await?;
+            txn.commit().await?;
+            Ok(inserted)
+        })
+    }
+
+    fn seal<'q>(&'q mut self, value: &'q [u8]) -> BoxFuture<'q, Result<Vec<u8>, Error>> {
+        let value = SecretBytes::from_slice(value);
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            unblock(move || key.seal_value(value)).await
+        })
+    }
+
+    fn unseal<'q>(&'q mut self, sealed: &'q [u8]) -> BoxFuture<'q, Result<SecretBytes, Error>> {
+        let sealed = sealed.to_vec();
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            unblock(move || key.unseal_value(sealed)).await
+        })
+    }
+
+    fn export_tag_hasher(&mut self) -> BoxFuture<'_, Result<TagHasher, Error>> {
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            Ok(key.tag_hasher())
+        })
+    }
+
     fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async move {
             let mut sess = acquire_session(&mut *self).await?;
@@ -655,6 +954,15 @@ impl QueryPrepare for PostgresBackend {
     }
 }
 
+/// Extract the row estimate from the top node of a text format query plan
+fn parse_plan_rows(plan: &str) -> Option<i64> {
+    let (_, rest) = plan.split_once(" rows=")?;
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
 async fn acquire_key(
     session: &mut DbSession<Postgres>,
 ) -> Result<(ProfileId, Arc<ProfileKey>), Error> {
@@ -744,6 +1052,45 @@ async fn perform_insert(
             .map_err(err_map!(Backend, "Error removing existing entry tags"))?;
         row_id
     };
+    insert_tags(active, row_id, enc_tags).await
+}
+
+async fn perform_upsert(
+    active: &mut DbSessionTxn<'_, Postgres>,
+    kind: EntryKind,
+    enc_category: &[u8],
+    enc_name: &[u8],
+    enc_value: &[u8],
+    enc_tags: Option<Vec<EncEntryTag>>,
+    expiry_ms: Option<i64>,
+) -> Result<bool, Error> {
+    trace!("Upsert entry");
+    let (row_id, inserted): (i64, bool) = sqlx::query_as(UPSERT_QUERY)
+        .bind(active.profile_id)
+        .bind(kind as i16)
+        .bind(enc_category)
+        .bind(enc_name)
+        .bind(enc_value)
+        .bind(expiry_ms.map(expiry_timestamp).transpose()?)
+        .fetch_one(active.connection_mut())
+        .await
+        .map_err(err_map!(Backend, "Error upserting entry"))?;
+    if !inserted {
+        sqlx::query(TAG_DELETE_QUERY)
+            .bind(row_id)
+            .execute(active.connection_mut())
+            .await
+            .map_err(err_map!(Backend, "Error removing existing entry tags"))?;
+    }
+    insert_tags(active, row_id, enc_tags).await?;
+    Ok(inserted)
+}
+
+async fn insert_tags(
+    active: &mut DbSessionTxn<'_, Postgres>,
+    row_id: i64,
+    enc_tags: Option<Vec<EncEntryTag>>,
+) -> Result<(), Error> {
     if let Some(tags) = enc_tags {
         for tag in tags {
             sqlx::query(TAG_INSERT_QUERY)
@@ -789,7 +1136,9 @@ fn perform_scan(
     key: Arc<ProfileKey>,
     kind: Option<EntryKind>,
     category: Option<String>,
+    name_range: Option<NameRange>,
     tag_filter: Option<TagFilter>,
+    tag_mode: TagMode,
     offset: Option<i64>,
     limit: Option<i64>,
     order_by: Option<OrderBy>,
@@ -800,21 +1149,25 @@ fn perform_scan(
         let mut params = QueryParams::new();
         params.push(profile_id);
         params.push(kind.map(|k| k as i16));
-        let (enc_category, tag_filter) = unblock({
+        let (enc_category, tag_filter, tag_names) = unblock({
             let key = key.clone();
             let enc_category = category.map(|c| ProfileKey::prepare_input(c.as_bytes()));
-            let params_len = params.len() + 1; // plus category
+            // plus category and name range
+            let params_len = params.len() + if name_range.is_some() { 3 } else { 1 };
             move || {
                 Result::<_, Error>::Ok((
                     enc_category
                         .map(|c| key.encrypt_entry_category(c))
                         .transpose()?,
-                    encode_tag_filter::<PostgresBackend>(tag_filter, &key, params_len)?
+                    encode_tag_filter::<PostgresBackend>(tag_filter, &key, params_len)?,
+                    encode_tag_names(&tag_mode, &key)?,
                 ))
             }
         }).await?;
         params.push(enc_category);
-        let mut query = extend_query::<PostgresBackend>(SCAN_QUERY, &mut params, tag_filter, offset, limit, order_by, descending)?;
+        let query = extend_name_range::<PostgresBackend>(SCAN_QUERY, &mut params, name_range);
+        let query = extend_query::<PostgresBackend>(&query, &mut params, tag_filter, offset, limit, order_by, descending)?;
+        let mut query = extend_tag_select::<PostgresBackend>(&query, &mut params, tag_names);
         if for_update {
             query.push_str(" FOR NO KEY UPDATE");
         }
@@ -857,4 +1210,13 @@ mod tests {
             "This $3 is $12 a $5 string!",
         );
     }
+
+    #[test]
+    fn postgres_parse_plan_rows() {
+        assert_eq!(
+            parse_plan_rows("Seq Scan on items i  (cost=0.00..25.88 rows=6 width=8)"),
+            Some(6)
+        );
+        assert_eq!(parse_plan_rows("Result  (cost=0.00..0.01)"), None);
+    }
 }
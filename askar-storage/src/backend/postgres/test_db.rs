@@ -83,6 +83,7 @@ impl TestDB {
             key_cache,
             opts.host,
             opts.name,
+            opts.max_value_size,
         ));
 
         Ok(TestDB {
@@ -16,26 +16,33 @@ use sqlx::{
 
 use super::{
     db_utils::{
-        decode_tags, decrypt_scan_batch, encode_profile_key, encode_tag_filter, expiry_timestamp,
-        extend_query, prepare_tags, random_profile_name, Connection, DbSession, DbSessionActive,
-        DbSessionRef, DbSessionTxn, EncScanEntry, ExtDatabase, QueryParams, QueryPrepare,
-        PAGE_SIZE,
+        decode_tags, decrypt_scan_batch, encode_profile_key, encode_tag_filter, encode_tag_index,
+        encode_tag_names, expiry_timestamp, extend_name_range, extend_query, extend_tag_select,
+        name_prefix_range, parse_schema_version, prepare_tags, random_profile_name, Connection,
+        DbSession, DbSessionActive, DbSessionRef, DbSessionTxn, EncScanEntry, ExtDatabase,
+        NameRange, QueryParams, QueryPrepare, PAGE_SIZE,
     },
-    Backend, BackendSession,
+    Backend, BackendKind, BackendSession, SCHEMA_VERSION,
 };
 use crate::{
-    backend::OrderBy,
-    entry::{EncEntryTag, Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter},
+    backend::{OrderBy, ProfileStats},
+    crypto::buffer::SecretBytes,
+    entry::{EncEntryTag, Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter, TagMode},
     error::Error,
     future::{unblock, BoxFuture},
-    protect::{EntryEncryptor, KeyCache, PassKey, ProfileId, ProfileKey, StoreKeyMethod},
+    protect::{
+        EntryEncryptor, KeyCache, PassKey, ProfileId, ProfileKey, StoreKeyMethod, TagHasher,
+    },
 };
 
 mod provision;
 pub use provision::SqliteStoreOptions;
+pub use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
 
 const CONFIG_FETCH_QUERY: &str = "SELECT value FROM config WHERE name = ?1";
 const CONFIG_UPDATE_QUERY: &str = "INSERT OR REPLACE INTO config (name, value) VALUES (?1, ?2)";
+// the statements upgrading the schema from each version to the next, starting from version 1
+const MIGRATIONS: &[&str] = &[];
 const COUNT_QUERY: &str = "SELECT COUNT(*) FROM items i
     WHERE profile_id = ?1
     AND (kind = ?2 OR ?2 IS NULL)
@@ -65,6 +72,10 @@ const DELETE_ALL_QUERY: &str = "DELETE FROM items AS i
     WHERE i.profile_id = ?1
     AND (i.kind = ?2 OR ?2 IS NULL)
     AND (i.category = ?3 OR ?3 IS NULL)";
+const STATS_QUERY: &str = "SELECT kind, category, COUNT(*) FROM items
+    WHERE profile_id = ?1
+    AND (expiry IS NULL OR DATETIME(expiry) > DATETIME('now'))
+    GROUP BY kind, category";
 const TAG_INSERT_QUERY: &str = "INSERT INTO items_tags
     (item_id, name, value, plaintext) VALUES (?1, ?2, ?3, ?4)";
 const TAG_DELETE_QUERY: &str = "DELETE FROM items_tags
@@ -75,6 +86,7 @@ pub struct SqliteBackend {
     conn_pool: SqlitePool,
     active_profile: String,
     key_cache: Arc<KeyCache>,
+    max_value_size: usize,
     path: String,
 }
 
@@ -84,11 +96,13 @@ impl SqliteBackend {
         active_profile: String,
         key_cache: KeyCache,
         path: String,
+        max_value_size: usize,
     ) -> Self {
         Self {
             conn_pool,
             active_profile,
             key_cache: Arc::new(key_cache),
+            max_value_size,
             path,
         }
     }
@@ -111,11 +125,19 @@ impl Backend for SqliteBackend {
     type Session = DbSession<Sqlite>;
 
     fn create_profile(&self, name: Option<String>) -> BoxFuture<'_, Result<String, Error>> {
+        self.create_profile_with_plaintext_names(name, Vec::new())
+    }
+
+    fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> BoxFuture<'_, Result<String, Error>> {
         let name = name.unwrap_or_else(random_profile_name);
         Box::pin(async move {
             let store_key = self.key_cache.store_key.clone();
             let (profile_key, enc_key) = unblock(move || {
-                let profile_key = ProfileKey::new()?;
+                let profile_key = ProfileKey::new()?.with_plaintext_names(categories);
                 let enc_key = encode_profile_key(&profile_key, &store_key)?;
                 Result::<_, Error>::Ok((profile_key, enc_key))
             })
@@ -142,6 +164,10 @@ impl Backend for SqliteBackend {
         })
     }
 
+    fn kind(&self) -> BackendKind {
+        BackendKind::Sqlite
+    }
+
     fn get_active_profile(&self) -> String {
         self.active_profile.clone()
     }
@@ -162,6 +188,14 @@ impl Backend for SqliteBackend {
     fn set_default_profile(&self, profile: String) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async move {
             let mut conn = self.conn_pool.acquire().await?;
+            let found: Option<i64> = sqlx::query_scalar("SELECT id FROM profiles WHERE name = ?1")
+                .bind(&profile)
+                .fetch_optional(conn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching profile"))?;
+            if found.is_none() {
+                return Err(err_msg!(NotFound, "Profile not found"));
+            }
             sqlx::query(CONFIG_UPDATE_QUERY)
                 .bind("default_profile")
                 .bind(profile)
@@ -186,8 +220,79 @@ impl Backend for SqliteBackend {
         })
     }
 
+    fn profile_stats(&self, profile: Option<String>) -> BoxFuture<'_, Result<ProfileStats, Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (profile_id, key) = acquire_key(&mut session).await?;
+            let mut active = acquire_session(&mut session).await?;
+            let rows = sqlx::query(STATS_QUERY)
+                .bind(profile_id)
+                .fetch_all(active.connection_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching profile stats"))?;
+            session.close(false).await?;
+            let mut counts = Vec::with_capacity(rows.len());
+            for row in rows {
+                let kind: u32 = row.try_get(0)?;
+                let kind = EntryKind::try_from(kind as usize)?;
+                let enc_category: Vec<u8> = row.try_get(1)?;
+                let count: i64 = row.try_get(2)?;
+                counts.push((kind, enc_category, count));
+            }
+            unblock(move || {
+                let mut stats = ProfileStats::default();
+                for (kind, enc_category, count) in counts {
+                    stats.add(kind, key.decrypt_entry_category(enc_category)?, count);
+                }
+                Ok(stats)
+            })
+            .await
+        })
+    }
+
+    fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> BoxFuture<'_, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (_, key) = acquire_key(&mut session).await?;
+            session.close(false).await?;
+            Ok(key.plaintext_name_categories.clone())
+        })
+    }
+
+    fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: String,
+    ) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            let mut session = self.session(profile, false)?;
+            let (_, key) = acquire_key(&mut session).await?;
+            let (index_name, enc_name) = unblock(move || encode_tag_index(&tag_name, &key)).await?;
+            let mut active = acquire_session(&mut session).await?;
+            sqlx::query(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON items_tags (value, item_id)
+                WHERE name = X'{}' AND plaintext = 1",
+                index_name,
+                hex::encode(enc_name)
+            ))
+            .execute(active.connection_mut())
+            .await
+            .map_err(err_map!(Backend, "Error creating tag index"))?;
+            session.close(false).await
+        })
+    }
+
     fn remove_profile(&self, name: String) -> BoxFuture<'_, Result<bool, Error>> {
         Box::pin(async move {
+            if name == self.active_profile || name == self.get_default_profile().await? {
+                return Err(err_msg!(
+                    Input,
+                    "Cannot remove the active or default profile"
+                ));
+            }
             let mut conn = self.conn_pool.acquire().await?;
             let ret = sqlx::query("DELETE FROM profiles WHERE name=?")
                 .bind(&name)
@@ -223,6 +328,58 @@ impl Backend for SqliteBackend {
         })
     }
 
+    fn schema_version(&self) -> BoxFuture<'_, Result<u32, Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn_pool.acquire().await?;
+            let version: Option<String> = sqlx::query_scalar(CONFIG_FETCH_QUERY)
+                .bind("version")
+                .fetch_one(conn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching store version"))?;
+            conn.return_to_pool().await;
+            parse_schema_version(
+                version
+                    .as_deref()
+                    .ok_or_else(|| err_msg!(Unsupported, "Store version not found"))?,
+            )
+        })
+    }
+
+    fn migrate(&self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            let mut conn = self.conn_pool.acquire().await?;
+            let mut txn = conn.begin().await?;
+            let version: Option<String> = sqlx::query_scalar(CONFIG_FETCH_QUERY)
+                .bind("version")
+                .fetch_one(txn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error fetching store version"))?;
+            let version = parse_schema_version(
+                version
+                    .as_deref()
+                    .ok_or_else(|| err_msg!(Unsupported, "Store version not found"))?,
+            )?;
+            if version == SCHEMA_VERSION {
+                return Ok(());
+            }
+            for step in &MIGRATIONS[(version - 1) as usize..] {
+                sqlx::query(step)
+                    .execute(txn.as_mut())
+                    .await
+                    .map_err(err_map!(Backend, "Error migrating store schema"))?;
+            }
+            sqlx::query(CONFIG_UPDATE_QUERY)
+                .bind("version")
+                .bind(SCHEMA_VERSION.to_string())
+                .execute(txn.as_mut())
+                .await
+                .map_err(err_map!(Backend, "Error updating store version"))?;
+            txn.commit().await?;
+            conn.return_to_pool().await;
+            Ok(())
+        })
+    }
+
     fn rekey(
         &mut self,
         method: StoreKeyMethod,
@@ -282,7 +439,9 @@ impl Backend for SqliteBackend {
         profile: Option<String>,
         kind: Option<EntryKind>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
@@ -292,13 +451,16 @@ impl Backend for SqliteBackend {
             let session = self.session(profile, false)?;
             let mut active = session.owned_ref();
             let (profile_id, key) = acquire_key(&mut active).await?;
+            let name_range = name_prefix_range(category.as_deref(), name_prefix, &key)?;
             let scan = perform_scan(
                 active,
                 profile_id,
                 key.clone(),
                 kind,
                 category.clone(),
+                name_range,
                 tag_filter,
+                tag_mode,
                 offset,
                 limit,
                 order_by,
@@ -319,6 +481,7 @@ impl Backend for SqliteBackend {
             self.key_cache.clone(),
             profile.unwrap_or_else(|| self.active_profile.clone()),
             transaction,
+            self.max_value_size,
         ))
     }
 
@@ -380,6 +543,7 @@ impl BackendSession for DbSession<Sqlite> {
         kind: EntryKind,
         category: &str,
         name: &str,
+        tag_mode: TagMode,
         _for_update: bool,
     ) -> BoxFuture<'_, Result<Option<Entry>, Error>> {
         let category = category.to_string();
@@ -387,24 +551,28 @@ impl BackendSession for DbSession<Sqlite> {
 
         Box::pin(async move {
             let (profile_id, key) = acquire_key(&mut *self).await?;
-            let (enc_category, enc_name) = unblock({
+            let (enc_category, enc_name, tag_names) = unblock({
                 let key = key.clone();
                 let category = ProfileKey::prepare_input(category.as_bytes());
                 let name = ProfileKey::prepare_input(name.as_bytes());
                 move || {
+                    let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
                     Result::<_, Error>::Ok((
                         key.encrypt_entry_category(category)?,
-                        key.encrypt_entry_name(name)?,
+                        enc_name,
+                        encode_tag_names(&tag_mode, &key)?,
                     ))
                 }
             })
             .await?;
+            let mut params = QueryParams::new();
+            params.push(profile_id);
+            params.push(kind as i16);
+            params.push(enc_category);
+            params.push(enc_name);
+            let query = extend_tag_select::<SqliteBackend>(FETCH_QUERY, &mut params, tag_names);
             let mut active = acquire_session(&mut *self).await?;
-            if let Some(row) = sqlx::query(FETCH_QUERY)
-                .bind(profile_id)
-                .bind(kind as i16)
-                .bind(enc_category)
-                .bind(enc_name)
+            if let Some(row) = sqlx::query_with(query.as_str(), params)
                 .fetch_optional(active.connection_mut())
                 .await
                 .map_err(err_map!(Backend, "Error performing fetch query"))?
@@ -431,6 +599,7 @@ impl BackendSession for DbSession<Sqlite> {
         kind: Option<EntryKind>,
         category: Option<&'q str>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
@@ -446,7 +615,9 @@ impl BackendSession for DbSession<Sqlite> {
                 key.clone(),
                 kind,
                 category.clone(),
+                None,
                 tag_filter,
+                tag_mode,
                 None,
                 limit,
                 order_by,
@@ -524,13 +695,15 @@ impl BackendSession for DbSession<Sqlite> {
                 let value = ProfileKey::prepare_input(value.unwrap_or_default());
                 let tags = tags.map(prepare_tags);
                 Box::pin(async move {
+                    self.check_value_size(value.len())?;
                     let (_, key) = acquire_key(&mut *self).await?;
                     let (enc_category, enc_name, enc_value, enc_tags) = unblock(move || {
                         let enc_value =
                             key.encrypt_entry_value(category.as_ref(), name.as_ref(), value)?;
+                        let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
                         Result::<_, Error>::Ok((
                             key.encrypt_entry_category(category)?,
-                            key.encrypt_entry_name(name)?,
+                            enc_name,
                             enc_value,
                             tags.transpose()?
                                 .map(|t| key.encrypt_entry_tags(t))
@@ -559,10 +732,8 @@ impl BackendSession for DbSession<Sqlite> {
             EntryOperation::Remove => Box::pin(async move {
                 let (_, key) = acquire_key(&mut *self).await?;
                 let (enc_category, enc_name) = unblock(move || {
-                    Result::<_, Error>::Ok((
-                        key.encrypt_entry_category(category)?,
-                        key.encrypt_entry_name(name)?,
-                    ))
+                    let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
+                    Result::<_, Error>::Ok((key.encrypt_entry_category(category)?, enc_name))
                 })
                 .await?;
                 let mut active = acquire_session(&mut *self).await?;
@@ -571,6 +742,75 @@ impl BackendSession for DbSession<Sqlite> {
         }
     }
 
+    fn upsert<'q>(
+        &'q mut self,
+        kind: EntryKind,
+        category: &'q str,
+        name: &'q str,
+        value: &'q [u8],
+        tags: Option<&'q [EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> BoxFuture<'q, Result<bool, Error>> {
+        let category = ProfileKey::prepare_input(category.as_bytes());
+        let name = ProfileKey::prepare_input(name.as_bytes());
+        let value = ProfileKey::prepare_input(value);
+        let tags = tags.map(prepare_tags);
+        Box::pin(async move {
+            self.check_value_size(value.len())?;
+            let (_, key) = acquire_key(&mut *self).await?;
+            let (enc_category, enc_name, enc_value, enc_tags) = unblock(move || {
+                let enc_value = key.encrypt_entry_value(category.as_ref(), name.as_ref(), value)?;
+                let enc_name = key.encrypt_entry_name(category.as_ref(), name)?;
+                Result::<_, Error>::Ok((
+                    key.encrypt_entry_category(category)?,
+                    enc_name,
+                    enc_value,
+                    tags.transpose()?
+                        .map(|t| key.encrypt_entry_tags(t))
+                        .transpose()?,
+                ))
+            })
+            .await?;
+            let mut active = acquire_session(&mut *self).await?;
+            let mut txn = active.as_transaction().await?;
+            let inserted = perform_upsert(
+                &mut txn,
+                kind,
+                &enc_category,
+                &enc_name,
+                &enc_value,
+                enc_tags,
+                expiry_ms,
+            )
+            .await?;
+            txn.commit().await?;
+            Ok(inserted)
+        })
+    }
+
+    fn seal<'q>(&'q mut self, value: &'q [u8]) -> BoxFuture<'q, Result<Vec<u8>, Error>> {
+        let value = SecretBytes::from_slice(value);
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            unblock(move || key.seal_value(value)).await
+        })
+    }
+
+    fn unseal<'q>(&'q mut self, sealed: &'q [u8]) -> BoxFuture<'q, Result<SecretBytes, Error>> {
+        let sealed = sealed.to_vec();
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            unblock(move || key.unseal_value(sealed)).await
+        })
+    }
+
+    fn export_tag_hasher(&mut self) -> BoxFuture<'_, Result<TagHasher, Error>> {
+        Box::pin(async move {
+            let (_, key) = acquire_key(&mut *self).await?;
+            Ok(key.tag_hasher())
+        })
+    }
+
     fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
         Box::pin(async move {
             let mut sess = acquire_session(&mut *self).await?;
@@ -695,6 +935,66 @@ async fn perform_insert(
             .map_err(err_map!(Backend, "Error removing existing entry tags"))?;
         row_id
     };
+    insert_tags(active, row_id, enc_tags).await
+}
+
+async fn perform_upsert(
+    active: &mut DbSessionTxn<'_, Sqlite>,
+    kind: EntryKind,
+    enc_category: &[u8],
+    enc_name: &[u8],
+    enc_value: &[u8],
+    enc_tags: Option<Vec<EncEntryTag>>,
+    expiry_ms: Option<i64>,
+) -> Result<bool, Error> {
+    trace!("Upsert entry");
+    // the update acquires the database write lock, even when no row matches, so
+    // the entry cannot be inserted by another connection before the insert below
+    let updated: Option<i64> = sqlx::query_scalar(UPDATE_QUERY)
+        .bind(active.profile_id)
+        .bind(kind as i16)
+        .bind(enc_category)
+        .bind(enc_name)
+        .bind(enc_value)
+        .bind(expiry_ms.map(expiry_timestamp).transpose()?)
+        .fetch_optional(active.connection_mut())
+        .await
+        .map_err(err_map!(Backend, "Error updating existing entry"))?;
+    let (row_id, inserted) = match updated {
+        Some(row_id) => (row_id, false),
+        None => {
+            let done = sqlx::query(INSERT_QUERY)
+                .bind(active.profile_id)
+                .bind(kind as i16)
+                .bind(enc_category)
+                .bind(enc_name)
+                .bind(enc_value)
+                .bind(expiry_ms.map(expiry_timestamp).transpose()?)
+                .execute(active.connection_mut())
+                .await
+                .map_err(err_map!(Backend, "Error inserting new entry"))?;
+            if done.rows_affected() == 0 {
+                return Err(err_msg!(Busy, "Entry was inserted concurrently"));
+            }
+            (done.last_insert_rowid(), true)
+        }
+    };
+    if !inserted {
+        sqlx::query(TAG_DELETE_QUERY)
+            .bind(row_id)
+            .execute(active.connection_mut())
+            .await
+            .map_err(err_map!(Backend, "Error removing existing entry tags"))?;
+    }
+    insert_tags(active, row_id, enc_tags).await?;
+    Ok(inserted)
+}
+
+async fn insert_tags(
+    active: &mut DbSessionTxn<'_, Sqlite>,
+    row_id: i64,
+    enc_tags: Option<Vec<EncEntryTag>>,
+) -> Result<(), Error> {
     if let Some(tags) = enc_tags {
         for tag in tags {
             sqlx::query(TAG_INSERT_QUERY)
@@ -740,7 +1040,9 @@ fn perform_scan(
     key: Arc<ProfileKey>,
     kind: Option<EntryKind>,
     category: Option<String>,
+    name_range: Option<NameRange>,
     tag_filter: Option<TagFilter>,
+    tag_mode: TagMode,
     offset: Option<i64>,
     limit: Option<i64>,
     order_by: Option<OrderBy>,
@@ -750,19 +1052,23 @@ fn perform_scan(
         let mut params = QueryParams::new();
         params.push(profile_id);
         params.push(kind.map(|k| k as i16));
-        let (enc_category, tag_filter) = unblock({
+        let (enc_category, tag_filter, tag_names) = unblock({
             let key = key.clone();
             let enc_category = category.as_ref().map(|c| ProfileKey::prepare_input(c.as_bytes()));
-            let params_len = params.len() + 1; // plus category
+            // plus category and name range
+            let params_len = params.len() + if name_range.is_some() { 3 } else { 1 };
             move || {
                 Result::<_, Error>::Ok((
                     enc_category.map(|c| key.encrypt_entry_category(c)).transpose()?,
-                    encode_tag_filter::<SqliteBackend>(tag_filter, &key, params_len)?
+                    encode_tag_filter::<SqliteBackend>(tag_filter, &key, params_len)?,
+                    encode_tag_names(&tag_mode, &key)?,
                 ))
             }
         }).await?;
         params.push(enc_category);
-        let query = extend_query::<SqliteBackend>(SCAN_QUERY, &mut params, tag_filter, offset, limit, order_by, descending)?;
+        let query = extend_name_range::<SqliteBackend>(SCAN_QUERY, &mut params, name_range);
+        let query = extend_query::<SqliteBackend>(&query, &mut params, tag_filter, offset, limit, order_by, descending)?;
+        let query = extend_tag_select::<SqliteBackend>(&query, &mut params, tag_names);
 
         let mut batch = Vec::with_capacity(PAGE_SIZE);
 
@@ -1,6 +1,11 @@
 use std::{
-    borrow::Cow, fs::remove_file, io::ErrorKind as IoErrorKind, str::FromStr,
-    thread::available_parallelism, time::Duration,
+    borrow::Cow,
+    fs::{metadata, remove_file, OpenOptions},
+    io::ErrorKind as IoErrorKind,
+    path::Path,
+    str::FromStr,
+    thread::available_parallelism,
+    time::Duration,
 };
 
 use sqlx::{
@@ -14,8 +19,8 @@ use sqlx::{
 use super::SqliteBackend;
 use crate::{
     backend::{
-        db_utils::{init_keys, random_profile_name},
-        ManageBackend,
+        db_utils::{init_keys, parse_schema_version, random_profile_name},
+        BackendKind, ManageBackend, StoreValidation, DEFAULT_MAX_VALUE_SIZE,
     },
     error::Error,
     future::{sleep, unblock, BoxFuture},
@@ -30,8 +35,17 @@ const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 const DEFAULT_JOURNAL_MODE: SqliteJournalMode = SqliteJournalMode::Wal;
 const DEFAULT_LOCKING_MODE: SqliteLockingMode = SqliteLockingMode::Normal;
 const DEFAULT_SYNCHRONOUS: SqliteSynchronous = SqliteSynchronous::Full;
+const DEFAULT_WAL_SYNCHRONOUS: SqliteSynchronous = SqliteSynchronous::Normal;
 
 /// Configuration options for Sqlite stores
+///
+/// The journal mode defaults to `WAL` for file databases and `MEMORY` for
+/// in-memory databases. In WAL mode the `synchronous` pragma defaults to
+/// `NORMAL`, which avoids a sync on every commit while still protecting
+/// against database corruption, but the most recent transactions may be
+/// rolled back following a power loss. Other journal modes default to `FULL`.
+/// `FULL` synchronization may be selected using the `synchronous` query
+/// parameter when every committed transaction must be durable.
 #[derive(Debug)]
 pub struct SqliteStoreOptions {
     pub(crate) in_memory: bool,
@@ -42,7 +56,9 @@ pub struct SqliteStoreOptions {
     pub(crate) journal_mode: SqliteJournalMode,
     pub(crate) locking_mode: SqliteLockingMode,
     pub(crate) shared_cache: bool,
-    pub(crate) synchronous: SqliteSynchronous,
+    pub(crate) synchronous: Option<SqliteSynchronous>,
+    pub(crate) read_only: bool,
+    pub(crate) max_value_size: usize,
 }
 
 impl Default for SqliteStoreOptions {
@@ -95,6 +111,8 @@ impl SqliteStoreOptions {
         let journal_mode = if let Some(mode) = opts.query.remove("journal_mode") {
             SqliteJournalMode::from_str(&mode)
                 .map_err(err_map!(Input, "Error parsing 'journal_mode' parameter"))?
+        } else if in_memory {
+            SqliteJournalMode::Memory
         } else {
             DEFAULT_JOURNAL_MODE
         };
@@ -109,11 +127,26 @@ impl SqliteStoreOptions {
         } else {
             in_memory
         };
-        let synchronous = if let Some(sync) = opts.query.remove("synchronous") {
-            SqliteSynchronous::from_str(&sync)
-                .map_err(err_map!(Input, "Error parsing 'synchronous' parameter"))?
+        let synchronous = opts
+            .query
+            .remove("synchronous")
+            .map(|sync| {
+                SqliteSynchronous::from_str(&sync)
+                    .map_err(err_map!(Input, "Error parsing 'synchronous' parameter"))
+            })
+            .transpose()?;
+        let read_only = if let Some(read_only) = opts.query.remove("read_only") {
+            read_only
+                .parse()
+                .map_err(err_map!(Input, "Error parsing 'read_only' parameter"))?
+        } else {
+            false
+        };
+        let max_value_size = if let Some(size) = opts.query.remove("max_value_size") {
+            size.parse()
+                .map_err(err_map!(Input, "Error parsing 'max_value_size' parameter"))?
         } else {
-            DEFAULT_SYNCHRONOUS
+            DEFAULT_MAX_VALUE_SIZE
         };
 
         Ok(Self {
@@ -126,19 +159,76 @@ impl SqliteStoreOptions {
             locking_mode,
             shared_cache,
             synchronous,
+            read_only,
+            max_value_size,
         })
     }
 
+    /// Set the journal mode applied to each new connection
+    pub fn journal_mode(mut self, journal_mode: SqliteJournalMode) -> Self {
+        self.journal_mode = journal_mode;
+        self
+    }
+
+    /// Set the `synchronous` pragma applied to each new connection
+    ///
+    /// Defaults to `NORMAL` in WAL mode and `FULL` otherwise.
+    pub fn synchronous(mut self, synchronous: SqliteSynchronous) -> Self {
+        self.synchronous = Some(synchronous);
+        self
+    }
+
+    fn synchronous_mode(&self) -> SqliteSynchronous {
+        self.synchronous
+            .unwrap_or(if matches!(self.journal_mode, SqliteJournalMode::Wal) {
+                DEFAULT_WAL_SYNCHRONOUS
+            } else {
+                DEFAULT_SYNCHRONOUS
+            })
+    }
+
+    /// Open the database in read-only mode
+    ///
+    /// Any attempt to modify the store will fail with an `Unsupported` error,
+    /// and the journal and auto-vacuum modes of the database are left unchanged.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set the maximum size in bytes of an entry value
+    ///
+    /// Defaults to [`DEFAULT_MAX_VALUE_SIZE`]. This may also be set using the
+    /// `max_value_size` query parameter of the store URL.
+    pub fn max_value_size(mut self, max_value_size: usize) -> Self {
+        self.max_value_size = max_value_size;
+        self
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.in_memory && matches!(self.journal_mode, SqliteJournalMode::Wal) {
+            return Err(err_msg!(
+                Input,
+                "The WAL journal mode is not supported for in-memory databases"
+            ));
+        }
+        Ok(())
+    }
+
     async fn pool(&self, auto_create: bool) -> std::result::Result<SqlitePool, SqlxError> {
         #[allow(unused_mut)]
         let mut conn_opts = SqliteConnectOptions::from_str(self.path.as_ref())?
             .create_if_missing(auto_create)
-            .auto_vacuum(SqliteAutoVacuum::Incremental)
             .busy_timeout(self.busy_timeout)
-            .journal_mode(self.journal_mode)
             .locking_mode(self.locking_mode)
             .shared_cache(self.shared_cache)
-            .synchronous(self.synchronous);
+            .synchronous(self.synchronous_mode())
+            .read_only(self.read_only);
+        if !self.read_only {
+            conn_opts = conn_opts
+                .auto_vacuum(SqliteAutoVacuum::Incremental)
+                .journal_mode(self.journal_mode);
+        }
         #[cfg(feature = "log")]
         {
             conn_opts = conn_opts
@@ -164,6 +254,29 @@ impl SqliteStoreOptions {
         profile: Option<String>,
         recreate: bool,
     ) -> Result<SqliteBackend, Error> {
+        Ok(self
+            .open_or_provision(method, pass_key, profile, recreate)
+            .await?
+            .0)
+    }
+
+    /// Open an existing Sqlite store from these configuration options, or
+    /// provision a new store if none exists
+    ///
+    /// The returned flag is `true` if a new store was provisioned. An existing
+    /// store is only replaced when `recreate` is set. If the store is
+    /// provisioned concurrently by another instance, it is opened instead.
+    pub async fn open_or_provision(
+        self,
+        method: StoreKeyMethod,
+        pass_key: PassKey<'_>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> Result<(SqliteBackend, bool), Error> {
+        self.validate()?;
+        if self.read_only {
+            return Err(err_msg!(Input, "Cannot provision a read-only store"));
+        }
         if recreate && !self.in_memory {
             try_remove_file(self.path.to_string()).await?;
         }
@@ -172,40 +285,106 @@ impl SqliteStoreOptions {
             .await
             .map_err(err_map!(Backend, "Error creating database pool"))?;
 
-        if !recreate {
-            let mut conn = conn_pool.acquire().await?;
-            let found = sqlx::query_scalar::<_, i64>(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='config'",
+        if !recreate && has_config(&conn_pool).await? {
+            let backend = open_db(
+                conn_pool,
+                Some(method),
+                pass_key,
+                profile,
+                self.path.to_string(),
+                self.max_value_size,
             )
-            .fetch_one(conn.as_mut())
-            .await
-            .map_err(err_map!(Backend, "Error checking for existing store"))?
-                == 1;
-            conn.return_to_pool().await;
-            if found {
-                return open_db(
+            .await?;
+            return Ok((backend, false));
+        }
+        // else: no 'config' table, assume empty database
+
+        let default_profile = profile.clone().unwrap_or_else(random_profile_name);
+        let key_cache = match init_db(
+            &conn_pool,
+            &default_profile,
+            method.clone(),
+            pass_key.as_ref(),
+        )
+        .await
+        {
+            Ok(key_cache) => key_cache,
+            // another instance may have provisioned the store after the check
+            Err(err) if recreate || !has_config(&conn_pool).await? => return Err(err),
+            Err(_) => {
+                let backend = open_db(
                     conn_pool,
                     Some(method),
                     pass_key,
                     profile,
                     self.path.to_string(),
+                    self.max_value_size,
                 )
-                .await;
+                .await?;
+                return Ok((backend, false));
             }
-        }
-        // else: no 'config' table, assume empty database
-
-        let default_profile = profile.unwrap_or_else(random_profile_name);
-        let key_cache = init_db(&conn_pool, &default_profile, method, pass_key).await?;
+        };
 
-        Ok(SqliteBackend::new(
-            conn_pool,
-            default_profile,
-            key_cache,
-            self.path.to_string(),
+        Ok((
+            SqliteBackend::new(
+                conn_pool,
+                default_profile,
+                key_cache,
+                self.path.to_string(),
+                self.max_value_size,
+            ),
+            true,
         ))
     }
 
+    /// Check that a Sqlite store could be provisioned from these configuration
+    /// options, without creating the database file or the store schema
+    ///
+    /// An existing database file must be writable by the current process, and
+    /// is opened read-only to check for a store. Otherwise the parent directory
+    /// must exist, and a temporary file is created and removed to check that it
+    /// is writable.
+    pub async fn validate_provision(self) -> Result<StoreValidation, Error> {
+        self.validate()?;
+        if self.read_only {
+            return Err(err_msg!(Input, "Cannot provision a read-only store"));
+        }
+        if self.in_memory {
+            return Ok(StoreValidation {
+                kind: BackendKind::Sqlite,
+                create_database: true,
+                store_exists: false,
+            });
+        }
+        let file_exists = unblock({
+            let path = self.path.clone();
+            move || check_database_path(Path::new(&path))
+        })
+        .await?;
+        let store_exists = if file_exists {
+            let conn_opts = SqliteConnectOptions::from_str(self.path.as_ref())?
+                .create_if_missing(false)
+                .busy_timeout(self.busy_timeout)
+                .read_only(true);
+            let conn_pool = SqlitePoolOptions::default()
+                .min_connections(0)
+                .max_connections(1)
+                .connect_with(conn_opts)
+                .await
+                .map_err(err_map!(Backend, "Error opening database"))?;
+            let found = has_config(&conn_pool).await;
+            conn_pool.close().await;
+            found?
+        } else {
+            false
+        };
+        Ok(StoreValidation {
+            kind: BackendKind::Sqlite,
+            create_database: !file_exists,
+            store_exists,
+        })
+    }
+
     /// Open an existing Sqlite store from this set of configuration options
     pub async fn open(
         self,
@@ -213,6 +392,7 @@ impl SqliteStoreOptions {
         pass_key: PassKey<'_>,
         profile: Option<String>,
     ) -> Result<SqliteBackend, Error> {
+        self.validate()?;
         let conn_pool = match self.pool(false).await {
             Ok(pool) => Ok(pool),
             Err(SqlxError::Database(db_err)) => {
@@ -229,7 +409,15 @@ impl SqliteStoreOptions {
             }
             Err(err) => Err(err.into()),
         }?;
-        open_db(conn_pool, method, pass_key, profile, self.path.to_string()).await
+        open_db(
+            conn_pool,
+            method,
+            pass_key,
+            profile,
+            self.path.to_string(),
+            self.max_value_size,
+        )
+        .await
     }
 
     /// Remove the Sqlite store defined by these configuration options
@@ -278,9 +466,36 @@ impl<'a> ManageBackend<'a> for SqliteStoreOptions {
         Box::pin(self.provision(method, pass_key, profile, recreate))
     }
 
+    fn open_or_provision_backend(
+        self,
+        method: StoreKeyMethod,
+        pass_key: PassKey<'a>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> BoxFuture<'a, Result<(SqliteBackend, bool), Error>> {
+        Box::pin(self.open_or_provision(method, pass_key, profile, recreate))
+    }
+
     fn remove_backend(self) -> BoxFuture<'a, Result<bool, Error>> {
         Box::pin(self.remove())
     }
+
+    fn validate_backend(self) -> BoxFuture<'a, Result<StoreValidation, Error>> {
+        Box::pin(self.validate_provision())
+    }
+}
+
+async fn has_config(conn_pool: &SqlitePool) -> Result<bool, Error> {
+    let mut conn = conn_pool.acquire().await?;
+    let found = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='config'",
+    )
+    .fetch_one(conn.as_mut())
+    .await
+    .map_err(err_map!(Backend, "Error checking for existing store"))?
+        == 1;
+    conn.return_to_pool().await;
+    Ok(found)
 }
 
 async fn init_db(
@@ -297,7 +512,7 @@ async fn init_db(
 
     let mut conn = conn_pool.acquire().await?;
 
-    sqlx::query(
+    let created = sqlx::query(
         r#"
         BEGIN EXCLUSIVE TRANSACTION;
 
@@ -358,7 +573,12 @@ async fn init_db(
     .bind(store_key_ref)
     .bind(enc_profile_key)
     .execute(conn.as_mut())
-    .await.map_err(err_map!(Backend, "Error creating database tables"))?;
+    .await;
+    if let Err(err) = created {
+        // end the transaction if it was started, so that the lock is released
+        sqlx::query("ROLLBACK").execute(conn.as_mut()).await.ok();
+        return Err(err_msg!(Backend, "Error creating database tables").with_cause(err));
+    }
 
     let row = sqlx::query("SELECT id FROM profiles WHERE name = ?1")
         .persistent(false)
@@ -380,9 +600,10 @@ async fn open_db(
     pass_key: PassKey<'_>,
     profile: Option<String>,
     path: String,
+    max_value_size: usize,
 ) -> Result<SqliteBackend, Error> {
     let mut conn = conn_pool.acquire().await?;
-    let mut ver_ok = false;
+    let mut version: Option<u32> = None;
     let mut default_profile: Option<String> = None;
     let mut store_key_ref: Option<String> = None;
 
@@ -402,15 +623,12 @@ async fn open_db(
                 store_key_ref.replace(row.try_get(1)?);
             }
             "version" => {
-                if row.try_get::<&str, _>(1)? != "1" {
-                    return Err(err_msg!(Unsupported, "Unsupported store version"));
-                }
-                ver_ok = true;
+                version.replace(parse_schema_version(row.try_get(1)?)?);
             }
             _ => (),
         }
     }
-    if !ver_ok {
+    if version.is_none() {
         return Err(err_msg!(Unsupported, "Store version not found"));
     }
     let profile = profile
@@ -442,7 +660,70 @@ async fn open_db(
     conn.return_to_pool().await;
     key_cache.add_profile_mut(profile.clone(), profile_id, profile_key);
 
-    Ok(SqliteBackend::new(conn_pool, profile, key_cache, path))
+    Ok(SqliteBackend::new(
+        conn_pool,
+        profile,
+        key_cache,
+        path,
+        max_value_size,
+    ))
+}
+
+/// Check that a database file could be opened or created for writing,
+/// returning `true` if the file exists
+///
+/// An existing file is opened for writing without modifying it. Otherwise a
+/// temporary file is created and removed in the parent directory, so that the
+/// permissions of the current process are checked.
+fn check_database_path(path: &Path) -> Result<bool, Error> {
+    match metadata(path) {
+        Ok(meta) if meta.is_dir() => Err(err_msg!(Input, "The database path is a directory")),
+        Ok(_) => match OpenOptions::new().write(true).open(path) {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == IoErrorKind::PermissionDenied => {
+                Err(err_msg!(Input, "The database file is not writable").with_cause(err))
+            }
+            Err(err) => Err(err_msg!(Backend, "Error opening database file").with_cause(err)),
+        },
+        Err(err) if err.kind() == IoErrorKind::NotFound => {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            match metadata(parent) {
+                Ok(meta) if !meta.is_dir() => Err(err_msg!(
+                    Input,
+                    "The parent of the database path is not a directory"
+                )),
+                Ok(_) => {
+                    let probe = parent.join(format!(".askar-probe-{}", uuid::Uuid::new_v4()));
+                    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+                        Ok(file) => {
+                            drop(file);
+                            remove_file(&probe)
+                                .map_err(err_map!(Backend, "Error removing temporary file"))?;
+                            Ok(false)
+                        }
+                        Err(err) if err.kind() == IoErrorKind::PermissionDenied => {
+                            Err(err_msg!(Input, "The database directory is not writable")
+                                .with_cause(err))
+                        }
+                        Err(err) => {
+                            Err(err_msg!(Backend, "Error checking database directory")
+                                .with_cause(err))
+                        }
+                    }
+                }
+                Err(err) if err.kind() == IoErrorKind::NotFound => {
+                    Err(err_msg!(NotFound, "The database directory was not found"))
+                }
+                Err(err) => {
+                    Err(err_msg!(Backend, "Error checking database directory").with_cause(err))
+                }
+            }
+        }
+        Err(err) => Err(err_msg!(Backend, "Error checking database path").with_cause(err)),
+    }
 }
 
 async fn try_remove_file(path: String) -> Result<bool, Error> {
@@ -470,3 +751,31 @@ async fn try_remove_file(path: String) -> Result<bool, Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_parse_uri() {
+        let opts = SqliteStoreOptions::new("sqlite://test.db").unwrap();
+        assert!(matches!(opts.synchronous_mode(), SqliteSynchronous::Normal));
+        assert!(matches!(opts.journal_mode, SqliteJournalMode::Wal));
+
+        let opts =
+            SqliteStoreOptions::new("sqlite://test.db?synchronous=normal&journal_mode=delete")
+                .unwrap();
+        assert!(matches!(opts.synchronous_mode(), SqliteSynchronous::Normal));
+        assert!(matches!(opts.journal_mode, SqliteJournalMode::Delete));
+
+        let opts = SqliteStoreOptions::new("sqlite://test.db?journal_mode=delete").unwrap();
+        assert!(matches!(opts.synchronous_mode(), SqliteSynchronous::Full));
+
+        let opts = SqliteStoreOptions::new("sqlite://test.db?synchronous=full").unwrap();
+        assert!(matches!(opts.synchronous_mode(), SqliteSynchronous::Full));
+
+        let opts = SqliteStoreOptions::new("sqlite://:memory:").unwrap();
+        assert!(matches!(opts.journal_mode, SqliteJournalMode::Memory));
+        assert!(matches!(opts.synchronous_mode(), SqliteSynchronous::Full));
+    }
+}
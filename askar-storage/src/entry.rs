@@ -2,6 +2,7 @@
 
 use std::{
     fmt::{self, Debug, Formatter},
+    ops::Deref,
     pin::Pin,
     str::FromStr,
 };
@@ -38,6 +39,9 @@ pub struct Entry {
     pub value: SecretBytes,
 
     /// Tags associated with the entry record
+    ///
+    /// Tags loaded from the store are sorted in the same order as a `TagSet`,
+    /// independent of the backend.
     pub tags: Vec<EntryTag>,
 }
 
@@ -63,6 +67,81 @@ impl Entry {
     pub(crate) fn sorted_tags(&self) -> Vec<&EntryTag> {
         sorted_tags(&self.tags)
     }
+
+    /// Describe the differences between this entry and another, for use in
+    /// debugging. Only the fields considered by the equality comparison are
+    /// checked, so the result is empty exactly when the entries are equal.
+    ///
+    /// This method requires the `entry_diff` feature.
+    #[cfg(feature = "entry_diff")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "entry_diff")))]
+    pub fn diff(&self, other: &Entry) -> EntryDiff {
+        let mut diff = EntryDiff::default();
+        if self.category != other.category {
+            diff.category = Some((self.category.clone(), other.category.clone()));
+        }
+        if self.name != other.name {
+            diff.name = Some((self.name.clone(), other.name.clone()));
+        }
+        if self.value != other.value {
+            diff.value_len = Some((self.value.len(), other.value.len()));
+        }
+        let (tags, other_tags) = (self.sorted_tags(), other.sorted_tags());
+        let (mut idx, mut other_idx) = (0, 0);
+        while idx < tags.len() || other_idx < other_tags.len() {
+            match (tags.get(idx), other_tags.get(other_idx)) {
+                (Some(tag), Some(other_tag)) if tag == other_tag => {
+                    idx += 1;
+                    other_idx += 1;
+                }
+                (Some(tag), Some(other_tag)) if tag > other_tag => {
+                    diff.tags_added.push((*other_tag).clone());
+                    other_idx += 1;
+                }
+                (Some(tag), _) => {
+                    diff.tags_removed.push((*tag).clone());
+                    idx += 1;
+                }
+                (None, Some(other_tag)) => {
+                    diff.tags_added.push((*other_tag).clone());
+                    other_idx += 1;
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        diff
+    }
+}
+
+/// The differences between two entries, produced by [`Entry::diff`]
+///
+/// Entry values are not included, only their lengths.
+#[cfg(feature = "entry_diff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "entry_diff")))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryDiff {
+    /// The differing categories of the entries
+    pub category: Option<(String, String)>,
+    /// The differing names of the entries
+    pub name: Option<(String, String)>,
+    /// The lengths of the entry values, when the values differ
+    pub value_len: Option<(usize, usize)>,
+    /// Tags which are only present on the first entry
+    pub tags_removed: Vec<EntryTag>,
+    /// Tags which are only present on the second entry
+    pub tags_added: Vec<EntryTag>,
+}
+
+#[cfg(feature = "entry_diff")]
+impl EntryDiff {
+    /// Check whether no differences were found
+    pub fn is_empty(&self) -> bool {
+        self.category.is_none()
+            && self.name.is_none()
+            && self.value_len.is_none()
+            && self.tags_removed.is_empty()
+            && self.tags_added.is_empty()
+    }
 }
 
 impl PartialEq for Entry {
@@ -107,6 +186,10 @@ pub enum EntryOperation {
 }
 
 /// A tag on an entry record in the store
+///
+/// Tag values may be empty strings. An empty value is stored and returned as
+/// such, and is matched by an equality filter on `""`; it is distinct from the
+/// tag being absent from the entry.
 #[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Zeroize)]
 pub enum EntryTag {
     /// An entry tag to be stored encrypted
@@ -176,6 +259,75 @@ impl Debug for EntryTag {
     }
 }
 
+/// A normalized set of entry tags
+///
+/// Tags are kept sorted according to the ordering of `EntryTag`: encrypted
+/// tags before plaintext tags, then by name and value. Exact duplicates are
+/// merged. Multiple tags may share the same name with distinct values, as
+/// this is how a tag with several values is represented.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct TagSet(Vec<EntryTag>);
+
+impl TagSet {
+    /// Create a new, empty `TagSet`
+    #[inline]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Add a tag to the set, returning `false` if it was already present
+    pub fn insert(&mut self, tag: EntryTag) -> bool {
+        match self.0.binary_search(&tag) {
+            Ok(_) => false,
+            Err(pos) => {
+                self.0.insert(pos, tag);
+                true
+            }
+        }
+    }
+
+    /// Access the normalized tags as a slice
+    #[inline]
+    pub fn as_slice(&self) -> &[EntryTag] {
+        &self.0
+    }
+
+    /// Unwrap into the normalized list of tags
+    #[inline]
+    pub fn into_vec(self) -> Vec<EntryTag> {
+        self.0
+    }
+}
+
+impl Deref for TagSet {
+    type Target = [EntryTag];
+
+    #[inline]
+    fn deref(&self) -> &[EntryTag] {
+        &self.0
+    }
+}
+
+impl From<Vec<EntryTag>> for TagSet {
+    fn from(mut tags: Vec<EntryTag>) -> Self {
+        tags.sort();
+        tags.dedup();
+        Self(tags)
+    }
+}
+
+impl FromIterator<EntryTag> for TagSet {
+    fn from_iter<I: IntoIterator<Item = EntryTag>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl From<TagSet> for Vec<EntryTag> {
+    fn from(tags: TagSet) -> Self {
+        tags.0
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct EncEntryTag {
     pub name: Vec<u8>,
@@ -212,6 +364,9 @@ impl TagFilter {
     }
 
     /// Get the inverse of a tag filter
+    ///
+    /// The result matches every entry not matched by `filter`, including entries
+    /// which lack the referenced tags entirely.
     #[inline]
     pub fn negate(filter: TagFilter) -> Self {
         Self {
@@ -284,6 +439,10 @@ impl TagFilter {
     }
 
     /// Create an EXISTS tag filter for a set of tag names
+    ///
+    /// Entries must have each of the tags set, with any value. Use
+    /// [`TagFilter::negate`] to select entries lacking all of the tags. The
+    /// filter is serialized using the `$exist` operator.
     #[inline]
     pub fn exist(names: Vec<String>) -> Self {
         Self {
@@ -317,6 +476,29 @@ impl FromStr for TagFilter {
     }
 }
 
+/// Selection of the tags to be loaded for each entry returned by a query
+///
+/// Only the selected tags are returned from the backend and decrypted. Tag
+/// filters are applied independently of the selected tags.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TagMode {
+    /// Load all tags for each entry
+    #[default]
+    All,
+    /// Load only the tags with the given names. As in a tag filter, the names of
+    /// plaintext tags may be given with a `~` prefix
+    Names(Vec<String>),
+    /// Skip loading tags, returning entries with an empty list of tags
+    None,
+}
+
+impl TagMode {
+    /// Load only the tags with the given names
+    pub fn names<N: Into<String>>(names: impl IntoIterator<Item = N>) -> Self {
+        Self::Names(names.into_iter().map(Into::into).collect())
+    }
+}
+
 /// An active record scan of a store backend
 pub struct Scan<'s, T> {
     #[allow(clippy::type_complexity)]
@@ -360,3 +542,75 @@ impl<S> Debug for Scan<'_, S> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_set_normalize() {
+        let tags = TagSet::from(vec![
+            EntryTag::Plaintext("b".to_string(), "2".to_string()),
+            EntryTag::Encrypted("c".to_string(), "3".to_string()),
+            EntryTag::Encrypted("a".to_string(), "1".to_string()),
+            EntryTag::Encrypted("c".to_string(), "3".to_string()),
+            EntryTag::Encrypted("a".to_string(), "0".to_string()),
+        ]);
+        assert_eq!(
+            tags.as_slice(),
+            &[
+                EntryTag::Encrypted("a".to_string(), "0".to_string()),
+                EntryTag::Encrypted("a".to_string(), "1".to_string()),
+                EntryTag::Encrypted("c".to_string(), "3".to_string()),
+                EntryTag::Plaintext("b".to_string(), "2".to_string()),
+            ]
+        );
+
+        let mut other = TagSet::new();
+        for tag in tags.iter().rev() {
+            assert!(other.insert(tag.clone()));
+        }
+        assert!(!other.insert(EntryTag::Encrypted("c".to_string(), "3".to_string())));
+        assert_eq!(other, tags);
+    }
+
+    #[cfg(feature = "entry_diff")]
+    #[test]
+    fn entry_diff() {
+        let entry = Entry::new(
+            EntryKind::Item,
+            "category",
+            "name",
+            "value",
+            vec![
+                EntryTag::Plaintext("b".to_string(), "2".to_string()),
+                EntryTag::Encrypted("a".to_string(), "1".to_string()),
+                EntryTag::Encrypted("a".to_string(), "1".to_string()),
+            ],
+        );
+        let mut other = entry.clone();
+        other.tags.reverse();
+        assert!(entry.diff(&other).is_empty());
+        assert_eq!(entry, other);
+
+        other.name = "other".to_string();
+        other.value = "new value".into();
+        other.tags = vec![
+            EntryTag::Encrypted("a".to_string(), "1".to_string()),
+            EntryTag::Plaintext("c".to_string(), "3".to_string()),
+        ];
+        assert_eq!(
+            entry.diff(&other),
+            EntryDiff {
+                category: None,
+                name: Some(("name".to_string(), "other".to_string())),
+                value_len: Some((5, 9)),
+                tags_removed: vec![
+                    EntryTag::Encrypted("a".to_string(), "1".to_string()),
+                    EntryTag::Plaintext("b".to_string(), "2".to_string()),
+                ],
+                tags_added: vec![EntryTag::Plaintext("c".to_string(), "3".to_string())],
+            }
+        );
+    }
+}
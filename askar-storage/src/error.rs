@@ -10,8 +10,23 @@ pub enum ErrorKind {
     Backend,
 
     /// The store backend was too busy to handle the request
+    ///
+    /// This is a transient condition and the operation may be retried. It is
+    /// produced for SQLite `SQLITE_BUSY` and `SQLITE_LOCKED` results (including
+    /// extended result codes), for Postgres serialization failures (`40001`),
+    /// deadlocks (`40P01`) and lock timeouts (`55P03`), and when no pooled
+    /// connection could be acquired before the pool timeout.
     Busy,
 
+    /// The connection to the store backend was lost or closed
+    ///
+    /// This is a transient condition and the operation may be retried, as a
+    /// new connection is established for the next operation. It is produced
+    /// when a connection is reset or closed by the server or an intermediate
+    /// proxy, for Postgres connection exceptions (class `08`) and server
+    /// shutdown (`57P01` to `57P03`), and when the connection pool is closed.
+    Connection,
+
     /// A custom error type for external integrations
     Custom,
 
@@ -40,6 +55,7 @@ impl ErrorKind {
         match self {
             Self::Backend => "Backend error",
             Self::Busy => "Busy",
+            Self::Connection => "Connection error",
             Self::Custom => "Custom error",
             Self::Duplicate => "Duplicate",
             Self::Encryption => "Encryption error",
@@ -99,11 +115,122 @@ impl Error {
         mut self,
         err: T,
     ) -> Self {
-        self.cause = Some(err.into());
+        let cause = err.into();
+        #[cfg(any(feature = "postgres", feature = "sqlite"))]
+        if self.kind == ErrorKind::Backend {
+            if let Some(sqlx_err) = cause.downcast_ref::<sqlx::Error>() {
+                if sqlx_error_is_busy(sqlx_err) {
+                    self.kind = ErrorKind::Busy;
+                } else if sqlx_error_is_disconnect(sqlx_err) {
+                    self.kind = ErrorKind::Connection;
+                } else if sqlx_error_is_read_only(sqlx_err) {
+                    self.kind = ErrorKind::Unsupported;
+                }
+            }
+        }
+        self.cause = Some(cause);
         self
     }
 }
 
+/// Determine whether a database error represents a lost or closed connection
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+fn sqlx_error_is_disconnect(err: &sqlx::Error) -> bool {
+    use std::io::ErrorKind as IoErrorKind;
+
+    match err {
+        sqlx::Error::PoolClosed => true,
+        sqlx::Error::Io(io_err) => matches!(
+            io_err.kind(),
+            IoErrorKind::ConnectionReset
+                | IoErrorKind::ConnectionAborted
+                | IoErrorKind::ConnectionRefused
+                | IoErrorKind::NotConnected
+                | IoErrorKind::BrokenPipe
+                | IoErrorKind::UnexpectedEof
+        ),
+        #[cfg(feature = "postgres")]
+        sqlx::Error::Database(db_err)
+            if db_err
+                .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+                .is_some() =>
+        {
+            // connection_exception, admin_shutdown, crash_shutdown, cannot_connect_now
+            db_err.code().is_some_and(|code| {
+                code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Determine whether a database error represents a transient lock conflict
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+fn sqlx_error_is_busy(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => {
+            #[cfg(feature = "postgres")]
+            if db_err
+                .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+                .is_some()
+            {
+                // serialization_failure, deadlock_detected, lock_not_available
+                return matches!(
+                    db_err.code().as_deref(),
+                    Some("40001") | Some("40P01") | Some("55P03")
+                );
+            }
+            #[cfg(feature = "sqlite")]
+            if db_err
+                .try_downcast_ref::<sqlx::sqlite::SqliteError>()
+                .is_some()
+            {
+                // the extended result code is reported, the primary result
+                // code is found in the lower 8 bits (SQLITE_BUSY, SQLITE_LOCKED)
+                return db_err
+                    .code()
+                    .and_then(|code| code.parse::<i32>().ok())
+                    .map(|code| matches!(code & 0xff, 5 | 6))
+                    .unwrap_or(false);
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Determine whether a database error represents a write to a read-only store
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+fn sqlx_error_is_read_only(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => {
+            #[cfg(feature = "postgres")]
+            if db_err
+                .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+                .is_some()
+            {
+                // read_only_sql_transaction
+                return db_err.code().as_deref() == Some("25006");
+            }
+            #[cfg(feature = "sqlite")]
+            if db_err
+                .try_downcast_ref::<sqlx::sqlite::SqliteError>()
+                .is_some()
+            {
+                // SQLITE_READONLY, with any extended result code
+                return db_err
+                    .code()
+                    .and_then(|code| code.parse::<i32>().ok())
+                    .map(|code| code & 0xff == 8)
+                    .unwrap_or(false);
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(msg) = self.message.as_ref() {
@@ -146,6 +273,10 @@ impl From<ErrorKind> for Error {
 // all sqlx errors manually, to ensure there is some context around the error
 #[cfg(any(feature = "postgres", feature = "sqlite"))]
 impl From<sqlx::Error> for Error {
+    /// Database errors indicating a busy or locked store are reported
+    /// as [`ErrorKind::Busy`], lost or closed connections as
+    /// [`ErrorKind::Connection`], writes to a read-only store as
+    /// [`ErrorKind::Unsupported`], and all others as [`ErrorKind::Backend`]
     fn from(err: sqlx::Error) -> Self {
         Error::from(ErrorKind::Backend).with_cause(err)
     }
@@ -163,6 +294,7 @@ impl From<CryptoError> for Error {
             | CryptoErrorKind::MissingSecretKey
             | CryptoErrorKind::Usage => ErrorKind::Input,
             CryptoErrorKind::Unsupported => ErrorKind::Unsupported,
+            _ => ErrorKind::Unexpected,
         };
         Error::from_msg(kind, err.message())
     }
@@ -188,3 +320,63 @@ macro_rules! err_map {
         |err| err_msg!($($params)*).with_cause(err)
     };
 }
+
+#[cfg(all(test, any(feature = "postgres", feature = "sqlite")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_timeout_busy() {
+        assert_eq!(
+            Error::from(sqlx::Error::PoolTimedOut).kind(),
+            ErrorKind::Busy
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_busy() {
+        use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection, Executor};
+
+        let path = std::env::temp_dir().join(format!("sqlite-busy-{}.db", uuid::Uuid::new_v4()));
+        let opts = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .busy_timeout(std::time::Duration::ZERO);
+        crate::future::block_on(async {
+            let mut conn = opts.connect().await.unwrap();
+            conn.execute("BEGIN IMMEDIATE").await.unwrap();
+            let mut other = opts.connect().await.unwrap();
+            let err = other.execute("BEGIN IMMEDIATE").await.unwrap_err();
+            let err = err_msg!(Backend, "Error starting transaction").with_cause(err);
+            assert_eq!(err.kind(), ErrorKind::Busy);
+            other.close().await.unwrap();
+            conn.close().await.unwrap();
+        });
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn connection_errors_not_busy() {
+        for err in [
+            sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+            sqlx::Error::WorkerCrashed,
+        ] {
+            assert_eq!(Error::from(err).kind(), ErrorKind::Backend);
+        }
+    }
+
+    #[test]
+    fn lost_connection() {
+        for err in [
+            sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+            sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe)),
+            sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)),
+            sqlx::Error::PoolClosed,
+        ] {
+            assert_eq!(Error::from(err).kind(), ErrorKind::Connection);
+        }
+        let err = err_msg!(Backend, "Error fetching row").with_cause(sqlx::Error::PoolClosed);
+        assert_eq!(err.kind(), ErrorKind::Connection);
+    }
+}
@@ -18,6 +18,35 @@ static RUNTIME: Lazy<ArcSwapOption<Runtime>> = Lazy::new(|| {
     )))
 });
 
+/// Run a future within a tracing span, recording the outcome on completion.
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument<'f, T: Send + 'f>(
+    span: tracing::Span,
+    fut: BoxFuture<'f, Result<T, crate::Error>>,
+    rows: fn(&T) -> Option<usize>,
+) -> BoxFuture<'f, Result<T, crate::Error>> {
+    use tracing::Instrument;
+
+    Box::pin(
+        async move {
+            let result = fut.await;
+            let span = tracing::Span::current();
+            match &result {
+                Ok(value) => {
+                    if let Some(count) = rows(value) {
+                        span.record("rows", count);
+                    }
+                }
+                Err(err) => {
+                    span.record("error", tracing::field::debug(err.kind()));
+                }
+            }
+            result
+        }
+        .instrument(span),
+    )
+}
+
 /// Block the current thread on an async task, when not running inside the scheduler.
 pub fn block_on<R>(f: impl Future<Output = R>) -> R {
     if let Some(rt) = RUNTIME.load().clone() {
@@ -27,6 +56,18 @@ pub fn block_on<R>(f: impl Future<Output = R>) -> R {
     }
 }
 
+/// Run a function within the context of the async runtime, without blocking on
+/// a future. This allows resources which spawn tasks when dropped to be released
+/// outside of `block_on`.
+pub fn enter<R>(f: impl FnOnce() -> R) -> R {
+    if let Some(rt) = RUNTIME.load().clone() {
+        let _guard = rt.enter();
+        f()
+    } else {
+        f()
+    }
+}
+
 /// Run a blocking task without interrupting the async scheduler.
 #[inline]
 pub async fn unblock<F, T>(f: F) -> T
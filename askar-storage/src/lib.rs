@@ -25,7 +25,7 @@ extern crate log;
 extern crate serde;
 
 pub mod backend;
-pub use self::backend::{Backend, BackendSession, ManageBackend};
+pub use self::backend::{Backend, BackendKind, BackendSession, ManageBackend, StoreValidation};
 
 #[cfg(feature = "any")]
 pub mod any;
@@ -51,7 +51,7 @@ mod protect;
 pub use protect::{
     generate_raw_store_key,
     kdf::{Argon2Level, KdfMethod},
-    PassKey, StoreKeyMethod,
+    PassKey, StoreKeyMethod, TagHasher,
 };
 
 mod wql;
@@ -60,3 +60,29 @@ macro_rules! map_err_log {
         }
     };
 }
+
+/// Wrap a backend future in a tracing span, recording the number of rows
+/// affected and the kind of any error. Entry names, values and tags must
+/// never be added as span fields.
+#[cfg(feature = "tracing")]
+macro_rules! traced {
+    ($fut:expr, $rows:expr, $name:literal $(, $($field:tt)+)?) => {
+        $crate::future::instrument(
+            tracing::debug_span!(
+                $name,
+                $($($field)+,)?
+                rows = tracing::field::Empty,
+                error = tracing::field::Empty
+            ),
+            $fut,
+            $rows,
+        )
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! traced {
+    ($fut:expr, $rows:expr, $name:literal $(, $($field:tt)+)?) => {
+        $fut
+    };
+}
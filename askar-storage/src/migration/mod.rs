@@ -262,8 +262,7 @@ impl IndySdkToAriesAskarMigration {
         };
 
         let profile_key = match profile_row {
-            Some(profile_row) => ciborium::from_reader(&profile_row[..])
-                .map_err(err_map!(Input, "Invalid cbor encoding for profile_key"))?,
+            Some(profile_row) => ProfileKey::from_slice(&profile_row)?,
             None => {
                 let pk = ProfileKey::new()?;
                 let enc_pk = key.master.wrap_data(pk.to_bytes()?)?;
@@ -116,10 +116,11 @@ impl Strategy {
             None => Default::default(),
         };
 
+        let name = key.encrypt_entry_name(&item.typ, item.name.into())?;
         let updated_indy_item = UpdatedIndyItem {
             id: item.id,
             category: key.encrypt_entry_category(item.typ.into())?,
-            name: key.encrypt_entry_name(item.name.into())?,
+            name,
             value,
             tags: key.encrypt_entry_tags(item.tags)?,
         };
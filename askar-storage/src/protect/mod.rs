@@ -12,7 +12,7 @@ mod pass_key;
 pub use self::pass_key::PassKey;
 
 mod profile_key;
-pub use self::profile_key::ProfileKey;
+pub use self::profile_key::{ProfileKey, TagHasher};
 
 mod store_key;
 pub use self::store_key::{generate_raw_store_key, StoreKey, StoreKeyMethod, StoreKeyReference};
@@ -76,7 +76,7 @@ pub(crate) trait EntryEncryptor {
     }
 
     fn encrypt_entry_category(&self, category: SecretBytes) -> Result<Vec<u8>, Error>;
-    fn encrypt_entry_name(&self, name: SecretBytes) -> Result<Vec<u8>, Error>;
+    fn encrypt_entry_name(&self, category: &[u8], name: SecretBytes) -> Result<Vec<u8>, Error>;
     fn encrypt_entry_value(
         &self,
         category: &[u8],
@@ -86,7 +86,7 @@ pub(crate) trait EntryEncryptor {
     fn encrypt_entry_tags(&self, tags: Vec<EntryTag>) -> Result<Vec<EncEntryTag>, Error>;
 
     fn decrypt_entry_category(&self, enc_category: Vec<u8>) -> Result<String, Error>;
-    fn decrypt_entry_name(&self, enc_name: Vec<u8>) -> Result<String, Error>;
+    fn decrypt_entry_name(&self, category: &[u8], enc_name: Vec<u8>) -> Result<String, Error>;
     fn decrypt_entry_value(
         &self,
         category: &[u8],
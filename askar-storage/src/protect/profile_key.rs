@@ -18,13 +18,22 @@ use crate::{
 
 pub type ProfileKey = ProfileKeyImpl<Chacha20Key<C20P>, HmacKey<Sha256, U32>>;
 
+/// The version header prepended to sealed values, also used as the AAD
+const SEALED_VERSION: u8 = 1;
+
+/// The label used to derive the encryption key for sealed values
+///
+/// The item HMAC key also derives the stored nonces of entry categories and
+/// names from their plaintext. The leading 0xFF byte never occurs in UTF-8
+/// text, so no category or name can reproduce the derivation of this key.
+const SEALED_KEY_LABEL: &[u8] = b"\xffaskar:sealed";
+
 /// A record combining the keys required to encrypt and decrypt storage entries
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(bound(
     deserialize = "Key: for<'a> Deserialize<'a>, HmacKey: for<'a> Deserialize<'a>",
     serialize = "Key: Serialize, HmacKey: Serialize"
 ))]
-#[serde(tag = "ver", rename = "1")]
 pub struct ProfileKeyImpl<Key, HmacKey> {
     #[serde(rename = "ick")]
     pub category_key: Key,
@@ -38,6 +47,9 @@ pub struct ProfileKeyImpl<Key, HmacKey> {
     pub tag_value_key: Key,
     #[serde(rename = "thk")]
     pub tags_hmac_key: HmacKey,
+    /// Categories whose entry names are stored as plaintext
+    #[serde(rename = "pnc", default, skip_serializing_if = "Vec::is_empty")]
+    pub plaintext_name_categories: Vec<String>,
 }
 
 impl<Key, HmacKey> ProfileKeyImpl<Key, HmacKey>
@@ -53,25 +65,62 @@ where
             tag_name_key: KeyGen::random()?,
             tag_value_key: KeyGen::random()?,
             tags_hmac_key: KeyGen::random()?,
+            plaintext_name_categories: Vec::new(),
         })
     }
 }
 
+impl<Key, HmacKey> ProfileKeyImpl<Key, HmacKey> {
+    /// Store the names of entries in the given categories as plaintext
+    pub fn with_plaintext_names(mut self, categories: Vec<String>) -> Self {
+        self.plaintext_name_categories = categories;
+        self
+    }
+
+    /// Check whether the names of entries in a category are stored as plaintext
+    pub fn has_plaintext_names(&self, category: &[u8]) -> bool {
+        self.plaintext_name_categories
+            .iter()
+            .any(|c| c.as_bytes() == category)
+    }
+}
+
+/// The serialized form of a profile key, tagged with its format version
+///
+/// Version 2 is written when the names of entries in any category are stored
+/// as plaintext. Earlier versions of this library only accept version 1, and
+/// so fail to load such a profile key instead of ignoring the categories.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "ver")]
+enum VersionedProfileKey<K> {
+    #[serde(rename = "1")]
+    V1(K),
+    #[serde(rename = "2")]
+    V2(K),
+}
+
 impl<Key, HmacKey> ProfileKeyImpl<Key, HmacKey>
 where
     Key: Serialize + for<'de> Deserialize<'de>,
     HmacKey: Serialize + for<'de> Deserialize<'de>,
 {
     pub fn to_bytes(&self) -> Result<SecretBytes, Error> {
+        let versioned = if self.plaintext_name_categories.is_empty() {
+            VersionedProfileKey::V1(self)
+        } else {
+            VersionedProfileKey::V2(self)
+        };
         let mut bytes = Vec::new();
-        ciborium::into_writer(self, &mut bytes)
+        ciborium::into_writer(&versioned, &mut bytes)
             .map_err(err_map!(Unexpected, "Error serializing profile key"))?;
 
         Ok(SecretBytes::from(bytes))
     }
 
     pub fn from_slice(input: &[u8]) -> Result<Self, Error> {
-        ciborium::from_reader(input).map_err(err_map!(Unsupported, "Invalid profile key"))
+        match ciborium::from_reader(input).map_err(err_map!(Unsupported, "Invalid profile key"))? {
+            VersionedProfileKey::V1(key) | VersionedProfileKey::V2(key) => Ok(key),
+        }
     }
 }
 
@@ -129,6 +178,42 @@ where
         ))?)
     }
 
+    #[inline]
+    fn derive_sealing_key(&self) -> Result<Key, Error> {
+        Ok(Key::from_key_derivation(
+            self.item_hmac_key.hmac_deriver(&[SEALED_KEY_LABEL]),
+        )?)
+    }
+
+    /// Encrypt an arbitrary value which is not associated with a stored entry
+    pub fn seal_value(&self, value: SecretBytes) -> Result<Vec<u8>, Error> {
+        let key = self.derive_sealing_key()?;
+        let header = [SEALED_VERSION];
+        let nonce = ArrayKey::<Key::NonceSize>::random();
+        let mut buffer = value;
+        key.encrypt_in_place(&mut buffer, nonce.as_ref(), &header)?;
+        buffer.buffer_insert(0, nonce.as_ref())?;
+        buffer.buffer_insert(0, &header)?;
+        Ok(buffer.into_vec())
+    }
+
+    /// Decrypt a value produced by `seal_value`
+    pub fn unseal_value(&self, sealed: Vec<u8>) -> Result<SecretBytes, Error> {
+        let prefix_len = 1 + Key::NonceSize::USIZE;
+        if sealed.len() < prefix_len + Key::TagSize::USIZE {
+            return Err(err_msg!(Encryption, "Invalid sealed value"));
+        }
+        if sealed[0] != SEALED_VERSION {
+            return Err(err_msg!(Unsupported, "Unsupported sealed value version"));
+        }
+        let key = self.derive_sealing_key()?;
+        let mut buffer = SecretBytes::from(sealed);
+        let nonce = ArrayKey::<Key::NonceSize>::from_slice(&buffer.as_ref()[1..prefix_len]);
+        buffer.buffer_remove(0..prefix_len)?;
+        key.decrypt_in_place(&mut buffer, nonce.as_ref(), &[SEALED_VERSION])?;
+        Ok(buffer)
+    }
+
     pub fn encrypt_tag_name(&self, name: SecretBytes) -> Result<Vec<u8>, Error> {
         Self::encrypt_searchable(name, &self.tag_name_key, &self.tags_hmac_key)
     }
@@ -146,6 +231,48 @@ where
     }
 }
 
+impl ProfileKey {
+    /// Create a hasher for the deterministic tag name and value tokens
+    pub fn tag_hasher(&self) -> TagHasher {
+        TagHasher(self.tags_hmac_key.clone())
+    }
+}
+
+/// Computes the deterministic tokens used to look up encrypted tags
+///
+/// Each encrypted tag name and value is stored with a prefix derived from a
+/// keyed hash of its plaintext, which is used for equality lookups. This type
+/// reproduces those tokens, allowing external search indices to be built over
+/// tags without access to the keys used to encrypt entries and tag contents.
+///
+/// Anyone holding a `TagHasher` can test guesses of tag names and values, and
+/// can tell which entries share a tag value. Low-entropy tag values should be
+/// considered disclosed to any system the hasher is shared with.
+#[derive(Clone, Debug)]
+pub struct TagHasher(HmacKey<Sha256, U32>);
+
+impl TagHasher {
+    /// Compute the token for an encrypted tag name
+    pub fn hash_tag_name(&self, name: &str) -> Result<Vec<u8>, Error> {
+        self.hash(name.as_bytes())
+    }
+
+    /// Compute the token for an encrypted tag value
+    ///
+    /// Names and values are hashed with the same key, so tokens for a name and
+    /// value should be combined or kept in separate indices.
+    pub fn hash_tag_value(&self, value: &str) -> Result<Vec<u8>, Error> {
+        self.hash(value.as_bytes())
+    }
+
+    fn hash(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let token = ArrayKey::<<Chacha20Key<C20P> as KeyAeadMeta>::NonceSize>::from_key_derivation(
+            self.0.hmac_deriver(&[input]),
+        )?;
+        Ok(token.as_ref().to_vec())
+    }
+}
+
 impl<Key: PartialEq, HmacKey: PartialEq> PartialEq for ProfileKeyImpl<Key, HmacKey> {
     fn eq(&self, other: &Self) -> bool {
         self.category_key == other.category_key
@@ -154,6 +281,7 @@ impl<Key: PartialEq, HmacKey: PartialEq> PartialEq for ProfileKeyImpl<Key, HmacK
             && self.tag_name_key == other.tag_name_key
             && self.tag_value_key == other.tag_value_key
             && self.tags_hmac_key == other.tags_hmac_key
+            && self.plaintext_name_categories == other.plaintext_name_categories
     }
 }
 impl<Key: PartialEq, HmacKey: PartialEq> Eq for ProfileKeyImpl<Key, HmacKey> {}
@@ -173,8 +301,12 @@ where
         Self::encrypt_searchable(category, &self.category_key, &self.item_hmac_key)
     }
 
-    fn encrypt_entry_name(&self, name: SecretBytes) -> Result<Vec<u8>, Error> {
-        Self::encrypt_searchable(name, &self.name_key, &self.item_hmac_key)
+    fn encrypt_entry_name(&self, category: &[u8], name: SecretBytes) -> Result<Vec<u8>, Error> {
+        if self.has_plaintext_names(category) {
+            Ok(name.into_vec())
+        } else {
+            Self::encrypt_searchable(name, &self.name_key, &self.item_hmac_key)
+        }
     }
 
     fn encrypt_entry_value(
@@ -191,8 +323,12 @@ where
         decode_utf8(Self::decrypt(enc_category, &self.category_key)?.into_vec())
     }
 
-    fn decrypt_entry_name(&self, enc_name: Vec<u8>) -> Result<String, Error> {
-        decode_utf8(Self::decrypt(enc_name, &self.name_key)?.into_vec())
+    fn decrypt_entry_name(&self, category: &[u8], enc_name: Vec<u8>) -> Result<String, Error> {
+        if self.has_plaintext_names(category) {
+            decode_utf8(enc_name)
+        } else {
+            decode_utf8(Self::decrypt(enc_name, &self.name_key)?.into_vec())
+        }
     }
 
     fn decrypt_entry_value(
@@ -230,7 +366,7 @@ where
     }
 
     fn decrypt_entry_tags(&self, enc_tags: Vec<EncEntryTag>) -> Result<Vec<EntryTag>, Error> {
-        enc_tags.into_iter().try_fold(vec![], |mut acc, tag| {
+        let mut tags = enc_tags.into_iter().try_fold(vec![], |mut acc, tag| {
             let name = decode_utf8(self.decrypt_tag_name(tag.name)?.into_vec())?;
             acc.push(if tag.plaintext {
                 let value = decode_utf8(tag.value)?;
@@ -239,8 +375,11 @@ where
                 let value = decode_utf8(self.decrypt_tag_value(tag.value)?.into_vec())?;
                 EntryTag::Encrypted(name, value)
             });
-            Result::Ok(acc)
-        })
+            Result::<_, Error>::Ok(acc)
+        })?;
+        // the row order of the tags is backend-dependent
+        tags.sort();
+        Ok(tags)
     }
 }
 
@@ -252,7 +391,10 @@ fn decode_utf8(value: Vec<u8>) -> Result<String, Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entry::{Entry, EntryKind};
+    use crate::{
+        crypto::repr::ToSecretBytes,
+        entry::{Entry, EntryKind},
+    };
 
     #[test]
     fn encrypt_entry_round_trip() {
@@ -271,7 +413,10 @@ mod tests {
             .encrypt_entry_category(test_record.category.clone().into())
             .unwrap();
         let enc_name = key
-            .encrypt_entry_name(test_record.name.clone().into())
+            .encrypt_entry_name(
+                test_record.category.as_bytes(),
+                test_record.name.clone().into(),
+            )
             .unwrap();
         let enc_value = key
             .encrypt_entry_value(
@@ -288,7 +433,8 @@ mod tests {
         let cmp_record = Entry::new(
             EntryKind::Item,
             key.decrypt_entry_category(enc_category).unwrap(),
-            key.decrypt_entry_name(enc_name).unwrap(),
+            key.decrypt_entry_name(test_record.category.as_bytes(), enc_name)
+                .unwrap(),
             key.decrypt_entry_value(
                 test_record.category.as_bytes(),
                 test_record.name.as_bytes(),
@@ -300,6 +446,44 @@ mod tests {
         assert_eq!(test_record, cmp_record);
     }
 
+    #[test]
+    fn seal_value_round_trip() {
+        let key = ProfileKey::new().unwrap();
+        let input = SecretBytes::from(&b"hello"[..]);
+        let sealed = key.seal_value(input.clone()).unwrap();
+        assert_eq!(sealed[0], SEALED_VERSION);
+        assert_eq!(key.unseal_value(sealed.clone()).unwrap(), input);
+
+        let mut modified = sealed.clone();
+        modified[0] = 2;
+        assert!(key.unseal_value(modified).is_err());
+        let other = ProfileKey::new().unwrap();
+        assert!(other.unseal_value(sealed).is_err());
+    }
+
+    #[test]
+    fn sealing_key_not_exposed() {
+        let key = ProfileKey::new().unwrap();
+        let sealing_key = key.derive_sealing_key().unwrap().to_secret_bytes().unwrap();
+        let label = String::from_utf8_lossy(SEALED_KEY_LABEL).into_owned();
+        for input in ["askar:sealed", label.as_str()] {
+            let enc_category = key
+                .encrypt_entry_category(ProfileKey::prepare_input(input.as_bytes()))
+                .unwrap();
+            let enc_name = key
+                .encrypt_entry_name(b"category", ProfileKey::prepare_input(input.as_bytes()))
+                .unwrap();
+            for enc in [enc_category, enc_name] {
+                // the nonce is stored in cleartext before the ciphertext
+                let nonce = &enc[..<Chacha20Key<C20P> as KeyAeadMeta>::NonceSize::USIZE];
+                assert!(!sealing_key
+                    .as_ref()
+                    .windows(nonce.len())
+                    .any(|w| w == nonce));
+            }
+        }
+    }
+
     #[test]
     fn check_encrypt_searchable() {
         let input = SecretBytes::from(&b"hello"[..]);
@@ -316,10 +500,72 @@ mod tests {
 
     #[test]
     fn serialize_round_trip() {
+        fn version(cbor: &[u8]) -> String {
+            let value: ciborium::Value = ciborium::from_reader(cbor).unwrap();
+            value
+                .as_map()
+                .unwrap()
+                .iter()
+                .find(|(k, _)| k.as_text() == Some("ver"))
+                .and_then(|(_, v)| v.as_text())
+                .unwrap()
+                .to_string()
+        }
+
         let key = ProfileKey::new().unwrap();
-        let mut key_cbor = Vec::new();
-        ciborium::into_writer(&key, &mut key_cbor).unwrap();
-        let key_cmp = ciborium::from_reader(&key_cbor[..]).unwrap();
+        let key_cbor = key.to_bytes().unwrap();
+        let key_cmp = ProfileKey::from_slice(&key_cbor).unwrap();
         assert_eq!(key, key_cmp);
+        assert_eq!(version(&key_cbor), "1");
+
+        // readers supporting only version 1 must reject keys with plaintext names
+        let plain_key = key.with_plaintext_names(vec!["docs".to_string()]);
+        let plain_cbor = plain_key.to_bytes().unwrap();
+        assert_eq!(version(&plain_cbor), "2");
+        let plain_cmp = ProfileKey::from_slice(&plain_cbor).unwrap();
+        assert_eq!(plain_key, plain_cmp);
+        assert!(plain_cmp.has_plaintext_names(b"docs"));
+    }
+
+    #[test]
+    fn plaintext_entry_names() {
+        let key = ProfileKey::new()
+            .unwrap()
+            .with_plaintext_names(vec!["docs".to_string()]);
+        let enc_name = key
+            .encrypt_entry_name(b"docs", SecretBytes::from_slice(b"doc:1"))
+            .unwrap();
+        assert_eq!(enc_name, b"doc:1");
+        assert_eq!(key.decrypt_entry_name(b"docs", enc_name).unwrap(), "doc:1");
+
+        let enc_name = key
+            .encrypt_entry_name(b"other", SecretBytes::from_slice(b"doc:1"))
+            .unwrap();
+        assert_ne!(enc_name, b"doc:1");
+        assert_eq!(key.decrypt_entry_name(b"other", enc_name).unwrap(), "doc:1");
+    }
+
+    #[test]
+    fn tag_hasher_matches_encrypted_tags() {
+        let key = ProfileKey::new().unwrap();
+        let hasher = key.tag_hasher();
+        let enc_name = key
+            .encrypt_tag_name(SecretBytes::from_slice(b"name"))
+            .unwrap();
+        let enc_value = key
+            .encrypt_tag_value(SecretBytes::from_slice(b"value"))
+            .unwrap();
+        let name_token = hasher.hash_tag_name("name").unwrap();
+        let value_token = hasher.hash_tag_value("value").unwrap();
+        assert_eq!(name_token, enc_name[..name_token.len()]);
+        assert_eq!(value_token, enc_value[..value_token.len()]);
+        assert_ne!(
+            name_token,
+            ProfileKey::new()
+                .unwrap()
+                .tag_hasher()
+                .hash_tag_name("name")
+                .unwrap()
+        );
     }
 }
@@ -28,6 +28,10 @@ pub enum AbstractQuery<K, V> {
     /// Match one of multiple field values in a set
     In(K, Vec<V>),
     /// Match any non-null field value of the given field names
+    ///
+    /// Serialized as `$exist`, the canonical WQL operator, while `$exists` is also
+    /// accepted as an alias when parsing. All of the named fields must be present;
+    /// when negated, the query matches records lacking every one of them.
     Exist(Vec<K>),
 }
 
@@ -315,8 +319,8 @@ mod serde_support {
                 Ok(Some(Query::Not(Box::new(operator))))
             }
             ("$not", _) => Err("$not must be JSON object"),
-            ("$exist", JsonValue::String(key)) => Ok(Some(Query::Exist(vec![key]))),
-            ("$exist", JsonValue::Array(keys)) => {
+            ("$exist" | "$exists", JsonValue::String(key)) => Ok(Some(Query::Exist(vec![key]))),
+            ("$exist" | "$exists", JsonValue::Array(keys)) => {
                 if keys.is_empty() {
                     Ok(None)
                 } else {
@@ -331,7 +335,9 @@ mod serde_support {
                     Ok(Some(Query::Exist(ks)))
                 }
             }
-            ("$exist", _) => Err("$exist must be used with a string or array of strings"),
+            ("$exist" | "$exists", _) => {
+                Err("$exist must be used with a string or array of strings")
+            }
             (_, JsonValue::String(value)) => Ok(Some(Query::Eq(key, value))),
             (_, JsonValue::Object(map)) => {
                 if map.len() == 1 {
@@ -611,6 +617,35 @@ mod tests {
         assert_eq!(query, expected);
     }
 
+    #[test]
+    fn test_exists_alias_parse() {
+        let name1 = _random_string(10);
+        let name2 = _random_string(10);
+
+        let json = format!(r#"{{"$exists":["{}","{}"]}}"#, name1, name2);
+
+        let query: Query = ::serde_json::from_str(&json).unwrap();
+
+        let expected = Query::Exist(vec![name1, name2]);
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn test_exists_alias_to_string() {
+        let name1 = _random_string(10);
+
+        let json = format!(r#"{{"$exists":"{}"}}"#, name1);
+
+        let query: Query = ::serde_json::from_str(&json).unwrap();
+
+        let json = ::serde_json::to_string(&query).unwrap();
+
+        let expected = format!(r#"{{"$exist":["{}"]}}"#, name1);
+
+        assert_eq!(json, expected);
+    }
+
     #[test]
     fn test_and_exist() {
         let name1 = _random_string(10);
@@ -77,6 +77,9 @@ where
             self.arguments.push(v);
         }
 
+        // negation is applied to the membership test rather than the comparison, so
+        // that entries lacking the tag entirely are included. item_id is never NULL,
+        // so NOT IN behaves as NOT EXISTS here
         let query = format!(
             "i.id {} (SELECT item_id FROM items_tags WHERE name = ${} AND value {} ${}{} AND plaintext = {})",
             if negate { "NOT IN" } else { "IN" },
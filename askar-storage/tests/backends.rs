@@ -18,11 +18,36 @@ macro_rules! backend_tests {
             $run(super::utils::db_create_remove_profile)
         }
 
+        #[test]
+        fn remove_profile_entries() {
+            $run(super::utils::db_remove_profile_entries)
+        }
+
         #[test]
         fn list_profiles() {
             $run(super::utils::db_list_profiles)
         }
 
+        #[test]
+        fn profile_stats() {
+            $run(super::utils::db_profile_stats)
+        }
+
+        #[test]
+        fn seal_unseal() {
+            $run(super::utils::db_seal_unseal)
+        }
+
+        #[test]
+        fn export_tag_hasher() {
+            $run(super::utils::db_export_tag_hasher)
+        }
+
+        #[test]
+        fn schema_version() {
+            $run(super::utils::db_schema_version)
+        }
+
         #[test]
         fn get_set_default_profile() {
             $run(super::utils::db_get_set_default_profile)
@@ -38,6 +63,31 @@ macro_rules! backend_tests {
             $run(super::utils::db_insert_fetch)
         }
 
+        #[test]
+        fn fetch_tag_order() {
+            $run(super::utils::db_fetch_tag_order)
+        }
+
+        #[test]
+        fn empty_tag_values() {
+            $run(super::utils::db_empty_tag_values)
+        }
+
+        #[test]
+        fn negate_absent_tags() {
+            $run(super::utils::db_negate_absent_tags)
+        }
+
+        #[test]
+        fn create_tag_index() {
+            $run(super::utils::db_create_tag_index)
+        }
+
+        #[test]
+        fn fetch_tag_names() {
+            $run(super::utils::db_fetch_tag_names)
+        }
+
         #[test]
         fn insert_duplicate() {
             $run(super::utils::db_insert_duplicate)
@@ -58,6 +108,11 @@ macro_rules! backend_tests {
             $run(super::utils::db_replace_fetch)
         }
 
+        #[test]
+        fn upsert() {
+            $run(super::utils::db_upsert)
+        }
+
         #[test]
         fn replace_missing() {
             $run(super::utils::db_replace_missing)
@@ -78,6 +133,16 @@ macro_rules! backend_tests {
             $run(super::utils::db_scan)
         }
 
+        #[test]
+        fn scan_no_tags() {
+            $run(super::utils::db_scan_no_tags)
+        }
+
+        #[test]
+        fn scan_name_prefix() {
+            $run(super::utils::db_scan_name_prefix)
+        }
+
         #[test]
         fn remove_all() {
             $run(super::utils::db_remove_all)
@@ -128,9 +193,16 @@ fn log_init() {
 mod sqlite {
     use askar_storage::any::{into_any_backend, AnyBackend};
     use askar_storage::backend::copy_store;
-    use askar_storage::backend::sqlite::SqliteStoreOptions;
+    use askar_storage::backend::sqlite::{
+        SqliteJournalMode, SqliteStoreOptions, SqliteSynchronous,
+    };
     use askar_storage::future::block_on;
-    use askar_storage::{generate_raw_store_key, Backend, ManageBackend, StoreKeyMethod};
+    use askar_storage::{
+        entry::{EntryKind, EntryOperation, TagMode},
+        generate_raw_store_key, Backend, BackendKind, BackendSession, ErrorKind, ManageBackend,
+        StoreKeyMethod, StoreValidation,
+    };
+    use sqlx::Connection;
     use std::{future::Future, path::Path};
 
     use super::*;
@@ -224,6 +296,140 @@ mod sqlite {
         })
     }
 
+    #[test]
+    fn journal_mode_options() {
+        log_init();
+        let fname = format!("sqlite-journal-{}.db", uuid::Uuid::new_v4());
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+
+        block_on(async move {
+            let err = SqliteStoreOptions::in_memory()
+                .journal_mode(SqliteJournalMode::Wal)
+                .provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect_err("Expected error for WAL journal mode in memory");
+            assert_eq!(err.kind(), ErrorKind::Input);
+
+            let store = SqliteStoreOptions::new(format!("{fname}?journal_mode=delete").as_str())
+                .expect("Error initializing sqlite store options")
+                .synchronous(SqliteSynchronous::Full)
+                .provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect("Error provisioning sqlite store");
+            store.close().await.expect("Error closing sqlite store");
+
+            SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .remove_backend()
+                .await
+                .expect("Error removing sqlite store");
+        })
+    }
+
+    #[test]
+    fn open_read_only() {
+        log_init();
+        let fname = format!("sqlite-read-only-{}.db", uuid::Uuid::new_v4());
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+
+        block_on(async move {
+            // a read-only connection cannot clean up the write-ahead log on close
+            let store = SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .journal_mode(SqliteJournalMode::Delete)
+                .provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect("Error provisioning sqlite store");
+            let mut conn = store.session(None, false).expect("Error with session");
+            conn.update(
+                EntryKind::Item,
+                EntryOperation::Insert,
+                "category",
+                "name",
+                Some(b"value"),
+                None,
+                None,
+            )
+            .await
+            .expect("Error inserting test row");
+            conn.close(true).await.expect("Error with session");
+            store.close().await.expect(ERR_CLOSE);
+
+            let store = SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .read_only(true)
+                .open_backend(Some(StoreKeyMethod::RawKey), key.as_ref(), None)
+                .await
+                .expect("Error opening read-only sqlite store");
+            let mut conn = store.session(None, false).expect("Error with session");
+            conn.fetch(EntryKind::Item, "category", "name", TagMode::All, false)
+                .await
+                .expect("Error fetching test row")
+                .expect("Expected row");
+            let err = conn
+                .update(
+                    EntryKind::Item,
+                    EntryOperation::Remove,
+                    "category",
+                    "name",
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .expect_err("Expected error removing from read-only store");
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+            conn.close(false).await.expect("Error with session");
+            store.close().await.expect(ERR_CLOSE);
+
+            SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .remove_backend()
+                .await
+                .expect("Error removing sqlite store");
+        })
+    }
+
+    #[test]
+    fn open_newer_schema() {
+        log_init();
+        let fname = format!("sqlite-schema-{}.db", uuid::Uuid::new_v4());
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+
+        block_on(async move {
+            let store = SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .journal_mode(SqliteJournalMode::Delete)
+                .provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect("Error provisioning sqlite store");
+            store.close().await.expect(ERR_CLOSE);
+
+            let mut conn = sqlx::SqliteConnection::connect(&format!("sqlite://{fname}"))
+                .await
+                .expect("Error connecting to database");
+            sqlx::query("UPDATE config SET value='99' WHERE name='version'")
+                .execute(&mut conn)
+                .await
+                .expect("Error updating store version");
+            conn.close().await.expect(ERR_CLOSE);
+
+            let err = SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .journal_mode(SqliteJournalMode::Delete)
+                .open_backend(Some(StoreKeyMethod::RawKey), key.as_ref(), None)
+                .await
+                .expect_err("Expected error opening store with a newer schema");
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+            SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .remove_backend()
+                .await
+                .expect("Error removing sqlite store");
+        })
+    }
+
     #[test]
     fn copy_db() {
         log_init();
@@ -277,6 +483,117 @@ mod sqlite {
         })
     }
 
+    #[test]
+    fn open_or_provision_db() {
+        log_init();
+        let fname = format!("sqlite-test-{}.db", uuid::Uuid::new_v4());
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+
+        block_on(async move {
+            // concurrent calls must provision the store exactly once
+            let tasks = (0..4)
+                .map(|_| {
+                    let fname = fname.clone();
+                    let key = key.clone();
+                    tokio::spawn(async move {
+                        SqliteStoreOptions::new(fname.as_str())
+                            .expect("Error initializing sqlite store options")
+                            .open_or_provision_backend(StoreKeyMethod::RawKey, key, None, false)
+                            .await
+                            .expect("Error opening or provisioning sqlite store")
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut stores = Vec::new();
+            for task in tasks {
+                stores.push(task.await.expect("Error joining task"));
+            }
+            assert_eq!(stores.iter().filter(|(_, created)| *created).count(), 1);
+
+            let (store, created) = SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .open_or_provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect("Error opening sqlite store");
+            assert!(!created);
+            assert_eq!(store.get_active_profile(), stores[0].0.get_active_profile());
+            stores.push((store, created));
+            for (store, _) in stores {
+                store.close().await.expect("Error closing sqlite store");
+            }
+
+            SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .remove_backend()
+                .await
+                .expect("Error removing sqlite store");
+        });
+    }
+
+    #[test]
+    fn validate_uri_db() {
+        log_init();
+        let fname = format!("sqlite-test-{}.db", uuid::Uuid::new_v4());
+        let db_url = format!("sqlite://{fname}");
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+
+        block_on(async move {
+            let check = db_url
+                .as_str()
+                .validate_backend()
+                .await
+                .expect("Error validating sqlite store");
+            assert_eq!(
+                check,
+                StoreValidation {
+                    kind: BackendKind::Sqlite,
+                    create_database: true,
+                    store_exists: false,
+                }
+            );
+            // validation must not create the database
+            assert!(!Path::new(&fname).exists());
+
+            let store = db_url
+                .as_str()
+                .provision_backend(StoreKeyMethod::RawKey, key.as_ref(), None, false)
+                .await
+                .expect("Error provisioning sqlite store");
+            let check = db_url
+                .as_str()
+                .validate_backend()
+                .await
+                .expect("Error validating sqlite store");
+            assert!(!check.create_database);
+            assert!(check.store_exists);
+            store.close().await.expect("Error closing sqlite store");
+
+            let err = format!("sqlite://missing-{}/test.db", uuid::Uuid::new_v4())
+                .as_str()
+                .validate_backend()
+                .await
+                .expect_err("Expected error for missing directory");
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+            let err = format!("{db_url}?read_only=true")
+                .as_str()
+                .validate_backend()
+                .await
+                .expect_err("Expected error for read-only store");
+            assert_eq!(err.kind(), ErrorKind::Input);
+            let err = "unknown://test"
+                .validate_backend()
+                .await
+                .expect_err("Expected error for unsupported backend");
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+            SqliteStoreOptions::new(fname.as_str())
+                .expect("Error initializing sqlite store options")
+                .remove_backend()
+                .await
+                .expect("Error removing sqlite store");
+        });
+    }
+
     #[test]
     fn txn_contention_file() {
         log_init();
@@ -323,6 +640,81 @@ mod sqlite {
 
     backend_tests!(with_sqlite_in_memory);
 
+    #[test]
+    fn max_value_size() {
+        log_init();
+        let key = generate_raw_store_key(None).expect("Error creating raw key");
+        block_on(async move {
+            let db = SqliteStoreOptions::in_memory()
+                .max_value_size(16)
+                .provision(StoreKeyMethod::RawKey, key, None, false)
+                .await
+                .expect("Error provisioning sqlite store");
+            let mut conn = db.session(None, false).expect("Error starting session");
+            conn.update(
+                EntryKind::Item,
+                EntryOperation::Insert,
+                "category",
+                "name",
+                Some(&[0u8; 16]),
+                None,
+                None,
+            )
+            .await
+            .expect("Error inserting test row");
+            for op in [EntryOperation::Insert, EntryOperation::Replace] {
+                let err = conn
+                    .update(
+                        EntryKind::Item,
+                        op,
+                        "category",
+                        "name",
+                        Some(&[0u8; 17]),
+                        None,
+                        None,
+                    )
+                    .await
+                    .expect_err("Expected error for oversized value");
+                assert_eq!(err.kind(), ErrorKind::Input);
+            }
+            conn.close(false).await.expect(ERR_CLOSE);
+            db.close().await.expect(ERR_CLOSE);
+        })
+    }
+
+    #[test]
+    fn backend_kind() {
+        with_sqlite_in_memory(|db| async move {
+            assert_eq!(db.kind(), BackendKind::Sqlite);
+        })
+    }
+
+    #[test]
+    fn count_estimate_exact() {
+        with_sqlite_in_memory(|db| async move {
+            let mut conn = db.session(None, false).expect("Error starting session");
+            for name in ["a", "b"] {
+                conn.update(
+                    EntryKind::Item,
+                    EntryOperation::Insert,
+                    "category",
+                    name,
+                    Some(b"value"),
+                    None,
+                    None,
+                )
+                .await
+                .expect("Error inserting test row");
+            }
+            let estimate = conn
+                .count_estimate(Some(EntryKind::Item), Some("category"), None)
+                .await
+                .expect("Error estimating count");
+            assert_eq!(estimate, 2);
+            conn.close(false).await.expect(ERR_CLOSE);
+        })
+    }
+
     #[test]
     fn provision_from_str() {
         let key = generate_raw_store_key(None).expect("Error creating raw key");
@@ -1,12 +1,14 @@
 use askar_storage::{
     any::AnyBackend,
-    entry::{Entry, EntryKind, EntryOperation, EntryTag, TagFilter},
-    Backend, BackendSession, ErrorKind,
+    backend::{ProfileStats, SCHEMA_VERSION},
+    entry::{Entry, EntryKind, EntryOperation, EntryTag, TagFilter, TagMode, TagSet},
+    Backend, BackendSession, Error, ErrorKind,
 };
 
 use tokio::task::spawn;
 
 const ERR_PROFILE: &str = "Error creating profile";
+const ERR_PROFILE_STATS: &str = "Error fetching profile stats";
 const ERR_SESSION: &str = "Error starting session";
 const ERR_TRANSACTION: &str = "Error starting transaction";
 const ERR_COMMIT: &str = "Error committing transaction";
@@ -41,10 +43,46 @@ pub async fn db_create_remove_profile(db: AnyBackend) {
         .expect("Error removing profile"));
 }
 
+pub async fn db_remove_profile_entries(db: AnyBackend) {
+    let profile = db.create_profile(None).await.expect(ERR_PROFILE);
+    let mut conn = db.session(Some(profile.clone()), false).expect(ERR_SESSION);
+    conn.update(
+        EntryKind::Item,
+        EntryOperation::Insert,
+        "category",
+        "name",
+        Some(b"value"),
+        None,
+        None,
+    )
+    .await
+    .expect(ERR_INSERT);
+    drop(conn);
+
+    assert!(db
+        .remove_profile(profile.clone())
+        .await
+        .expect("Error removing profile"));
+    db.create_profile(Some(profile.clone()))
+        .await
+        .expect(ERR_PROFILE);
+    let stats = db
+        .profile_stats(Some(profile))
+        .await
+        .expect(ERR_PROFILE_STATS);
+    assert_eq!(stats, ProfileStats::default());
+
+    let err = db
+        .remove_profile(db.get_active_profile())
+        .await
+        .expect_err(ERR_REQ_ERR);
+    assert_eq!(err.kind(), ErrorKind::Input);
+}
+
 pub async fn db_fetch_fail(db: AnyBackend) {
     let mut conn = db.session(None, false).expect(ERR_SESSION);
     let result = conn
-        .fetch(EntryKind::Item, "cat", "name", false)
+        .fetch(EntryKind::Item, "cat", "name", TagMode::All, false)
         .await
         .expect(ERR_FETCH);
     assert!(result.is_none());
@@ -77,7 +115,13 @@ pub async fn db_insert_fetch(db: AnyBackend) {
     .expect(ERR_INSERT);
 
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect(ERR_FETCH)
         .expect(ERR_REQ_ROW);
@@ -88,6 +132,7 @@ pub async fn db_insert_fetch(db: AnyBackend) {
             Some(EntryKind::Item),
             Some(&test_row.category),
             None,
+            TagMode::All,
             None,
             None,
             false,
@@ -99,6 +144,129 @@ pub async fn db_insert_fetch(db: AnyBackend) {
     assert_eq!(rows[0], test_row);
 }
 
+pub async fn db_fetch_tag_order(db: AnyBackend) {
+    let tags = vec![
+        EntryTag::Plaintext("t3".to_string(), "v3".to_string()),
+        EntryTag::Encrypted("t2".to_string(), "v2".to_string()),
+        EntryTag::Plaintext("t1".to_string(), "v1".to_string()),
+        EntryTag::Encrypted("t1".to_string(), "v1".to_string()),
+    ];
+    let expect_tags = TagSet::from(tags.clone()).into_vec();
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    conn.update(
+        EntryKind::Item,
+        EntryOperation::Insert,
+        "category",
+        "name",
+        Some(b"value"),
+        Some(tags.as_slice()),
+        None,
+    )
+    .await
+    .expect(ERR_INSERT);
+
+    let row = conn
+        .fetch(EntryKind::Item, "category", "name", TagMode::All, false)
+        .await
+        .expect(ERR_FETCH)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(row.tags, expect_tags);
+    drop(conn);
+
+    let mut scan = db
+        .scan(
+            None,
+            Some(EntryKind::Item),
+            Some("category".to_string()),
+            None,
+            None,
+            TagMode::All,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await
+        .expect(ERR_SCAN);
+    let rows = scan
+        .fetch_next()
+        .await
+        .expect(ERR_SCAN_NEXT)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].tags, expect_tags);
+}
+
+pub async fn db_fetch_tag_names(db: AnyBackend) {
+    let tags = vec![
+        EntryTag::Encrypted("t1".to_string(), "v1".to_string()),
+        EntryTag::Encrypted("t3".to_string(), "v3".to_string()),
+        EntryTag::Plaintext("t2".to_string(), "v2".to_string()),
+    ];
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    conn.update(
+        EntryKind::Item,
+        EntryOperation::Insert,
+        "category",
+        "name",
+        Some(b"value"),
+        Some(tags.as_slice()),
+        None,
+    )
+    .await
+    .expect(ERR_INSERT);
+
+    let row = conn
+        .fetch(
+            EntryKind::Item,
+            "category",
+            "name",
+            TagMode::names(["~t2", "t3"]),
+            false,
+        )
+        .await
+        .expect(ERR_FETCH)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(row.tags, &tags[1..]);
+
+    let rows = conn
+        .fetch_all(
+            Some(EntryKind::Item),
+            Some("category"),
+            Some(TagFilter::is_eq("t3", "v3")),
+            TagMode::names(["t1"]),
+            None,
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect(ERR_FETCH_ALL);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].tags, &tags[..1]);
+    assert_eq!(rows[0].value, &b"value"[..]);
+
+    let rows = conn
+        .fetch_all(
+            Some(EntryKind::Item),
+            Some("category"),
+            None,
+            TagMode::names(Vec::<String>::new()),
+            None,
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect(ERR_FETCH_ALL);
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].tags.is_empty());
+}
+
 pub async fn db_insert_duplicate(db: AnyBackend) {
     let test_row = Entry::new(EntryKind::Item, "category", "name", "value", Vec::new());
 
@@ -215,6 +383,7 @@ pub async fn db_replace_fetch(db: AnyBackend) {
             EntryKind::Item,
             &replace_row.category,
             &replace_row.name,
+            TagMode::All,
             false,
         )
         .await
@@ -223,6 +392,79 @@ pub async fn db_replace_fetch(db: AnyBackend) {
     assert_eq!(row, replace_row);
 }
 
+pub async fn db_upsert(db: AnyBackend) {
+    let test_row = Entry::new(
+        EntryKind::Item,
+        "category",
+        "name",
+        "value",
+        vec![EntryTag::Encrypted("t1".to_string(), "v1".to_string())],
+    );
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    let inserted = conn
+        .upsert(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            &test_row.value,
+            Some(test_row.tags.as_slice()),
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    assert!(inserted);
+
+    let row = conn
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
+        .await
+        .expect(ERR_FETCH)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(row, test_row);
+
+    let mut replace_row = test_row.clone();
+    replace_row.value = "new value".into();
+    replace_row.tags = vec![EntryTag::Plaintext("t2".to_string(), "v2".to_string())];
+    let inserted = conn
+        .upsert(
+            EntryKind::Item,
+            &replace_row.category,
+            &replace_row.name,
+            &replace_row.value,
+            Some(replace_row.tags.as_slice()),
+            None,
+        )
+        .await
+        .expect(ERR_REPLACE);
+    assert!(!inserted);
+
+    let row = conn
+        .fetch(
+            EntryKind::Item,
+            &replace_row.category,
+            &replace_row.name,
+            TagMode::All,
+            false,
+        )
+        .await
+        .expect(ERR_FETCH)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(row, replace_row);
+
+    let count = conn
+        .count(Some(EntryKind::Item), Some(&test_row.category), None)
+        .await
+        .expect(ERR_COUNT);
+    assert_eq!(count, 1);
+}
+
 pub async fn db_replace_missing(db: AnyBackend) {
     let test_row = Entry::new(EntryKind::Item, "category", "name", "value", Vec::new());
 
@@ -488,7 +730,9 @@ pub async fn db_scan(db: AnyBackend) {
             None,
             Some(EntryKind::Item),
             Some(category.clone()),
+            None,
             tag_filter,
+            TagMode::All,
             offset,
             limit,
             None,
@@ -507,7 +751,9 @@ pub async fn db_scan(db: AnyBackend) {
             None,
             Some(EntryKind::Item),
             Some(category.clone()),
+            None,
             tag_filter,
+            TagMode::All,
             offset,
             limit,
             None,
@@ -519,6 +765,345 @@ pub async fn db_scan(db: AnyBackend) {
     assert_eq!(rows, None);
 }
 
+pub async fn db_scan_name_prefix(db: AnyBackend) {
+    let profile = db
+        .create_profile_with_plaintext_names(None, vec!["docs".to_string()])
+        .await
+        .expect(ERR_PROFILE);
+    assert_eq!(
+        db.plaintext_name_categories(Some(profile.clone()))
+            .await
+            .expect(ERR_PROFILE),
+        vec!["docs".to_string()]
+    );
+    assert!(db
+        .plaintext_name_categories(None)
+        .await
+        .expect(ERR_PROFILE)
+        .is_empty());
+
+    let mut conn = db.session(Some(profile.clone()), false).expect(ERR_SESSION);
+    for (category, name) in [
+        ("docs", "doc:12"),
+        ("docs", "doc:123:1"),
+        ("docs", "doc:123:2"),
+        ("docs", "doc:124:1"),
+        ("other", "doc:123:1"),
+    ] {
+        conn.update(
+            EntryKind::Item,
+            EntryOperation::Insert,
+            category,
+            name,
+            Some(b"value"),
+            None,
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    }
+    for category in ["docs", "other"] {
+        let row = conn
+            .fetch(EntryKind::Item, category, "doc:123:1", TagMode::All, false)
+            .await
+            .expect(ERR_FETCH)
+            .expect(ERR_REQ_ROW);
+        assert_eq!(row.category, category);
+        assert_eq!(row.name, "doc:123:1");
+    }
+    drop(conn);
+
+    let scan_names = |category: Option<&str>, name_prefix: Option<&str>| {
+        let db = db.clone();
+        let profile = profile.clone();
+        let category = category.map(str::to_string);
+        let name_prefix = name_prefix.map(str::to_string);
+        async move {
+            let mut scan = db
+                .scan(
+                    Some(profile),
+                    Some(EntryKind::Item),
+                    category,
+                    name_prefix,
+                    None,
+                    TagMode::All,
+                    None,
+                    None,
+                    None,
+                    false,
+                )
+                .await?;
+            let mut names = Vec::new();
+            while let Some(rows) = scan.fetch_next().await? {
+                names.extend(rows.into_iter().map(|row| row.name));
+            }
+            names.sort();
+            Result::<_, Error>::Ok(names)
+        }
+    };
+
+    assert_eq!(
+        scan_names(Some("docs"), Some("doc:123:"))
+            .await
+            .expect(ERR_SCAN),
+        ["doc:123:1", "doc:123:2"]
+    );
+    assert_eq!(
+        scan_names(Some("docs"), Some("doc:12"))
+            .await
+            .expect(ERR_SCAN)
+            .len(),
+        4
+    );
+    assert!(scan_names(Some("docs"), Some("doc:2"))
+        .await
+        .expect(ERR_SCAN)
+        .is_empty());
+    assert_eq!(scan_names(None, None).await.expect(ERR_SCAN).len(), 5);
+
+    for (category, kind) in [
+        (None, ErrorKind::Input),
+        (Some("other"), ErrorKind::Unsupported),
+    ] {
+        let err = scan_names(category, Some("doc:123:"))
+            .await
+            .expect_err(ERR_REQ_ERR);
+        assert_eq!(err.kind(), kind);
+    }
+}
+
+pub async fn db_empty_tag_values(db: AnyBackend) {
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    for (name, tags) in [
+        (
+            "empty",
+            vec![
+                EntryTag::Encrypted("enc".to_string(), "".to_string()),
+                EntryTag::Plaintext("plain".to_string(), "".to_string()),
+            ],
+        ),
+        (
+            "filled",
+            vec![
+                EntryTag::Encrypted("enc".to_string(), "x".to_string()),
+                EntryTag::Plaintext("plain".to_string(), "x".to_string()),
+            ],
+        ),
+        ("absent", vec![]),
+    ] {
+        conn.update(
+            EntryKind::Item,
+            EntryOperation::Insert,
+            "category",
+            name,
+            Some(b"value"),
+            Some(tags.as_slice()),
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    }
+
+    let row = conn
+        .fetch(EntryKind::Item, "category", "empty", TagMode::All, false)
+        .await
+        .expect(ERR_FETCH)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(
+        row.tags,
+        vec![
+            EntryTag::Encrypted("enc".to_string(), "".to_string()),
+            EntryTag::Plaintext("plain".to_string(), "".to_string()),
+        ]
+    );
+
+    for (filter, expected) in [
+        (TagFilter::is_eq("enc", ""), vec!["empty"]),
+        (TagFilter::is_eq("~plain", ""), vec!["empty"]),
+        (TagFilter::is_not_eq("enc", ""), vec!["filled"]),
+        (TagFilter::is_not_eq("~plain", ""), vec!["filled"]),
+        (TagFilter::is_in("enc", vec!["".to_string()]), vec!["empty"]),
+        (
+            TagFilter::is_in("~plain", vec!["".to_string()]),
+            vec!["empty"],
+        ),
+        (
+            TagFilter::exist(vec!["enc".to_string()]),
+            vec!["empty", "filled"],
+        ),
+        (
+            TagFilter::exist(vec!["~plain".to_string()]),
+            vec!["empty", "filled"],
+        ),
+    ] {
+        let rows = conn
+            .fetch_all(
+                Some(EntryKind::Item),
+                Some("category"),
+                Some(filter.clone()),
+                TagMode::None,
+                None,
+                None,
+                false,
+                false,
+            )
+            .await
+            .expect(ERR_FETCH_ALL);
+        let mut names = rows.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, expected, "Unexpected result for filter {filter:?}");
+    }
+}
+
+pub async fn db_negate_absent_tags(db: AnyBackend) {
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    for (name, tags) in [
+        (
+            "both",
+            vec![
+                EntryTag::Encrypted("enc".to_string(), "a".to_string()),
+                EntryTag::Plaintext("plain".to_string(), "a".to_string()),
+            ],
+        ),
+        (
+            "enc_only",
+            vec![EntryTag::Encrypted("enc".to_string(), "b".to_string())],
+        ),
+        ("absent", vec![]),
+    ] {
+        conn.update(
+            EntryKind::Item,
+            EntryOperation::Insert,
+            "category",
+            name,
+            Some(b"value"),
+            Some(tags.as_slice()),
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    }
+
+    for (filter, expected) in [
+        (
+            TagFilter::negate(TagFilter::is_eq("enc", "a")),
+            vec!["absent", "enc_only"],
+        ),
+        (
+            TagFilter::negate(TagFilter::is_eq("~plain", "a")),
+            vec!["absent", "enc_only"],
+        ),
+        (
+            TagFilter::negate(TagFilter::is_in(
+                "enc",
+                vec!["a".to_string(), "b".to_string()],
+            )),
+            vec!["absent"],
+        ),
+        (
+            TagFilter::negate(TagFilter::is_not_eq("enc", "a")),
+            vec!["absent", "both"],
+        ),
+        (
+            TagFilter::negate(TagFilter::exist(vec!["~plain".to_string()])),
+            vec!["absent", "enc_only"],
+        ),
+        (
+            TagFilter::negate(TagFilter::exist(vec![
+                "enc".to_string(),
+                "~plain".to_string(),
+            ])),
+            vec!["absent"],
+        ),
+        (
+            TagFilter::negate(TagFilter::all_of(vec![
+                TagFilter::is_eq("enc", "a"),
+                TagFilter::is_eq("~plain", "a"),
+            ])),
+            vec!["absent", "enc_only"],
+        ),
+        (
+            TagFilter::negate(TagFilter::any_of(vec![
+                TagFilter::is_eq("enc", "b"),
+                TagFilter::is_eq("~plain", "a"),
+            ])),
+            vec!["absent"],
+        ),
+    ] {
+        let rows = conn
+            .fetch_all(
+                Some(EntryKind::Item),
+                Some("category"),
+                Some(filter.clone()),
+                TagMode::None,
+                None,
+                None,
+                false,
+                false,
+            )
+            .await
+            .expect(ERR_FETCH_ALL);
+        let mut names = rows.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, expected, "Unexpected result for filter {filter:?}");
+    }
+}
+
+pub async fn db_scan_no_tags(db: AnyBackend) {
+    let test_row = Entry::new(
+        EntryKind::Item,
+        "category",
+        "name",
+        "value",
+        vec![
+            EntryTag::Encrypted("t1".to_string(), "v1".to_string()),
+            EntryTag::Plaintext("t2".to_string(), "v2".to_string()),
+        ],
+    );
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+
+    conn.update(
+        EntryKind::Item,
+        EntryOperation::Insert,
+        &test_row.category,
+        &test_row.name,
+        Some(&test_row.value),
+        Some(test_row.tags.as_slice()),
+        None,
+    )
+    .await
+    .expect(ERR_INSERT);
+    drop(conn);
+
+    let mut scan = db
+        .scan(
+            None,
+            Some(EntryKind::Item),
+            Some(test_row.category.clone()),
+            None,
+            Some(TagFilter::is_eq("t1", "v1")),
+            TagMode::None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await
+        .expect(ERR_SCAN);
+    let rows = scan
+        .fetch_next()
+        .await
+        .expect(ERR_SCAN_NEXT)
+        .expect(ERR_REQ_ROW);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].name, test_row.name);
+    assert_eq!(rows[0].value, test_row.value);
+    assert!(rows[0].tags.is_empty());
+}
+
 pub async fn db_remove_all(db: AnyBackend) {
     let test_rows = [
         Entry::new(
@@ -609,7 +1194,13 @@ pub async fn db_txn_rollback(db: AnyBackend) {
     let mut conn = db.session(None, false).expect("Error starting new session");
 
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect("Error fetching test row");
     assert_eq!(row, None);
@@ -639,7 +1230,13 @@ pub async fn db_txn_drop(db: AnyBackend) {
     let mut conn = db.session(None, false).expect("Error starting new session");
 
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect("Error fetching test row");
     assert_eq!(row, None);
@@ -668,7 +1265,13 @@ pub async fn db_session_drop(db: AnyBackend) {
     let mut conn = db.session(None, false).expect(ERR_SESSION);
 
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect(ERR_FETCH);
     assert_eq!(row, Some(test_row));
@@ -696,7 +1299,13 @@ pub async fn db_txn_commit(db: AnyBackend) {
     let mut conn = db.session(None, false).expect(ERR_SESSION);
 
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect(ERR_FETCH);
     assert_eq!(row, Some(test_row));
@@ -722,7 +1331,13 @@ pub async fn db_txn_fetch_for_update(db: AnyBackend) {
     // could detect that a second transaction would block here?
     // depends on the backend. just checking that no SQL errors occur for now.
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, true)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            true,
+        )
         .await
         .expect(ERR_FETCH)
         .expect(ERR_REQ_ROW);
@@ -733,6 +1348,7 @@ pub async fn db_txn_fetch_for_update(db: AnyBackend) {
             Some(EntryKind::Item),
             Some(&test_row.category),
             None,
+            TagMode::All,
             Some(2),
             None,
             false,
@@ -782,7 +1398,7 @@ pub async fn db_txn_contention(db: AnyBackend) {
         for _ in 0..INC {
             let mut conn = db.session(None, true).expect(ERR_TRANSACTION);
             let row = conn
-                .fetch(EntryKind::Item, &category, &name, true)
+                .fetch(EntryKind::Item, &category, &name, TagMode::All, true)
                 .await
                 .map_err(|e| {
                     log::error!("{:?}", e);
@@ -829,7 +1445,13 @@ pub async fn db_txn_contention(db: AnyBackend) {
     // check the total
     let mut conn = db.session(None, false).expect(ERR_SESSION);
     let row = conn
-        .fetch(EntryKind::Item, &test_row.category, &test_row.name, false)
+        .fetch(
+            EntryKind::Item,
+            &test_row.category,
+            &test_row.name,
+            TagMode::All,
+            false,
+        )
         .await
         .expect(ERR_FETCH)
         .expect(ERR_REQ_ROW);
@@ -848,12 +1470,123 @@ pub async fn db_list_profiles(db: AnyBackend) {
     assert_eq!(profs, found);
 }
 
+pub async fn db_profile_stats(db: AnyBackend) {
+    let stats = db.profile_stats(None).await.expect(ERR_PROFILE_STATS);
+    assert_eq!(stats, ProfileStats::default());
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+    for (kind, category, name) in [
+        (EntryKind::Item, "a", "one"),
+        (EntryKind::Item, "a", "two"),
+        (EntryKind::Item, "b", "one"),
+        (EntryKind::Kms, "a", "one"),
+    ] {
+        conn.update(
+            kind,
+            EntryOperation::Insert,
+            category,
+            name,
+            Some(b"value"),
+            None,
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    }
+    drop(conn);
+
+    let stats = db.profile_stats(None).await.expect(ERR_PROFILE_STATS);
+    assert_eq!(
+        stats.items,
+        [("a".to_string(), 2), ("b".to_string(), 1)].into()
+    );
+    assert_eq!(stats.keys, [("a".to_string(), 1)].into());
+
+    let p_new = db.create_profile(None).await.expect(ERR_PROFILE);
+    let stats = db
+        .profile_stats(Some(p_new))
+        .await
+        .expect(ERR_PROFILE_STATS);
+    assert_eq!(stats, ProfileStats::default());
+
+    let err = db
+        .profile_stats(Some("missing".to_string()))
+        .await
+        .expect_err(ERR_REQ_ERR);
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+pub async fn db_seal_unseal(db: AnyBackend) {
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+    let sealed = conn.seal(b"manifest").await.expect("Error sealing value");
+    assert_ne!(&sealed[..], b"manifest");
+    let unsealed = conn.unseal(&sealed).await.expect("Error unsealing value");
+    assert_eq!(unsealed, &b"manifest"[..]);
+    conn.close(false).await.expect(ERR_SESSION);
+
+    let profile = db.create_profile(None).await.expect(ERR_PROFILE);
+    let mut conn = db.session(Some(profile), false).expect(ERR_SESSION);
+    let err = conn.unseal(&sealed).await.expect_err(ERR_REQ_ERR);
+    assert_eq!(err.kind(), ErrorKind::Encryption);
+    conn.close(false).await.expect(ERR_SESSION);
+}
+
+pub async fn db_export_tag_hasher(db: AnyBackend) {
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+    let hasher = conn
+        .export_tag_hasher()
+        .await
+        .expect("Error exporting tag hasher");
+    conn.close(false).await.expect(ERR_SESSION);
+    let token = hasher.hash_tag_value("value").expect("Error hashing tag");
+    assert_eq!(
+        token,
+        hasher.hash_tag_value("value").expect("Error hashing tag")
+    );
+    assert_ne!(
+        token,
+        hasher.hash_tag_value("other").expect("Error hashing tag")
+    );
+
+    let profile = db.create_profile(None).await.expect(ERR_PROFILE);
+    let mut conn = db.session(Some(profile), false).expect(ERR_SESSION);
+    let other = conn
+        .export_tag_hasher()
+        .await
+        .expect("Error exporting tag hasher");
+    conn.close(false).await.expect(ERR_SESSION);
+    assert_ne!(
+        token,
+        other.hash_tag_value("value").expect("Error hashing tag")
+    );
+}
+
+pub async fn db_schema_version(db: AnyBackend) {
+    let version = db
+        .schema_version()
+        .await
+        .expect("Error fetching schema version");
+    assert_eq!(version, SCHEMA_VERSION);
+    db.migrate().await.expect("Error migrating store");
+    let version = db
+        .schema_version()
+        .await
+        .expect("Error fetching schema version");
+    assert_eq!(version, SCHEMA_VERSION);
+}
+
 pub async fn db_get_set_default_profile(db: AnyBackend) {
     let p_default = db.get_default_profile().await.unwrap();
     let p_new = db.create_profile(None).await.unwrap();
     assert_ne!(p_new, p_default);
     db.set_default_profile(p_new.clone()).await.unwrap();
     assert_eq!(db.get_default_profile().await.unwrap(), p_new);
+    let err = db
+        .set_default_profile("missing".to_string())
+        .await
+        .expect_err(ERR_REQ_ERR);
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(db.get_default_profile().await.unwrap(), p_new);
 }
 
 pub async fn db_import_scan(db: AnyBackend) {
@@ -893,6 +1626,8 @@ pub async fn db_import_scan(db: AnyBackend) {
             None,
             None,
             None,
+            TagMode::All,
+            None,
             None,
             None,
             false,
@@ -912,6 +1647,8 @@ pub async fn db_import_scan(db: AnyBackend) {
             None,
             None,
             None,
+            TagMode::All,
+            None,
             None,
             None,
             false,
@@ -924,3 +1661,43 @@ pub async fn db_import_scan(db: AnyBackend) {
     let rows = scan.fetch_next().await.expect(ERR_SCAN_NEXT);
     assert_eq!(rows, None);
 }
+
+pub async fn db_create_tag_index(db: AnyBackend) {
+    db.create_tag_index(None, "~plain".to_string())
+        .await
+        .expect("Error creating tag index");
+    // creating the same index again has no effect
+    db.create_tag_index(None, "plain".to_string())
+        .await
+        .expect("Error creating tag index");
+
+    let mut conn = db.session(None, false).expect(ERR_SESSION);
+    for (name, value) in [("one", "a"), ("two", "b")] {
+        conn.update(
+            EntryKind::Item,
+            EntryOperation::Insert,
+            "category",
+            name,
+            Some(b"value"),
+            Some(&[EntryTag::Plaintext("plain".to_string(), value.to_string())]),
+            None,
+        )
+        .await
+        .expect(ERR_INSERT);
+    }
+    let rows = conn
+        .fetch_all(
+            Some(EntryKind::Item),
+            Some("category"),
+            Some(TagFilter::is_eq("~plain", "b")),
+            TagMode::None,
+            None,
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect(ERR_FETCH_ALL);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].name, "two");
+}
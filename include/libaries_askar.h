@@ -27,6 +27,7 @@ enum ErrorCode
   NotFound = 6,
   Unexpected = 7,
   Unsupported = 8,
+  Connection = 9,
   Custom = 100,
 };
 #ifndef __cplusplus
@@ -289,6 +290,21 @@ ErrorCode askar_argon2_derive_password(int8_t parameters,
                                        const struct Argon2Config *config,
                                        struct SecretBuffer *out);
 
+/**
+ * ## Derive password using Argon2, returning a PHC-format encoded string
+ *
+ * The `parameters` and `config` arguments are interpreted as for
+ * `askar_argon2_derive_password`. The output is a string of the form
+ * `$argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>`, with the salt and
+ * 32-byte hash encoded using unpadded base64.
+ *
+ */
+ErrorCode askar_argon2_derive_password_encoded(int8_t parameters,
+                                               struct ByteBuffer password,
+                                               struct ByteBuffer salt,
+                                               const struct Argon2Config *config,
+                                               const char **out);
+
 void askar_buffer_free(struct SecretBuffer buffer);
 
 void askar_clear_custom_logger(void);
@@ -311,6 +327,8 @@ ErrorCode askar_entry_list_get_value(EntryListHandle handle,
 
 ErrorCode askar_get_current_error(const char **error_json_p);
 
+ErrorCode askar_get_last_error_info(const char **error_json_p);
+
 void askar_handle_list_free(struct FfiHandleList handle);
 
 ErrorCode askar_key_aead_decrypt(LocalKeyHandle handle,
@@ -488,6 +506,7 @@ ErrorCode askar_scan_start(StoreHandle handle,
                            FfiStr profile,
                            FfiStr category,
                            FfiStr tag_filter,
+                           FfiStr tag_names,
                            int64_t offset,
                            int64_t limit,
                            FfiStr order_by,
@@ -509,6 +528,7 @@ ErrorCode askar_session_count(SessionHandle handle,
 ErrorCode askar_session_fetch(SessionHandle handle,
                               FfiStr category,
                               FfiStr name,
+                              FfiStr tag_names,
                               int8_t for_update,
                               void (*cb)(CallbackId cb_id, ErrorCode err, EntryListHandle results),
                               CallbackId cb_id);
@@ -516,6 +536,7 @@ ErrorCode askar_session_fetch(SessionHandle handle,
 ErrorCode askar_session_fetch_all(SessionHandle handle,
                                   FfiStr category,
                                   FfiStr tag_filter,
+                                  FfiStr tag_names,
                                   int64_t limit,
                                   FfiStr order_by,
                                   int8_t descending,
@@ -0,0 +1,324 @@
+//! Blocking wrappers for the store and session types
+//!
+//! Each method runs the corresponding async operation to completion on the
+//! shared runtime used internally by the store backends, so no runtime needs
+//! to be created by the caller. These methods must not be called from within
+//! an async context, as blocking on the shared runtime from one of its own
+//! worker threads will panic.
+//!
+//! A [`Store`] may be cloned and shared between threads. A [`Session`] may be
+//! moved to another thread but must only be used from one thread at a time.
+
+use askar_storage::backend::OrderBy;
+
+use crate::{
+    entry::{Entry, EntryTag, TagFilter, TagMode},
+    error::Error,
+    future::{block_on, enter},
+    kms::{KeyAlg, KeyEntry, KeyReference, LocalKey},
+    store::{PassKey, StoreKeyMethod, StoreValidation},
+};
+
+/// A blocking wrapper around an opened [`crate::Store`]
+#[derive(Debug, Clone)]
+pub struct Store(crate::Store);
+
+impl Store {
+    /// Provision a new store instance using a database URL
+    pub fn provision(
+        db_url: &str,
+        key_method: StoreKeyMethod,
+        pass_key: PassKey<'_>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> Result<Self, Error> {
+        block_on(crate::Store::provision(
+            db_url, key_method, pass_key, profile, recreate,
+        ))
+        .map(Self)
+    }
+
+    /// Open an existing store instance using a database URL, or provision a
+    /// new store if none exists
+    ///
+    /// The returned flag is `true` if a new store was provisioned.
+    pub fn open_or_provision(
+        db_url: &str,
+        key_method: StoreKeyMethod,
+        pass_key: PassKey<'_>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> Result<(Self, bool), Error> {
+        block_on(crate::Store::open_or_provision(
+            db_url, key_method, pass_key, profile, recreate,
+        ))
+        .map(|(store, created)| (Self(store), created))
+    }
+
+    /// Open a store instance from a database URL
+    pub fn open(
+        db_url: &str,
+        key_method: Option<StoreKeyMethod>,
+        pass_key: PassKey<'_>,
+        profile: Option<String>,
+    ) -> Result<Self, Error> {
+        block_on(crate::Store::open(db_url, key_method, pass_key, profile)).map(Self)
+    }
+
+    /// Remove a store instance using a database URL
+    pub fn remove(db_url: &str) -> Result<bool, Error> {
+        block_on(crate::Store::remove(db_url))
+    }
+
+    /// Check that a store could be provisioned using a database URL, without
+    /// creating the database or the store schema
+    ///
+    /// See [`crate::Store::validate_uri`].
+    pub fn validate_uri(db_url: &str) -> Result<StoreValidation, Error> {
+        block_on(crate::Store::validate_uri(db_url))
+    }
+
+    /// Enable an in-memory cache of up to `capacity` stored keys
+    ///
+    /// See [`crate::Store::with_key_cache`].
+    pub fn with_key_cache(self, capacity: usize) -> Self {
+        Self(self.0.with_key_cache(capacity))
+    }
+
+    /// Get the default profile name used when starting a scan or a session
+    pub fn get_active_profile(&self) -> String {
+        self.0.get_active_profile()
+    }
+
+    /// Create a new profile with the given profile name
+    pub fn create_profile(&self, name: Option<String>) -> Result<String, Error> {
+        block_on(self.0.create_profile(name))
+    }
+
+    /// Create a new profile which stores the names of entries in the given
+    /// categories as plaintext
+    pub fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> Result<String, Error> {
+        block_on(self.0.create_profile_with_plaintext_names(name, categories))
+    }
+
+    /// Get the categories whose entry names are stored as plaintext for a
+    /// profile, defaulting to the active profile
+    pub fn plaintext_name_categories(&self, profile: Option<String>) -> Result<Vec<String>, Error> {
+        block_on(self.0.plaintext_name_categories(profile))
+    }
+
+    /// Get the details of all store profiles
+    pub fn list_profiles(&self) -> Result<Vec<String>, Error> {
+        block_on(self.0.list_profiles())
+    }
+
+    /// Remove an existing profile with the given profile name
+    pub fn remove_profile(&self, name: String) -> Result<(), Error> {
+        block_on(self.0.remove_profile(name))
+    }
+
+    /// Create a new session against the store
+    pub fn session(&self, profile: Option<String>) -> Result<Session, Error> {
+        block_on(self.0.session(profile)).map(|sess| Session(Some(sess)))
+    }
+
+    /// Create a new transaction session against the store
+    pub fn transaction(&self, profile: Option<String>) -> Result<Session, Error> {
+        block_on(self.0.transaction(profile)).map(|sess| Session(Some(sess)))
+    }
+
+    /// Access the wrapped async store instance
+    pub fn as_async(&self) -> &crate::Store {
+        &self.0
+    }
+
+    /// Close the store instance, waiting for any shutdown procedures to complete.
+    pub fn close(self) -> Result<(), Error> {
+        block_on(self.0.close())
+    }
+}
+
+impl From<crate::Store> for Store {
+    fn from(store: crate::Store) -> Self {
+        Self(store)
+    }
+}
+
+impl From<Store> for crate::Store {
+    fn from(store: Store) -> Self {
+        store.0
+    }
+}
+
+/// A blocking wrapper around an active [`crate::Session`]
+///
+/// The wrapped session is dropped within the shared runtime, as returning its
+/// connection to the pool may spawn a background task.
+#[derive(Debug)]
+pub struct Session(Option<crate::Session>);
+
+impl Session {
+    fn inner(&mut self) -> &mut crate::Session {
+        self.0.as_mut().expect("Session already closed")
+    }
+
+    /// Count the number of entries for a given record category
+    pub fn count(
+        &mut self,
+        category: Option<&str>,
+        tag_filter: Option<TagFilter>,
+    ) -> Result<i64, Error> {
+        block_on(self.inner().count(category, tag_filter))
+    }
+
+    /// Retrieve the current record at `(category, name)`.
+    pub fn fetch(
+        &mut self,
+        category: &str,
+        name: &str,
+        tag_mode: TagMode,
+        for_update: bool,
+    ) -> Result<Option<Entry>, Error> {
+        block_on(self.inner().fetch(category, name, tag_mode, for_update))
+    }
+
+    /// Retrieve all records matching the given `category` and `tag_filter`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fetch_all(
+        &mut self,
+        category: Option<&str>,
+        tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
+        limit: Option<i64>,
+        order_by: Option<OrderBy>,
+        descending: bool,
+        for_update: bool,
+    ) -> Result<Vec<Entry>, Error> {
+        block_on(self.inner().fetch_all(
+            category, tag_filter, tag_mode, limit, order_by, descending, for_update,
+        ))
+    }
+
+    /// Insert a new record into the store
+    pub fn insert(
+        &mut self,
+        category: &str,
+        name: &str,
+        value: &[u8],
+        tags: Option<&[EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> Result<(), Error> {
+        block_on(self.inner().insert(category, name, value, tags, expiry_ms))
+    }
+
+    /// Remove a record from the store
+    pub fn remove(&mut self, category: &str, name: &str) -> Result<(), Error> {
+        block_on(self.inner().remove(category, name))
+    }
+
+    /// Replace the value and tags of a record in the store
+    pub fn replace(
+        &mut self,
+        category: &str,
+        name: &str,
+        value: &[u8],
+        tags: Option<&[EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> Result<(), Error> {
+        block_on(self.inner().replace(category, name, value, tags, expiry_ms))
+    }
+
+    /// Insert a new record, or replace the value and tags of an existing
+    /// record, in a single atomic operation
+    pub fn upsert(
+        &mut self,
+        category: &str,
+        name: &str,
+        value: &[u8],
+        tags: Option<&[EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> Result<bool, Error> {
+        block_on(self.inner().upsert(category, name, value, tags, expiry_ms))
+    }
+
+    /// Remove all records in the store matching a given `category` and `tag_filter`
+    pub fn remove_all(
+        &mut self,
+        category: Option<&str>,
+        tag_filter: Option<TagFilter>,
+    ) -> Result<i64, Error> {
+        block_on(self.inner().remove_all(category, tag_filter))
+    }
+
+    /// Insert a local key instance into the store
+    pub fn insert_key(
+        &mut self,
+        name: &str,
+        key: &LocalKey,
+        metadata: Option<&str>,
+        reference: Option<KeyReference>,
+        tags: Option<&[EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> Result<(), Error> {
+        block_on(
+            self.inner()
+                .insert_key(name, key, metadata, reference, tags, expiry_ms),
+        )
+    }
+
+    /// Generate and store a new random key, returning its name and the key
+    pub fn create_key(
+        &mut self,
+        alg: KeyAlg,
+        metadata: Option<&str>,
+        tags: Option<&[EntryTag]>,
+    ) -> Result<(String, LocalKey), Error> {
+        block_on(self.inner().create_key(alg, metadata, tags))
+    }
+
+    /// Fetch an existing key from the store
+    pub fn fetch_key(&mut self, name: &str, for_update: bool) -> Result<Option<KeyEntry>, Error> {
+        block_on(self.inner().fetch_key(name, for_update))
+    }
+
+    /// Remove an existing key from the store, returning `false` if it was
+    /// not found
+    pub fn remove_key(&mut self, name: &str) -> Result<bool, Error> {
+        block_on(self.inner().remove_key(name))
+    }
+
+    /// Discard the secret key of an existing keypair in the store, retaining
+    /// the public key
+    pub fn tombstone_key(&mut self, name: &str) -> Result<(), Error> {
+        block_on(self.inner().tombstone_key(name))
+    }
+
+    /// Access the wrapped async session
+    pub fn as_async_mut(&mut self) -> &mut crate::Session {
+        self.inner()
+    }
+
+    /// Commit the pending transaction
+    pub fn commit(mut self) -> Result<(), Error> {
+        let session = self.0.take().expect("Session already closed");
+        block_on(session.commit())
+    }
+
+    /// Roll back the pending transaction
+    pub fn rollback(mut self) -> Result<(), Error> {
+        let session = self.0.take().expect("Session already closed");
+        block_on(session.rollback())
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Some(session) = self.0.take() {
+            enter(move || drop(session));
+        }
+    }
+}
@@ -11,8 +11,18 @@ pub enum ErrorKind {
     Backend,
 
     /// The store backend was too busy to handle the request
+    ///
+    /// This indicates a transient lock conflict, such as a busy or locked
+    /// SQLite database or a Postgres serialization failure, and the
+    /// operation may be retried.
     Busy,
 
+    /// The connection to the store backend was lost or closed
+    ///
+    /// This is a transient condition and the operation may be retried, as a
+    /// new connection is established for the next operation.
+    Connection,
+
     /// A custom error type for external integrations
     Custom,
 
@@ -41,6 +51,7 @@ impl ErrorKind {
         match self {
             Self::Backend => "Backend error",
             Self::Busy => "Busy",
+            Self::Connection => "Connection error",
             Self::Custom => "Custom error",
             Self::Duplicate => "Duplicate",
             Self::Encryption => "Encryption error",
@@ -144,6 +155,7 @@ impl From<CryptoError> for Error {
             | CryptoErrorKind::MissingSecretKey
             | CryptoErrorKind::Usage => ErrorKind::Input,
             CryptoErrorKind::Unsupported => ErrorKind::Unsupported,
+            _ => ErrorKind::Unexpected,
         };
         Error::from_msg(kind, err.message())
     }
@@ -155,6 +167,7 @@ impl From<StorageError> for Error {
         let kind = match kind {
             StorageErrorKind::Backend => ErrorKind::Backend,
             StorageErrorKind::Busy => ErrorKind::Busy,
+            StorageErrorKind::Connection => ErrorKind::Connection,
             StorageErrorKind::Custom => ErrorKind::Custom,
             StorageErrorKind::Duplicate => ErrorKind::Duplicate,
             StorageErrorKind::Encryption => ErrorKind::Encryption,
@@ -1,5 +1,6 @@
 use crate::error::{Error, ErrorKind};
 
+use std::cell::RefCell;
 use std::os::raw::c_char;
 use std::sync::RwLock;
 
@@ -9,6 +10,10 @@ use once_cell::sync::Lazy;
 
 static LAST_ERROR: Lazy<RwLock<Option<Error>>> = Lazy::new(|| RwLock::new(None));
 
+thread_local! {
+    static LAST_ERROR_INFO: RefCell<Option<String>> = RefCell::new(None);
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
 #[repr(i64)]
 pub enum ErrorCode {
@@ -21,6 +26,7 @@ pub enum ErrorCode {
     NotFound = 6,
     Unexpected = 7,
     Unsupported = 8,
+    Connection = 9,
     Custom = 100,
 }
 
@@ -29,6 +35,7 @@ impl From<ErrorKind> for ErrorCode {
         match kind {
             ErrorKind::Backend => ErrorCode::Backend,
             ErrorKind::Busy => ErrorCode::Busy,
+            ErrorKind::Connection => ErrorCode::Connection,
             ErrorKind::Custom => ErrorCode::Custom,
             ErrorKind::Duplicate => ErrorCode::Duplicate,
             ErrorKind::Encryption => ErrorCode::Encryption,
@@ -75,12 +82,94 @@ pub fn get_current_error_json() -> String {
     }
 }
 
+/// Fetch the details of the last error raised on the current thread.
+///
+/// The result is a JSON object with the members `code` (numeric error code),
+/// `message` (the error message), and `cause` (the description of the
+/// underlying error, if any). As with `askar_get_current_error`, the details
+/// are retained until they are fetched or another error is raised. For an
+/// asynchronous operation, they are available within its callback.
+#[no_mangle]
+pub extern "C" fn askar_get_last_error_info(error_json_p: *mut *const c_char) -> ErrorCode {
+    trace!("askar_get_last_error_info");
+
+    let info = LAST_ERROR_INFO
+        .with(|info| info.borrow_mut().take())
+        .unwrap_or_else(|| r#"{"code":0,"message":null,"cause":null}"#.to_owned());
+    unsafe { *error_json_p = rust_string_to_c(info) };
+
+    ErrorCode::Success
+}
+
+fn error_info_json(err: &Error) -> String {
+    #[derive(Serialize)]
+    struct ErrorInfoJson {
+        code: i64,
+        message: String,
+        cause: Option<String>,
+    }
+
+    serde_json::json!(&ErrorInfoJson {
+        code: ErrorCode::from(err.kind()) as i64,
+        message: err
+            .message()
+            .map(str::to_string)
+            .unwrap_or_else(|| err.kind().to_string()),
+        cause: err.cause.as_ref().map(ToString::to_string),
+    })
+    .to_string()
+}
+
 pub fn set_last_error(error: Option<Error>) -> ErrorCode {
     trace!("askar_set_last_error");
     let code = match error.as_ref() {
         Some(err) => err.kind.into(),
         None => ErrorCode::Success,
     };
+    if let Some(err) = error.as_ref() {
+        LAST_ERROR_INFO.with(|info| info.replace(Some(error_info_json(err))));
+    }
     *LAST_ERROR.write().unwrap() = error;
     code
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CString, ptr, thread};
+
+    use super::*;
+
+    fn last_error_info() -> serde_json::Value {
+        let mut info_p = ptr::null();
+        assert_eq!(askar_get_last_error_info(&mut info_p), ErrorCode::Success);
+        let info = unsafe { CString::from_raw(info_p as *mut c_char) };
+        serde_json::from_slice(info.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn get_last_error_info() {
+        let err = err_msg!(Input, "Invalid input").with_cause(std::fmt::Error);
+        assert_eq!(set_last_error(Some(err)), ErrorCode::Input);
+        let info = last_error_info();
+        assert_eq!(info["code"], ErrorCode::Input as i64);
+        assert_eq!(info["message"], "Invalid input");
+        assert_eq!(info["cause"], std::fmt::Error.to_string());
+
+        // the details are cleared once fetched
+        assert_eq!(last_error_info()["code"], 0);
+    }
+
+    #[test]
+    fn last_error_info_per_thread() {
+        set_last_error(Some(err_msg!(NotFound, "Missing")));
+        thread::spawn(|| {
+            set_last_error(Some(err_msg!(Busy, "Busy")));
+            assert_eq!(last_error_info()["code"], ErrorCode::Busy as i64);
+        })
+        .join()
+        .unwrap();
+        let info = last_error_info();
+        assert_eq!(info["code"], ErrorCode::NotFound as i64);
+        assert_eq!(info["message"], "Missing");
+    }
+}
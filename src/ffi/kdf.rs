@@ -1,4 +1,9 @@
-use crate::ffi::{error::ErrorCode, secret::SecretBuffer};
+use std::os::raw::c_char;
+
+use crate::{
+    error::Error,
+    ffi::{error::ErrorCode, secret::SecretBuffer},
+};
 use askar_crypto::kdf::{
     argon2::{
         Algorithm as Argon2Algorithm, Argon2, Params as Argon2Params, Version as Argon2Version,
@@ -6,7 +11,7 @@ use askar_crypto::kdf::{
     },
     KeyDerivation,
 };
-use ffi_support::ByteBuffer;
+use ffi_support::{rust_string_to_c, ByteBuffer};
 
 #[repr(C)]
 pub struct Argon2Config {
@@ -27,6 +32,57 @@ pub struct Argon2Config {
     time_cost: i32,
 }
 
+fn argon2_params(parameters: i8, config: *const Argon2Config) -> Result<Argon2Params, Error> {
+    if parameters == -1 {
+        if let Some(cfg) = unsafe { config.as_ref() } {
+            let alg = match cfg.algorithm {
+                0 => Argon2Algorithm::Argon2d,
+                1 => Argon2Algorithm::Argon2i,
+                2 => Argon2Algorithm::Argon2id,
+                _ => return Err(err_msg!("Invalid value for algorithm")),
+            };
+            let version = match cfg.version {
+                16 => Argon2Version::V0x10,
+                19 => Argon2Version::V0x13,
+                _ => return Err(err_msg!("Invalid value for version")),
+            };
+            let parallelism = if cfg.parallelism > 0 {
+                cfg.parallelism as u32
+            } else {
+                return Err(err_msg!("Invalid value for parallelism"));
+            };
+            let mem_cost = if cfg.mem_cost > 0 {
+                cfg.mem_cost as u32
+            } else {
+                return Err(err_msg!("Invalid value for mem_cost"));
+            };
+            let time_cost = if cfg.time_cost > 0 {
+                cfg.time_cost as u32
+            } else {
+                return Err(err_msg!("Invalid value for time_cost"));
+            };
+            Ok(Argon2Params {
+                alg,
+                version,
+                parallelism,
+                mem_cost,
+                time_cost,
+            })
+        } else {
+            Err(err_msg!("Expected pointer to config"))
+        }
+    } else {
+        if !config.is_null() {
+            return Err(err_msg!("Unexpected custom configuration"));
+        }
+        match parameters {
+            0 => Ok(PARAMS_MODERATE),
+            1 => Ok(PARAMS_INTERACTIVE),
+            _ => Err(err_msg!("Invalid value for parameters")),
+        }
+    }
+}
+
 /// ## Derive password using Argon2
 ///
 /// The `parameters` argument determines the Argon2 derivation parameters:
@@ -43,54 +99,7 @@ pub extern "C" fn askar_argon2_derive_password(
     out: *mut SecretBuffer,
 ) -> ErrorCode {
     catch_err! {
-        let params = if parameters == -1 {
-            if let Some(cfg) = unsafe { config.as_ref() } {
-                let alg = match cfg.algorithm {
-                    0 => Argon2Algorithm::Argon2d,
-                    1 => Argon2Algorithm::Argon2i,
-                    2 => Argon2Algorithm::Argon2id,
-                    _ => return Err(err_msg!("Invalid value for algorithm"))
-                };
-                let version = match cfg.version {
-                    16 => Argon2Version::V0x10,
-                    19 => Argon2Version::V0x13,
-                    _ => return Err(err_msg!("Invalid value for version"))
-                };
-                let parallelism = if cfg.parallelism > 0 {
-                    cfg.parallelism as u32
-                } else {
-                    return Err(err_msg!("Invalid value for parallelism"))
-                };
-                let mem_cost = if cfg.mem_cost > 0 {
-                    cfg.mem_cost as u32
-                } else {
-                    return Err(err_msg!("Invalid value for mem_cost"))
-                };
-                let time_cost = if cfg.time_cost > 0 {
-                    cfg.time_cost as u32
-                } else {
-                    return Err(err_msg!("Invalid value for time_cost"))
-                };
-                Argon2Params {
-                    alg,
-                    version,
-                    parallelism,
-                    mem_cost,
-                    time_cost,
-                }
-            } else {
-                return Err(err_msg!("Expected pointer to config"))
-            }
-        } else {
-            if !config.is_null() {
-                return Err(err_msg!("Unexpected custom configuration"))
-            }
-            match parameters {
-                0 => PARAMS_MODERATE,
-                1 => PARAMS_INTERACTIVE,
-                _ => return Err(err_msg!("Invalid value for parameters"))
-            }
-        };
+        let params = argon2_params(parameters, config)?;
 
         let mut argon2 = Argon2::new(password.as_slice(), salt.as_slice(), params)?;
 
@@ -103,3 +112,31 @@ pub extern "C" fn askar_argon2_derive_password(
         Ok(ErrorCode::Success)
     }
 }
+
+/// ## Derive password using Argon2, returning a PHC-format encoded string
+///
+/// The `parameters` and `config` arguments are interpreted as for
+/// `askar_argon2_derive_password`. The output is a string of the form
+/// `$argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>`, with the salt and
+/// 32-byte hash encoded using unpadded base64.
+///
+#[no_mangle]
+pub extern "C" fn askar_argon2_derive_password_encoded(
+    parameters: i8,
+    password: ByteBuffer,
+    salt: ByteBuffer,
+    config: *const Argon2Config,
+    out: *mut *const c_char,
+) -> ErrorCode {
+    catch_err! {
+        let params = argon2_params(parameters, config)?;
+
+        let argon2 = Argon2::new(password.as_slice(), salt.as_slice(), params)?;
+
+        let encoded = argon2.derive_encoded(32)?;
+
+        unsafe { *out = rust_string_to_c(encoded) };
+
+        Ok(ErrorCode::Success)
+    }
+}
@@ -15,7 +15,7 @@ use super::{
     CallbackId, EnsureCallback, ErrorCode, ResourceHandle,
 };
 use crate::{
-    entry::{Entry, EntryOperation, Scan, TagFilter},
+    entry::{Entry, EntryOperation, Scan, TagFilter, TagMode},
     error::Error,
     ffi::result_list::{FfiHandleList, FfiStringList},
     future::spawn_ok,
@@ -368,7 +368,7 @@ pub extern "C" fn askar_store_remove_profile(
         let profile = profile.into_opt_string().ok_or_else(|| err_msg!("Profile name not provided"))?;
         let cb = EnsureCallback::new(move |result|
             match result {
-                Ok(removed) => cb(cb_id, ErrorCode::Success, removed as i8),
+                Ok(()) => cb(cb_id, ErrorCode::Success, 1),
                 Err(err) => cb(cb_id, set_last_error(Some(err)), 0),
             }
         );
@@ -620,6 +620,7 @@ pub extern "C" fn askar_scan_start(
     profile: FfiStr<'_>,
     category: FfiStr<'_>,
     tag_filter: FfiStr<'_>,
+    tag_names: FfiStr<'_>,
     offset: i64,
     limit: i64,
     order_by: FfiStr<'_>,
@@ -641,6 +642,14 @@ pub extern "C" fn askar_scan_start(
         let profile = profile.into_opt_string();
         let category = category.into_opt_string();
         let tag_filter = tag_filter.as_opt_str().map(TagFilter::from_str).transpose()?;
+        let tag_mode = if let Some(tag_names) = tag_names.as_opt_str() {
+            TagMode::Names(
+                serde_json::from_str::<Vec<String>>(tag_names)
+                    .map_err(err_map!("Error decoding tag names"))?,
+            )
+        } else {
+            TagMode::All
+        };
         let cb = EnsureCallback::new(move |result: Result<ScanHandle,Error>|
             match result {
                 Ok(scan_handle) => {
@@ -653,7 +662,7 @@ pub extern "C" fn askar_scan_start(
         spawn_ok(async move {
             let result = async {
                 let store = handle.load().await?;
-                let scan = store.scan(profile, category, tag_filter, Some(offset), if limit < 0 { None }else {Some(limit)}, order_by, descending).await?;
+                let scan = store.scan(profile, category, None, tag_filter, tag_mode, Some(offset), if limit < 0 { None }else {Some(limit)}, order_by, descending).await?;
                 Ok(FFI_SCANS.insert(handle, scan).await)
             }.await;
             cb.resolve(result);
@@ -782,6 +791,7 @@ pub extern "C" fn askar_session_fetch(
     handle: SessionHandle,
     category: FfiStr<'_>,
     name: FfiStr<'_>,
+    tag_names: FfiStr<'_>,
     for_update: i8,
     cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, results: EntryListHandle)>,
     cb_id: CallbackId,
@@ -791,6 +801,14 @@ pub extern "C" fn askar_session_fetch(
         let cb = cb.ok_or_else(|| err_msg!("No callback provided"))?;
         let category = category.into_opt_string().ok_or_else(|| err_msg!("Category not provided"))?;
         let name = name.into_opt_string().ok_or_else(|| err_msg!("Name not provided"))?;
+        let tag_mode = if let Some(tag_names) = tag_names.as_opt_str() {
+            TagMode::Names(
+                serde_json::from_str::<Vec<String>>(tag_names)
+                    .map_err(err_map!("Error decoding tag names"))?,
+            )
+        } else {
+            TagMode::All
+        };
         let cb = EnsureCallback::new(move |result: Result<Option<Entry>,Error>|
             match result {
                 Ok(Some(entry)) => {
@@ -804,7 +822,7 @@ pub extern "C" fn askar_session_fetch(
         spawn_ok(async move {
             let result = async {
                 let mut session = FFI_SESSIONS.borrow(handle).await?;
-                session.fetch(&category, &name, for_update != 0).await
+                session.fetch(&category, &name, tag_mode, for_update != 0).await
             }.await;
             cb.resolve(result);
         });
@@ -817,6 +835,7 @@ pub extern "C" fn askar_session_fetch_all(
     handle: SessionHandle,
     category: FfiStr<'_>,
     tag_filter: FfiStr<'_>,
+    tag_names: FfiStr<'_>,
     limit: i64,
     order_by: FfiStr<'_>,
     descending: i8,
@@ -837,6 +856,14 @@ pub extern "C" fn askar_session_fetch_all(
         let cb = cb.ok_or_else(|| err_msg!("No callback provided"))?;
         let category = category.into_opt_string();
         let tag_filter = tag_filter.as_opt_str().map(TagFilter::from_str).transpose()?;
+        let tag_mode = if let Some(tag_names) = tag_names.as_opt_str() {
+            TagMode::Names(
+                serde_json::from_str::<Vec<String>>(tag_names)
+                    .map_err(err_map!("Error decoding tag names"))?,
+            )
+        } else {
+            TagMode::All
+        };
         let limit = if limit < 0 { None } else {Some(limit)};
         let cb = EnsureCallback::new(move |result|
             match result {
@@ -850,7 +877,7 @@ pub extern "C" fn askar_session_fetch_all(
         spawn_ok(async move {
             let result = async {
                 let mut session = FFI_SESSIONS.borrow(handle).await?;
-                session.fetch_all(category.as_deref(), tag_filter, limit, order_by, descending, for_update != 0).await
+                session.fetch_all(category.as_deref(), tag_filter, tag_mode, limit, order_by, descending, for_update != 0).await
             }.await;
             cb.resolve(result);
         });
@@ -1167,9 +1194,13 @@ pub extern "C" fn askar_session_remove_key(
         spawn_ok(async move {
             let result = async {
                 let mut session = FFI_SESSIONS.borrow(handle).await?;
-                session.remove_key(
+                if session.remove_key(
                     &name,
-                ).await
+                ).await? {
+                    Ok(())
+                } else {
+                    Err(err_msg!(NotFound, "Key entry not found"))
+                }
             }.await;
             cb.resolve(result);
         });
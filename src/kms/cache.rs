@@ -0,0 +1,206 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::KeyEntry;
+
+/// A bounded in-memory cache of key entries, shared between the sessions
+/// of a store
+///
+/// Cached entries include the decrypted key data, which remains in process
+/// memory until the entry is evicted or invalidated. The key data is zeroized
+/// when the entry is dropped.
+#[derive(Debug)]
+pub(crate) struct KeyCache {
+    capacity: usize,
+    inner: Mutex<KeyCacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct KeyCacheInner {
+    entries: HashMap<(String, String), (KeyEntry, u64)>,
+    last_used: u64,
+    generation: u64,
+}
+
+impl KeyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(KeyCacheInner::default()),
+        }
+    }
+
+    /// Get the current generation of the cache, which is advanced on every
+    /// invalidation. Pass this value to `insert` to avoid caching an entry
+    /// which was fetched before a concurrent update.
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().generation
+    }
+
+    pub fn get(&self, profile: &str, name: &str) -> Option<KeyEntry> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_used += 1;
+        let last_used = inner.last_used;
+        inner
+            .entries
+            .get_mut(&(profile.to_string(), name.to_string()))
+            .map(|(entry, used)| {
+                *used = last_used;
+                entry.clone()
+            })
+    }
+
+    pub fn insert(&self, profile: &str, entry: KeyEntry, generation: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation != generation {
+            return;
+        }
+        let key = (profile.to_string(), entry.name.clone());
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= self.capacity {
+            // evict the least recently used entry
+            if let Some(evict) = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone())
+            {
+                inner.entries.remove(&evict);
+            }
+        }
+        inner.last_used += 1;
+        let last_used = inner.last_used;
+        inner.entries.insert(key, (entry, last_used));
+    }
+
+    pub fn invalidate(&self, profile: &str, name: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner
+            .entries
+            .remove(&(profile.to_string(), name.to_string()));
+    }
+
+    pub fn invalidate_profile(&self, profile: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.entries.retain(|(p, _), _| p != profile);
+    }
+}
+
+/// A view of the key cache for a single session
+///
+/// Transactions do not read from or populate the cache, as they may observe
+/// uncommitted changes. Entries updated within a transaction are invalidated
+/// immediately and again on commit, so that a concurrent session cannot
+/// leave a stale entry behind.
+#[derive(Debug)]
+pub(crate) struct SessionKeyCache {
+    cache: Arc<KeyCache>,
+    profile: String,
+    transaction: bool,
+    pending: Vec<String>,
+}
+
+impl SessionKeyCache {
+    pub fn new(cache: Arc<KeyCache>, profile: String, transaction: bool) -> Self {
+        Self {
+            cache,
+            profile,
+            transaction,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<KeyEntry> {
+        if self.transaction {
+            None
+        } else {
+            self.cache.get(&self.profile, name)
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.cache.generation()
+    }
+
+    pub fn insert(&self, entry: KeyEntry, generation: u64) {
+        if !self.transaction {
+            self.cache.insert(&self.profile, entry, generation);
+        }
+    }
+
+    pub fn invalidate(&mut self, name: &str) {
+        self.cache.invalidate(&self.profile, name);
+        if self.transaction {
+            self.pending.push(name.to_string());
+        }
+    }
+
+    /// Invalidate an entry again once an update has been written. A
+    /// concurrent session may have fetched the previous row after the
+    /// initial invalidation and cached it before the write landed. Within a
+    /// transaction, this is deferred until commit.
+    pub fn written(&self, name: &str) {
+        if !self.transaction {
+            self.cache.invalidate(&self.profile, name);
+        }
+    }
+
+    pub fn commit(&mut self) {
+        for name in self.pending.drain(..) {
+            self.cache.invalidate(&self.profile, &name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kms::KeyParams;
+
+    fn key_entry(name: &str, metadata: &str) -> KeyEntry {
+        KeyEntry {
+            name: name.to_string(),
+            params: KeyParams {
+                metadata: Some(metadata.to_string()),
+                reference: None,
+                data: None,
+            },
+            alg: None,
+            thumbprints: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn invalidate_after_write() {
+        let cache = Arc::new(KeyCache::new(4));
+        let mut writer = SessionKeyCache::new(cache.clone(), "p".to_string(), false);
+        let reader = SessionKeyCache::new(cache.clone(), "p".to_string(), false);
+
+        // the reader fetches the previous row after the writer's initial
+        // invalidation, but caches it after the write has landed
+        writer.invalidate("key");
+        let generation = reader.generation();
+        let stale = key_entry("key", "old");
+        reader.insert(stale, generation);
+        assert!(reader.get("key").is_some());
+        writer.written("key");
+        assert!(reader.get("key").is_none());
+
+        // within a transaction the entry is invalidated on commit
+        let mut txn = SessionKeyCache::new(cache.clone(), "p".to_string(), true);
+        txn.invalidate("key");
+        let generation = reader.generation();
+        reader.insert(key_entry("key", "old"), generation);
+        txn.written("key");
+        assert!(reader.get("key").is_some());
+        txn.commit();
+        assert!(reader.get("key").is_none());
+    }
+}
@@ -1,7 +1,10 @@
-use super::local_key::LocalKey;
+use super::{
+    enc::{Encrypted, ToDecrypt},
+    local_key::LocalKey,
+};
 use crate::{
     crypto::{
-        alg::{x25519::X25519KeyPair, KeyAlg},
+        alg::{x25519::X25519KeyPair, AesTypes, KeyAlg},
         buffer::SecretBytes,
         encrypt::crypto_box::{
             crypto_box as nacl_box, crypto_box_open as nacl_box_open,
@@ -106,3 +109,53 @@ pub fn derive_key_ecdh_es(
     let derive = EcdhEs::new(ephem_key, recip_key, alg_id, apu, apv, receive);
     LocalKey::from_key_derivation(key_alg, derive)
 }
+
+#[inline]
+fn check_key_wrap(kek: &LocalKey) -> Result<(), Error> {
+    match kek.algorithm() {
+        KeyAlg::Aes(AesTypes::A128Kw) | KeyAlg::Aes(AesTypes::A256Kw) => Ok(()),
+        _ => Err(err_msg!(Input, "AES key wrap key required")),
+    }
+}
+
+/// Encrypt a message under a new random data encryption key (DEK), wrapping
+/// the DEK with an AES key wrap key encryption key (KEK).
+///
+/// The DEK is generated using `dek_alg`, which must be an AEAD key type using
+/// a nonce (not an AES key wrap type), and is used once to encrypt the message
+/// with a random nonce. Returns a tuple of the wrapped DEK and the encrypted
+/// message.
+pub fn envelope_encrypt(
+    kek: &LocalKey,
+    dek_alg: KeyAlg,
+    message: &[u8],
+    aad: &[u8],
+) -> Result<(Encrypted, Encrypted), Error> {
+    check_key_wrap(kek)?;
+    let dek = LocalKey::generate_with_rng(dek_alg, true)?;
+    // key wrap algorithms report AEAD parameters but do not accept a nonce
+    if dek.aead_params()?.nonce_length == 0 {
+        return Err(err_msg!(
+            Input,
+            "AEAD data encryption key algorithm with a nonce required"
+        ));
+    }
+    let wrapped_dek = kek.wrap_key(&dek, &[])?;
+    let encrypted = dek.aead_encrypt(message, &[], aad)?;
+    Ok((wrapped_dek, encrypted))
+}
+
+/// Unwrap a data encryption key (DEK) with an AES key wrap key encryption key
+/// (KEK) and decrypt a message produced by `envelope_encrypt`.
+pub fn envelope_decrypt<'d>(
+    kek: &LocalKey,
+    dek_alg: KeyAlg,
+    wrapped_dek: &[u8],
+    ciphertext: impl Into<ToDecrypt<'d>>,
+    nonce: &[u8],
+    aad: &[u8],
+) -> Result<SecretBytes, Error> {
+    check_key_wrap(kek)?;
+    let dek = kek.unwrap_key(dek_alg, wrapped_dek, &[])?;
+    dek.aead_decrypt(ciphertext.into(), nonce, aad)
+}
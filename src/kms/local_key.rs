@@ -10,13 +10,19 @@ pub use crate::crypto::{
 };
 use crate::{
     crypto::{
-        alg::{bls::BlsKeyGen, AnyKey, AnyKeyCreate},
+        alg::{
+            bls::{BlsKeyGen, BlsKeyGenVersion},
+            AnyKey, AnyKeyCreate,
+        },
         encrypt::KeyAeadInPlace,
-        jwk::{FromJwk, ToJwk},
+        jwk::{
+            encrypted::{from_jwk_encrypted, to_jwk_encrypted},
+            FromJwk, ToJwk,
+        },
         kdf::{KeyDerivation, KeyExchange},
         random::{fill_random, RandomDet},
         repr::{ToPublicBytes, ToSecretBytes},
-        sign::{KeySigVerify, KeySign, SignatureType},
+        sign::{verify_signature_any, KeySigVerify, KeySign, SignatureType},
         Error as CryptoError,
     },
     error::Error,
@@ -40,6 +46,10 @@ impl LocalKey {
     pub fn from_seed(alg: KeyAlg, seed: &[u8], method: Option<&str>) -> Result<Self, Error> {
         let inner = match method {
             Some("bls_keygen") => Box::<AnyKey>::generate_with_rng(alg, BlsKeyGen::new(seed)?)?,
+            Some("bls_keygen_draft2") => Box::<AnyKey>::generate_with_rng(
+                alg,
+                BlsKeyGen::new_with_version(seed, BlsKeyGenVersion::Draft2)?,
+            )?,
             None | Some("") => Box::<AnyKey>::generate_with_rng(alg, RandomDet::new(seed))?,
             _ => {
                 return Err(err_msg!(
@@ -81,6 +91,16 @@ impl LocalKey {
         })
     }
 
+    /// Import a key or keypair from a password-protected JWK, encrypted as
+    /// a JWE using PBES2-HS512+A256KW and A256GCM
+    pub fn from_jwk_encrypted(jwe: &str, password: &str) -> Result<Self, Error> {
+        let inner = from_jwk_encrypted::<Box<AnyKey>>(jwe, password.as_bytes())?;
+        Ok(Self {
+            inner,
+            ephemeral: false,
+        })
+    }
+
     /// Export the raw bytes of the public key
     pub fn to_public_bytes(&self) -> Result<SecretBytes, Error> {
         Ok(self.inner.to_public_bytes()?)
@@ -96,10 +116,29 @@ impl LocalKey {
     }
 
     /// Export the raw bytes of the private key
+    ///
+    /// The returned buffer is zeroized on drop.
     pub fn to_secret_bytes(&self) -> Result<SecretBytes, Error> {
         Ok(self.inner.to_secret_bytes()?)
     }
 
+    /// Determine whether the key has a secret key, as required for signing
+    /// and key exchange
+    pub fn has_secret(&self) -> bool {
+        self.inner.has_secret()
+    }
+
+    /// Compare the key material of two keys in constant time. The secret keys
+    /// are compared when both are present, otherwise the public keys.
+    pub fn secret_eq(&self, other: &LocalKey) -> bool {
+        self.inner.secret_eq(&other.inner)
+    }
+
+    /// Compare the public keys of two keys in constant time
+    pub fn public_eq(&self, other: &LocalKey) -> bool {
+        self.inner.public_eq(&other.inner)
+    }
+
     /// Derive a new key from a Diffie-Hellman exchange between this keypair and a public key
     pub fn to_key_exchange(&self, alg: KeyAlg, pk: &LocalKey) -> Result<Self, Error> {
         let inner = Box::<AnyKey>::from_key_exchange(alg, &*self.inner, &*pk.inner)?;
@@ -139,6 +178,12 @@ impl LocalKey {
         Ok(self.inner.to_jwk_secret(None)?)
     }
 
+    /// Get the secret JWK representation for this key or keypair, encrypted
+    /// under a password as a JWE using PBES2-HS512+A256KW and A256GCM
+    pub fn to_jwk_encrypted(&self, password: &str) -> Result<String, Error> {
+        Ok(to_jwk_encrypted(&*self.inner, password.as_bytes())?)
+    }
+
     /// Get the JWK thumbprint for this key or keypair
     pub fn to_jwk_thumbprint(&self, alg: Option<KeyAlg>) -> Result<String, Error> {
         Ok(self.inner.to_jwk_thumbprint(alg)?)
@@ -239,11 +284,21 @@ impl LocalKey {
         signature: &[u8],
         sig_type: Option<&str>,
     ) -> Result<bool, Error> {
-        Ok(self.inner.verify_signature(
-            message,
-            signature,
-            sig_type.map(SignatureType::from_str).transpose()?,
-        )?)
+        verify_signature_with_key(&self.inner, message, signature, sig_type)
+    }
+
+    /// Verify a message signature without a known signature type, returning
+    /// the matching signature type if the signature is valid
+    ///
+    /// Pre-hashed signature types are tried after those over the full message.
+    /// This should only be used when the signature type is genuinely unknown,
+    /// see [`verify_signature_any`](crate::crypto::sign::verify_signature_any).
+    pub fn verify_signature_any(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<Option<SignatureType>, Error> {
+        Ok(verify_signature_any(&*self.inner, message, signature)?)
     }
 
     /// Wrap another key using this key
@@ -281,3 +336,34 @@ impl KeyExchange for LocalKey {
         self.inner.write_key_exchange(&other.inner, out)
     }
 }
+
+/// Verify a message signature with a key in JWK format
+///
+/// Returns `false` if the signature is not valid, and an error only if the key
+/// cannot be parsed or does not support the signature type.
+pub fn verify_signature_with_jwk(
+    jwk: &str,
+    message: &[u8],
+    signature: &[u8],
+    sig_type: Option<&str>,
+) -> Result<bool, Error> {
+    let key = Box::<AnyKey>::from_jwk(jwk)?;
+    verify_signature_with_key(&key, message, signature, sig_type)
+}
+
+/// Verify a message signature with a public verification key
+///
+/// Returns `false` if the signature is not valid, and an error only if the key
+/// does not support the signature type.
+pub fn verify_signature_with_key(
+    key: &AnyKey,
+    message: &[u8],
+    signature: &[u8],
+    sig_type: Option<&str>,
+) -> Result<bool, Error> {
+    Ok(key.verify_signature(
+        message,
+        signature,
+        sig_type.map(SignatureType::from_str).transpose()?,
+    )?)
+}
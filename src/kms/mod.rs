@@ -9,20 +9,25 @@ use zeroize::Zeroize;
 
 use crate::error::Error;
 
+mod cache;
+pub(crate) use self::cache::{KeyCache, SessionKeyCache};
+
 mod enc;
 pub use self::enc::{Encrypted, SecretBytes, ToDecrypt};
 
 mod envelope;
 pub use self::envelope::{
     crypto_box, crypto_box_open, crypto_box_random_nonce, crypto_box_seal, crypto_box_seal_open,
-    derive_key_ecdh_1pu, derive_key_ecdh_es,
+    derive_key_ecdh_1pu, derive_key_ecdh_es, envelope_decrypt, envelope_encrypt,
 };
 
 mod entry;
 pub use self::entry::{KeyEntry, KeyParams, KeyReference};
 
 mod local_key;
-pub use self::local_key::{KeyAlg, KeyBackend, LocalKey};
+pub use self::local_key::{
+    verify_signature_with_jwk, verify_signature_with_key, KeyAlg, KeyBackend, LocalKey,
+};
 
 /// Supported categories of KMS entries
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Zeroize)]
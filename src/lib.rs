@@ -21,10 +21,17 @@ pub use askar_storage as storage;
 #[doc(hidden)]
 pub use askar_storage::future;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 #[cfg(feature = "ffi")]
 mod ffi;
 
 pub mod kms;
+pub use kms::{verify_signature_with_jwk, verify_signature_with_key};
 
 mod store;
-pub use store::{entry, PassKey, Session, Store, StoreKeyMethod};
+pub use store::{
+    entry, BackendKind, KeyScan, PassKey, Session, Store, StoreKeyMethod, StoreValidation,
+    ValueStream,
+};
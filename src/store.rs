@@ -1,25 +1,48 @@
-use askar_storage::backend::{copy_profile, OrderBy};
+use std::sync::Arc;
+
+use askar_storage::backend::{copy_profile, OrderBy, ProfileStats, SCHEMA_VERSION};
 
 use crate::{
-    error::Error,
-    kms::{KeyEntry, KeyParams, KeyReference, KmsCategory, LocalKey},
+    crypto::{buffer::HexRepr, random::fill_random},
+    error::{Error, ErrorKind},
+    kms::{
+        KeyAlg, KeyCache, KeyEntry, KeyParams, KeyReference, KmsCategory, LocalKey, SecretBytes,
+        SessionKeyCache,
+    },
     storage::{
         any::{AnyBackend, AnyBackendSession},
         backend::{Backend, BackendSession, ManageBackend},
-        entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter},
-        generate_raw_store_key,
+        entry::{Entry, EntryKind, EntryOperation, EntryTag, Scan, TagFilter, TagMode},
+        generate_raw_store_key, TagHasher,
     },
 };
 
-pub use crate::storage::{entry, PassKey, StoreKeyMethod};
+pub use crate::storage::{entry, BackendKind, PassKey, StoreKeyMethod, StoreValidation};
 
 #[derive(Debug, Clone)]
 /// An instance of an opened store
-pub struct Store(AnyBackend);
+pub struct Store(AnyBackend, Option<Arc<KeyCache>>);
 
 impl Store {
     pub(crate) fn new(inner: AnyBackend) -> Self {
-        Self(inner)
+        Self(inner, None)
+    }
+
+    /// Enable an in-memory cache of up to `capacity` stored keys
+    ///
+    /// The cache is shared by all clones of the store instance, and is used by
+    /// `Session::fetch_key` and the methods which load a stored key, outside
+    /// of transactions. Entries are invalidated by `Session::update_key` and
+    /// `Session::remove_key`, and the least recently used entry is evicted
+    /// when the cache is full.
+    ///
+    /// Cached keys are held decrypted in process memory, and are only zeroized
+    /// once evicted or invalidated. Changes made to the stored keys by other
+    /// store instances or processes are not detected, and keys stored with
+    /// an expiry time may remain cached after they expire.
+    pub fn with_key_cache(mut self, capacity: usize) -> Self {
+        self.1 = Some(Arc::new(KeyCache::new(capacity)));
+        self
     }
 
     /// Provision a new store instance using a database URL
@@ -36,6 +59,26 @@ impl Store {
         Ok(Self::new(backend))
     }
 
+    /// Open an existing store instance using a database URL, or provision a
+    /// new store if none exists
+    ///
+    /// The returned flag is `true` if a new store was provisioned. An existing
+    /// store is only replaced when `recreate` is set. When several instances
+    /// call this method concurrently for the same store, only one provisions
+    /// it and the others open the new store.
+    pub async fn open_or_provision(
+        db_url: &str,
+        key_method: StoreKeyMethod,
+        pass_key: PassKey<'_>,
+        profile: Option<String>,
+        recreate: bool,
+    ) -> Result<(Self, bool), Error> {
+        let (backend, created) = db_url
+            .open_or_provision_backend(key_method, pass_key, profile, recreate)
+            .await?;
+        Ok((Self::new(backend), created))
+    }
+
     /// Open a store instance from a database URL
     pub async fn open(
         db_url: &str,
@@ -52,11 +95,28 @@ impl Store {
         Ok(db_url.remove_backend().await?)
     }
 
+    /// Check that a store could be provisioned using a database URL, without
+    /// creating the database or the store schema
+    ///
+    /// The URL is parsed and the backend must be supported. The database
+    /// location must be reachable: for Postgres, the server must accept a
+    /// connection, and for SQLite, the database file or its directory must be
+    /// writable. The result indicates whether provisioning would create the
+    /// database and whether an existing store was found.
+    pub async fn validate_uri(db_url: &str) -> Result<StoreValidation, Error> {
+        Ok(db_url.validate_backend().await?)
+    }
+
     /// Generate a new raw store key
     pub fn new_raw_key(seed: Option<&[u8]>) -> Result<PassKey<'static>, Error> {
         Ok(generate_raw_store_key(seed)?)
     }
 
+    /// Get the database engine used by the store
+    pub fn backend_kind(&self) -> BackendKind {
+        self.0.kind()
+    }
+
     /// Get the default profile name used when starting a scan or a session
     pub fn get_active_profile(&self) -> String {
         self.0.get_active_profile()
@@ -68,10 +128,34 @@ impl Store {
     }
 
     /// Set the default profile name used when opening the Store
+    ///
+    /// The name is persisted with the store, so that subsequent calls to `open`
+    /// without a profile name select it as the active profile. The active
+    /// profile of this instance is not changed. A `NotFound` error is returned
+    /// if the profile does not exist.
     pub async fn set_default_profile(&self, profile: String) -> Result<(), Error> {
         Ok(self.0.set_default_profile(profile).await?)
     }
 
+    /// Get the database schema version of the store
+    pub async fn schema_version(&self) -> Result<u32, Error> {
+        Ok(self.0.schema_version().await?)
+    }
+
+    /// Determine whether the store uses an older database schema version
+    ///
+    /// Stores are never upgraded automatically when opened. A store which
+    /// needs migration must be upgraded using `migrate` before use, while a
+    /// store using a newer schema version than supported cannot be opened.
+    pub async fn needs_migration(&self) -> Result<bool, Error> {
+        Ok(self.schema_version().await? < SCHEMA_VERSION)
+    }
+
+    /// Upgrade the database schema of the store to the current version
+    pub async fn migrate(&self) -> Result<(), Error> {
+        Ok(self.0.migrate().await?)
+    }
+
     /// Replace the wrapping key on a store
     pub async fn rekey(
         &mut self,
@@ -112,18 +196,93 @@ impl Store {
     }
 
     /// Create a new profile with the given profile name
+    ///
+    /// A new profile key is generated for the profile. If no name is given then
+    /// a random one is assigned. An error is returned if a profile with the same
+    /// name already exists.
     pub async fn create_profile(&self, name: Option<String>) -> Result<String, Error> {
         Ok(self.0.create_profile(name).await?)
     }
 
+    /// Create a new profile which stores the names of entries in the given
+    /// categories as plaintext
+    ///
+    /// Entry values and tags remain encrypted, but the names of entries in
+    /// these categories are visible to anyone with access to the database.
+    /// This allows a scan of one of the categories to be filtered by a name
+    /// prefix. Entry names in all other categories are encrypted, and the
+    /// categories cannot be changed after the profile is created. The profile
+    /// cannot be opened by earlier versions of this library.
+    pub async fn create_profile_with_plaintext_names(
+        &self,
+        name: Option<String>,
+        categories: Vec<String>,
+    ) -> Result<String, Error> {
+        Ok(self
+            .0
+            .create_profile_with_plaintext_names(name, categories)
+            .await?)
+    }
+
+    /// Get the categories whose entry names are stored as plaintext for a
+    /// profile, defaulting to the active profile
+    pub async fn plaintext_name_categories(
+        &self,
+        profile: Option<String>,
+    ) -> Result<Vec<String>, Error> {
+        Ok(self.0.plaintext_name_categories(profile).await?)
+    }
+
     /// Get the details of all store profiles
+    ///
+    /// A newly provisioned store contains a single default profile, in which
+    /// case only its name is returned.
     pub async fn list_profiles(&self) -> Result<Vec<String>, Error> {
         Ok(self.0.list_profiles().await?)
     }
 
-    /// Remove an existing profile with the given profile namestore.r
-    pub async fn remove_profile(&self, name: String) -> Result<bool, Error> {
-        Ok(self.0.remove_profile(name).await?)
+    /// Get the number of item and key entries in each category for a profile
+    ///
+    /// When no profile name is given, the active profile is used, which for a
+    /// store with a single profile is the default profile. Expired entries are
+    /// not counted.
+    pub async fn profile_stats(&self, profile: Option<String>) -> Result<ProfileStats, Error> {
+        Ok(self.0.profile_stats(profile).await?)
+    }
+
+    /// Create an index on the values of a plaintext tag for a profile
+    ///
+    /// When no profile name is given, the active profile is used. The tag name
+    /// may be given with or without the `~` prefix, and the index applies to
+    /// entries in all categories. The index is persisted in the database and is
+    /// picked up automatically by WQL queries on the tag. Encrypted tags are not
+    /// supported, as only equality matches can be performed on their values.
+    pub async fn create_tag_index(
+        &self,
+        profile: Option<String>,
+        tag_name: &str,
+    ) -> Result<(), Error> {
+        Ok(self
+            .0
+            .create_tag_index(profile, tag_name.to_string())
+            .await?)
+    }
+
+    /// Remove an existing profile with the given profile name
+    ///
+    /// All entries and keys belonging to the profile are removed along with it.
+    /// Removing the active or default profile is not permitted, and a
+    /// `NotFound` error is returned if the profile does not exist.
+    pub async fn remove_profile(&self, name: String) -> Result<(), Error> {
+        let removed = self.0.remove_profile(name.clone()).await?;
+        if let Some(cache) = self.1.as_ref() {
+            cache.invalidate_profile(&name);
+        }
+        if removed {
+            Ok(())
+        } else {
+            Err(err_msg!(NotFound, "Profile not found"))
+        }
     }
 
     /// Change the name of an existing profile
@@ -132,17 +291,26 @@ impl Store {
         from_profile: String,
         to_profile: String,
     ) -> Result<bool, Error> {
+        if let Some(cache) = self.1.as_ref() {
+            cache.invalidate_profile(&from_profile);
+        }
         Ok(self.0.rename_profile(from_profile, to_profile).await?)
     }
     /// Create a new scan instance against the store
     ///
-    /// The result will keep an open connection to the backend until it is consumed
+    /// The result will keep an open connection to the backend until it is consumed.
+    /// A `name_prefix` may be provided along with a `category` to restrict the
+    /// entries by name. This requires the entry names in the category to be
+    /// stored as plaintext, see `Store::create_profile_with_plaintext_names`.
+    /// The `tag_mode` selects which tags are loaded for each entry.
     #[allow(clippy::too_many_arguments)]
     pub async fn scan(
         &self,
         profile: Option<String>,
         category: Option<String>,
+        name_prefix: Option<String>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         offset: Option<i64>,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
@@ -154,7 +322,9 @@ impl Store {
                 profile,
                 Some(EntryKind::Item),
                 category,
+                name_prefix,
                 tag_filter,
+                tag_mode,
                 offset,
                 limit,
                 order_by,
@@ -163,9 +333,43 @@ impl Store {
             .await?)
     }
 
+    /// Create a new scan instance over the keys in the store
+    ///
+    /// Keys may be filtered by algorithm and by a filter on their user-defined
+    /// tags. The `offset` and `limit` are applied to the matching keys in the
+    /// same way as for `Store::scan`, and the result will keep an open
+    /// connection to the backend until it is consumed.
+    pub async fn scan_keys(
+        &self,
+        profile: Option<String>,
+        algorithm: Option<KeyAlg>,
+        tag_filter: Option<TagFilter>,
+        offset: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<KeyScan, Error> {
+        let tag_filter = key_tag_filter(algorithm.as_ref().map(KeyAlg::as_str), None, tag_filter);
+        let scan = self
+            .0
+            .scan(
+                profile,
+                Some(EntryKind::Kms),
+                Some(KmsCategory::CryptoKey.as_str().to_string()),
+                None,
+                tag_filter,
+                TagMode::All,
+                offset,
+                limit,
+                None,
+                false,
+            )
+            .await?;
+        Ok(KeyScan(scan))
+    }
+
     /// Create a new session against the store
     pub async fn session(&self, profile: Option<String>) -> Result<Session, Error> {
-        let mut sess = Session::new(self.0.session(profile, false)?);
+        let mut sess = Session::new(self.0.session(profile.clone(), false)?)
+            .with_key_cache(self.session_key_cache(profile, false));
         if let Err(e) = sess.ping().await {
             sess.0.close(false).await?;
             Err(e)
@@ -176,7 +380,8 @@ impl Store {
 
     /// Create a new transaction session against the store
     pub async fn transaction(&self, profile: Option<String>) -> Result<Session, Error> {
-        let mut txn = Session::new(self.0.session(profile, true)?);
+        let mut txn = Session::new(self.0.session(profile.clone(), true)?)
+            .with_key_cache(self.session_key_cache(profile, true));
         if let Err(e) = txn.ping().await {
             txn.0.close(false).await?;
             Err(e)
@@ -189,6 +394,17 @@ impl Store {
     pub async fn close(self) -> Result<(), Error> {
         Ok(self.0.close().await?)
     }
+
+    fn session_key_cache(
+        &self,
+        profile: Option<String>,
+        transaction: bool,
+    ) -> Option<SessionKeyCache> {
+        self.1.as_ref().map(|cache| {
+            let profile = profile.unwrap_or_else(|| self.0.get_active_profile());
+            SessionKeyCache::new(cache.clone(), profile, transaction)
+        })
+    }
 }
 
 impl From<AnyBackend> for Store {
@@ -197,13 +413,79 @@ impl From<AnyBackend> for Store {
     }
 }
 
+/// An active scan over the keys in a store
+#[derive(Debug)]
+pub struct KeyScan(Scan<'static, Entry>);
+
+impl KeyScan {
+    /// Fetch the next set of key entries
+    pub async fn fetch_next(&mut self) -> Result<Option<Vec<KeyEntry>>, Error> {
+        if let Some(rows) = self.0.fetch_next().await? {
+            let mut entries = Vec::with_capacity(rows.len());
+            for row in rows {
+                entries.push(KeyEntry::from_entry(row)?)
+            }
+            Ok(Some(entries))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A reader over the decrypted value of a record
+///
+/// The supported backends store each value as a single encrypted message, so
+/// the stream currently holds the whole decrypted value and returns it in
+/// chunks. Backends able to read values in parts may be supported by the same
+/// interface.
+#[derive(Debug)]
+pub struct ValueStream {
+    value: SecretBytes,
+    offset: usize,
+}
+
+impl ValueStream {
+    /// The maximum length of each chunk returned by `fetch_next`
+    pub const CHUNK_SIZE: usize = 64 * 1024;
+
+    fn new(value: SecretBytes) -> Self {
+        Self { value, offset: 0 }
+    }
+
+    /// Get the total length of the value in bytes
+    pub fn len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Determine if the value is empty
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Fetch the next chunk of the value, or `None` once it has been fully read
+    pub async fn fetch_next(&mut self) -> Result<Option<SecretBytes>, Error> {
+        if self.offset >= self.value.len() {
+            return Ok(None);
+        }
+        let end = self.value.len().min(self.offset + Self::CHUNK_SIZE);
+        let chunk = SecretBytes::from_slice(&self.value[self.offset..end]);
+        self.offset = end;
+        Ok(Some(chunk))
+    }
+}
+
 /// An active connection to the store backend
 #[derive(Debug)]
-pub struct Session(AnyBackendSession);
+pub struct Session(AnyBackendSession, Option<SessionKeyCache>);
 
 impl Session {
     pub(crate) fn new(inner: AnyBackendSession) -> Self {
-        Self(inner)
+        Self(inner, None)
+    }
+
+    pub(crate) fn with_key_cache(mut self, cache: Option<SessionKeyCache>) -> Self {
+        self.1 = cache;
+        self
     }
 
     /// Count the number of entries for a given record category
@@ -218,31 +500,70 @@ impl Session {
             .await?)
     }
 
+    /// Estimate the number of entries for a given record category
+    ///
+    /// This is cheaper than `count` for large categories, but the result is
+    /// only an approximation and may be substantially off. For PostgreSQL the
+    /// query planner's row estimate is returned, while SQLite performs an
+    /// exact count.
+    pub async fn count_estimate(
+        &mut self,
+        category: Option<&str>,
+        tag_filter: Option<TagFilter>,
+    ) -> Result<i64, Error> {
+        Ok(self
+            .0
+            .count_estimate(Some(EntryKind::Item), category, tag_filter)
+            .await?)
+    }
+
     /// Retrieve the current record at `(category, name)`.
     ///
-    /// Specify `for_update` when in a transaction to create an update lock on the
+    /// The `tag_mode` selects which tags are loaded for the record. Specify
+    /// `for_update` when in a transaction to create an update lock on the
     /// associated record, if supported by the store backend
     pub async fn fetch(
         &mut self,
         category: &str,
         name: &str,
+        tag_mode: TagMode,
         for_update: bool,
     ) -> Result<Option<Entry>, Error> {
         Ok(self
             .0
-            .fetch(EntryKind::Item, category, name, for_update)
+            .fetch(EntryKind::Item, category, name, tag_mode, for_update)
             .await?)
     }
 
+    /// Retrieve the value of the current record at `(category, name)` as a
+    /// stream of chunks.
+    ///
+    /// Record values are encrypted as a single message, so the backend reads
+    /// and decrypts the whole value before the first chunk is returned, but
+    /// callers may process large values incrementally.
+    pub async fn fetch_value_stream(
+        &mut self,
+        category: &str,
+        name: &str,
+    ) -> Result<Option<ValueStream>, Error> {
+        Ok(self
+            .0
+            .fetch(EntryKind::Item, category, name, TagMode::None, false)
+            .await?
+            .map(|entry| ValueStream::new(entry.value)))
+    }
+
     /// Retrieve all records matching the given `category` and `tag_filter`.
     ///
     /// Unlike `Store::scan`, this method may be used within a transaction. It should
     /// not be used for very large result sets due to correspondingly large memory
-    /// requirements
+    /// requirements. The `tag_mode` selects which tags are loaded for each record.
+    #[allow(clippy::too_many_arguments)]
     pub async fn fetch_all(
         &mut self,
         category: Option<&str>,
         tag_filter: Option<TagFilter>,
+        tag_mode: TagMode,
         limit: Option<i64>,
         order_by: Option<OrderBy>,
         descending: bool,
@@ -254,6 +575,7 @@ impl Session {
                 Some(EntryKind::Item),
                 category,
                 tag_filter,
+                tag_mode,
                 limit,
                 order_by,
                 descending,
@@ -324,6 +646,25 @@ impl Session {
             .await?)
     }
 
+    /// Insert a new record, or replace the value and tags of an existing
+    /// record, in a single atomic operation
+    ///
+    /// Returns `true` if a new record was inserted, or `false` if an existing
+    /// record was replaced.
+    pub async fn upsert(
+        &mut self,
+        category: &str,
+        name: &str,
+        value: &[u8],
+        tags: Option<&[EntryTag]>,
+        expiry_ms: Option<i64>,
+    ) -> Result<bool, Error> {
+        Ok(self
+            .0
+            .upsert(EntryKind::Item, category, name, value, tags, expiry_ms)
+            .await?)
+    }
+
     /// Remove all records in the store matching a given `category` and `tag_filter`
     pub async fn remove_all(
         &mut self,
@@ -364,6 +705,12 @@ impl Session {
     }
 
     /// Insert a local key instance into the store
+    ///
+    /// The `tags` are stored alongside the key entry and may be used to find
+    /// the key with `Store::scan_keys` or `Session::fetch_all_keys`. As with
+    /// record tags, encrypted tags support only equality matching, while
+    /// plaintext tags are stored unencrypted and may be used in any tag filter,
+    /// referenced by their name prefixed with `~`.
     pub async fn insert_key(
         &mut self,
         name: &str,
@@ -408,31 +755,137 @@ impl Session {
         Ok(())
     }
 
+    /// Import an externally generated key into the store
+    ///
+    /// The key is stored under the given name, and the resulting name is
+    /// returned. When no name is provided, an identifier is selected in the
+    /// same way as for `Session::create_key`. Keypairs are identified by their
+    /// JWK thumbprint as defined by RFC 7638: the base64url-encoded (unpadded)
+    /// SHA-256 hash of the canonical public JWK. Re-importing the same keypair
+    /// without a name is idempotent, returning the existing identifier and
+    /// leaving the stored metadata and tags unchanged. Symmetric keys are
+    /// assigned a new random identifier on each import.
+    ///
+    /// Keys are validated when the `LocalKey` is created, for example using
+    /// `LocalKey::from_jwk` or `LocalKey::from_secret_bytes`. A `Duplicate`
+    /// error is returned if a different key is already stored under the same
+    /// name.
+    pub async fn import_key(
+        &mut self,
+        name: Option<&str>,
+        key: &LocalKey,
+        metadata: Option<&str>,
+        tags: Option<&[EntryTag]>,
+    ) -> Result<String, Error> {
+        let (name, derived) = match name {
+            Some(name) => (name.to_string(), false),
+            None => (key_ident(key)?, true),
+        };
+        match self
+            .insert_key(&name, key, metadata, None, tags, None)
+            .await
+        {
+            Ok(()) => Ok(name),
+            Err(err) if derived && err.kind() == ErrorKind::Duplicate => {
+                // an entry is stored under the thumbprint of the keypair,
+                // check that it holds the same key
+                match self.fetch_key(&name, false).await? {
+                    Some(existing) if existing.thumbprints.contains(&name) => Ok(name),
+                    _ => Err(err),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Generate and store a new random key for any supported key algorithm
+    ///
+    /// Keypairs are stored under their JWK thumbprint, which is returned along
+    /// with the new key. Symmetric keys have no public component, and their
+    /// thumbprint would be a hash of the secret key value, so a random
+    /// identifier is assigned instead.
+    ///
+    /// Any `tags` are stored with the key entry in the same manner as for
+    /// `Session::insert_key`.
+    ///
+    /// The returned key may be used immediately, without fetching it again
+    /// from the store.
+    pub async fn create_key(
+        &mut self,
+        alg: KeyAlg,
+        metadata: Option<&str>,
+        tags: Option<&[EntryTag]>,
+    ) -> Result<(String, LocalKey), Error> {
+        let key = LocalKey::generate_with_rng(alg, false)?;
+        let name = key_ident(&key)?;
+        self.insert_key(&name, &key, metadata, None, tags, None)
+            .await?;
+        Ok((name, key))
+    }
+
     /// Fetch an existing key from the store
     ///
     /// Specify `for_update` when in a transaction to create an update lock on the
-    /// associated record, if supported by the store backend
+    /// associated record, if supported by the store backend. When the key cache
+    /// is enabled for the store, the entry may be returned from the cache.
     pub async fn fetch_key(
         &mut self,
         name: &str,
         for_update: bool,
     ) -> Result<Option<KeyEntry>, Error> {
-        Ok(
-            if let Some(row) = self
-                .0
-                .fetch(
-                    EntryKind::Kms,
-                    KmsCategory::CryptoKey.as_str(),
-                    name,
-                    for_update,
-                )
-                .await?
-            {
-                Some(KeyEntry::from_entry(row)?)
-            } else {
-                None
-            },
-        )
+        let cache = self.1.as_ref().filter(|_| !for_update);
+        if let Some(entry) = cache.and_then(|cache| cache.get(name)) {
+            return Ok(Some(entry));
+        }
+        let generation = cache.map(SessionKeyCache::generation);
+        let entry = if let Some(row) = self
+            .0
+            .fetch(
+                EntryKind::Kms,
+                KmsCategory::CryptoKey.as_str(),
+                name,
+                TagMode::All,
+                for_update,
+            )
+            .await?
+        {
+            KeyEntry::from_entry(row)?
+        } else {
+            return Ok(None);
+        };
+        if let (Some(cache), Some(generation)) = (cache, generation) {
+            cache.insert(entry.clone(), generation);
+        }
+        Ok(Some(entry))
+    }
+
+    /// Sign a message with an existing key from the store, using the default
+    /// signature type for the key algorithm
+    pub async fn sign_message(&mut self, name: &str, message: &[u8]) -> Result<Vec<u8>, Error> {
+        self.load_local_key(name).await?.sign_message(message, None)
+    }
+
+    /// Sign a message with an existing key from the store, using a specific
+    /// signature type such as `ES256ph` for pre-hashed input
+    ///
+    /// An `Unsupported` error is returned if the signature type is not
+    /// applicable to the key algorithm.
+    pub async fn sign_message_with(
+        &mut self,
+        name: &str,
+        message: &[u8],
+        sig_type: &str,
+    ) -> Result<Vec<u8>, Error> {
+        self.load_local_key(name)
+            .await?
+            .sign_message(message, Some(sig_type))
+    }
+
+    async fn load_local_key(&mut self, name: &str) -> Result<LocalKey, Error> {
+        self.fetch_key(name, false)
+            .await?
+            .ok_or_else(|| err_msg!(NotFound, "Key entry not found"))?
+            .load_local_key()
     }
 
     /// Retrieve all keys matching the given filters.
@@ -444,34 +897,14 @@ impl Session {
         limit: Option<i64>,
         for_update: bool,
     ) -> Result<Vec<KeyEntry>, Error> {
-        let mut query_parts = Vec::with_capacity(3);
-        if let Some(query) = tag_filter.map(|f| f.into_query()) {
-            query_parts.push(TagFilter::from(
-                query
-                    .map_names(|mut k| {
-                        k.replace_range(0..0, "user:");
-                        Result::<_, ()>::Ok(k)
-                    })
-                    .unwrap(),
-            ));
-        }
-        if let Some(algorithm) = algorithm {
-            query_parts.push(TagFilter::is_eq("alg", algorithm));
-        }
-        if let Some(thumbprint) = thumbprint {
-            query_parts.push(TagFilter::is_eq("thumb", thumbprint));
-        }
-        let tag_filter = if query_parts.is_empty() {
-            None
-        } else {
-            Some(TagFilter::all_of(query_parts))
-        };
+        let tag_filter = key_tag_filter(algorithm, thumbprint, tag_filter);
         let rows = self
             .0
             .fetch_all(
                 Some(EntryKind::Kms),
                 Some(KmsCategory::CryptoKey.as_str()),
                 tag_filter,
+                TagMode::All,
                 limit,
                 None,
                 false,
@@ -486,8 +919,13 @@ impl Session {
     }
 
     /// Remove an existing key from the store
-    pub async fn remove_key(&mut self, name: &str) -> Result<(), Error> {
-        Ok(self
+    ///
+    /// Returns `false` if no key was stored under the given name.
+    pub async fn remove_key(&mut self, name: &str) -> Result<bool, Error> {
+        if let Some(cache) = self.1.as_mut() {
+            cache.invalidate(name);
+        }
+        let removed = self
             .0
             .update(
                 EntryKind::Kms,
@@ -498,7 +936,76 @@ impl Session {
                 None,
                 None,
             )
-            .await?)
+            .await;
+        if let Some(cache) = self.1.as_ref() {
+            cache.written(name);
+        }
+        match removed {
+            Ok(()) => Ok(true),
+            Err(err) => {
+                let err = Error::from(err);
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(false)
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    /// Discard the secret key of an existing keypair in the store, retaining
+    /// the public key
+    ///
+    /// The metadata, tags and thumbprints of the key entry are kept, so that a
+    /// rotated key may still be found and used to verify signatures. A
+    /// tombstoned key cannot be used to sign messages or perform a key
+    /// exchange, and an `Input` error is returned if this is attempted. Any
+    /// expiry time set on the key entry is cleared.
+    ///
+    /// An `Unsupported` error is returned for symmetric keys, which have no
+    /// public component.
+    pub async fn tombstone_key(&mut self, name: &str) -> Result<(), Error> {
+        if let Some(cache) = self.1.as_mut() {
+            cache.invalidate(name);
+        }
+        let row = self
+            .0
+            .fetch(
+                EntryKind::Kms,
+                KmsCategory::CryptoKey.as_str(),
+                name,
+                TagMode::All,
+                true,
+            )
+            .await?
+            .ok_or_else(|| err_msg!(NotFound, "Key entry not found"))?;
+
+        let mut params = KeyParams::from_slice(&row.value)?;
+        let key = match params.data.as_ref() {
+            Some(data) if params.reference.is_none() => LocalKey::from_jwk_slice(data)?,
+            _ => return Err(err_msg!(Unsupported, "Only local keys may be tombstoned")),
+        };
+        // symmetric keys are rejected here, having no public key
+        let public = key.to_jwk_public(None)?;
+        params.data = Some(SecretBytes::from(public.into_bytes()));
+        let value = params.to_bytes()?;
+
+        self.0
+            .update(
+                EntryKind::Kms,
+                EntryOperation::Replace,
+                KmsCategory::CryptoKey.as_str(),
+                name,
+                Some(value.as_ref()),
+                Some(row.tags.as_slice()),
+                None,
+            )
+            .await?;
+        if let Some(cache) = self.1.as_ref() {
+            cache.written(name);
+        }
+
+        Ok(())
     }
 
     /// Replace the metadata and tags on an existing key in the store
@@ -509,9 +1016,18 @@ impl Session {
         tags: Option<&[EntryTag]>,
         expiry_ms: Option<i64>,
     ) -> Result<(), Error> {
+        if let Some(cache) = self.1.as_mut() {
+            cache.invalidate(name);
+        }
         let row = self
             .0
-            .fetch(EntryKind::Kms, KmsCategory::CryptoKey.as_str(), name, true)
+            .fetch(
+                EntryKind::Kms,
+                KmsCategory::CryptoKey.as_str(),
+                name,
+                TagMode::All,
+                true,
+            )
             .await?
             .ok_or_else(|| err_msg!(NotFound, "Key entry not found"))?;
 
@@ -542,10 +1058,37 @@ impl Session {
                 expiry_ms,
             )
             .await?;
+        if let Some(cache) = self.1.as_ref() {
+            cache.written(name);
+        }
 
         Ok(())
     }
 
+    /// Encrypt an arbitrary value using the profile key of the session
+    ///
+    /// The result carries a version header and is authenticated, so that it
+    /// may be stored outside of the entry API. Sealed values may only be
+    /// decrypted by a session on the same profile.
+    pub async fn seal(&mut self, value: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.0.seal(value).await?)
+    }
+
+    /// Decrypt a value produced by `seal` using the profile key of the session
+    pub async fn unseal(&mut self, sealed: &[u8]) -> Result<SecretBytes, Error> {
+        Ok(self.0.unseal(sealed).await?)
+    }
+
+    /// Export the hasher for the encrypted tag tokens of the session profile
+    ///
+    /// This allows an external search index to compute the same tokens used
+    /// for equality lookups on encrypted tags, without access to the keys
+    /// protecting entry values. The hasher can be used to confirm guessed tag
+    /// names and values, so it should only be shared with trusted systems.
+    pub async fn export_tag_hasher(&mut self) -> Result<TagHasher, Error> {
+        Ok(self.0.export_tag_hasher().await?)
+    }
+
     /// Test the connection to the store
     pub async fn ping(&mut self) -> Result<(), Error> {
         Ok(self.0.ping().await?)
@@ -553,7 +1096,11 @@ impl Session {
 
     /// Commit the pending transaction
     pub async fn commit(mut self) -> Result<(), Error> {
-        Ok(self.0.close(true).await?)
+        self.0.close(true).await?;
+        if let Some(cache) = self.1.as_mut() {
+            cache.commit();
+        }
+        Ok(())
     }
 
     /// Roll back the pending transaction
@@ -561,3 +1108,46 @@ impl Session {
         Ok(self.0.close(false).await?)
     }
 }
+
+/// Select an identifier for a key stored without a name. Keypairs use their
+/// JWK thumbprint, while symmetric keys are assigned a random identifier.
+fn key_ident(key: &LocalKey) -> Result<String, Error> {
+    if key.algorithm().public_key_length().is_some() {
+        key.to_jwk_thumbprint(None)
+    } else {
+        let mut ident = [0u8; 32];
+        fill_random(&mut ident);
+        Ok(HexRepr(ident).to_string())
+    }
+}
+
+fn key_tag_filter(
+    algorithm: Option<&str>,
+    thumbprint: Option<&str>,
+    tag_filter: Option<TagFilter>,
+) -> Option<TagFilter> {
+    let mut query_parts = Vec::with_capacity(3);
+    if let Some(query) = tag_filter.map(|f| f.into_query()) {
+        query_parts.push(TagFilter::from(
+            query
+                .map_names(|mut k| {
+                    // keep the plaintext tag prefix in front of the user namespace
+                    let pos = usize::from(k.starts_with('~'));
+                    k.replace_range(pos..pos, "user:");
+                    Result::<_, ()>::Ok(k)
+                })
+                .unwrap(),
+        ));
+    }
+    if let Some(algorithm) = algorithm {
+        query_parts.push(TagFilter::is_eq("alg", algorithm));
+    }
+    if let Some(thumbprint) = thumbprint {
+        query_parts.push(TagFilter::is_eq("thumb", thumbprint));
+    }
+    if query_parts.is_empty() {
+        None
+    } else {
+        Some(TagFilter::all_of(query_parts))
+    }
+}
@@ -1,6 +1,13 @@
 #![allow(clippy::bool_assert_comparison)]
 
-use aries_askar::kms::{KeyAlg, LocalKey};
+use aries_askar::{
+    crypto::{
+        alg::{AesTypes, Chacha20Types, EcCurves},
+        sign::SignatureType,
+    },
+    kms::{envelope_decrypt, envelope_encrypt, KeyAlg, LocalKey},
+    verify_signature_with_jwk,
+};
 
 const ERR_CREATE_KEYPAIR: &str = "Error creating keypair";
 const ERR_SIGN: &str = "Error signing message";
@@ -50,3 +57,118 @@ pub fn localkey_sign_verify() {
         true
     );
 }
+
+#[test]
+pub fn verify_signature_jwk() {
+    let keypair = LocalKey::generate_with_rng(KeyAlg::Ed25519, true).expect(ERR_CREATE_KEYPAIR);
+    let jwk = keypair.to_jwk_public(None).expect("Error encoding JWK");
+
+    let message = b"message".to_vec();
+    let sig = keypair.sign_message(&message, None).expect(ERR_SIGN);
+
+    assert_eq!(
+        verify_signature_with_jwk(&jwk, &message, &sig, None).expect(ERR_VERIFY),
+        true
+    );
+    assert_eq!(
+        verify_signature_with_jwk(&jwk, b"bad input", &sig, None).expect(ERR_VERIFY),
+        false
+    );
+    assert_eq!(
+        verify_signature_with_jwk(&jwk, &message, b"bad sig", None).expect(ERR_VERIFY),
+        false
+    );
+    assert_eq!(
+        verify_signature_with_jwk("{}", &message, &sig, None).is_err(),
+        true
+    );
+}
+
+#[test]
+pub fn envelope_encrypt_decrypt() {
+    let kek =
+        LocalKey::generate_with_rng(KeyAlg::Aes(AesTypes::A256Kw), true).expect(ERR_CREATE_KEYPAIR);
+    let message = b"message";
+    let aad = b"context";
+
+    for dek_alg in [
+        KeyAlg::Aes(AesTypes::A256Gcm),
+        KeyAlg::Chacha20(Chacha20Types::XC20P),
+    ] {
+        let (wrapped_dek, enc) =
+            envelope_encrypt(&kek, dek_alg, message, aad).expect("Error encrypting envelope");
+        let dec = envelope_decrypt(&kek, dek_alg, wrapped_dek.as_ref(), &enc, enc.nonce(), aad)
+            .expect("Error decrypting envelope");
+        assert_eq!(&dec[..], message);
+
+        assert!(
+            envelope_decrypt(&kek, dek_alg, wrapped_dek.as_ref(), &enc, enc.nonce(), b"").is_err()
+        );
+    }
+
+    // the data encryption key must be an AEAD key using a nonce
+    assert!(envelope_encrypt(&kek, KeyAlg::Aes(AesTypes::A128Kw), message, aad).is_err());
+    assert!(envelope_encrypt(&kek, KeyAlg::Ed25519, message, aad).is_err());
+
+    // the key encryption key must be an AES key wrap key
+    let kek = LocalKey::generate_with_rng(KeyAlg::Aes(AesTypes::A256Gcm), true)
+        .expect(ERR_CREATE_KEYPAIR);
+    assert!(envelope_encrypt(&kek, KeyAlg::Aes(AesTypes::A256Gcm), message, aad).is_err());
+}
+
+#[test]
+pub fn verify_signature_any_type() {
+    let keypair = LocalKey::generate_with_rng(KeyAlg::EcCurve(EcCurves::Secp256r1), true)
+        .expect(ERR_CREATE_KEYPAIR);
+    let message = b"message";
+
+    let sig = keypair.sign_message(message, None).expect(ERR_SIGN);
+    assert_eq!(
+        keypair
+            .verify_signature_any(message, &sig)
+            .expect(ERR_VERIFY),
+        Some(SignatureType::ES256)
+    );
+
+    let hashed = [7u8; 32];
+    let sig = keypair
+        .sign_message(&hashed, Some("ES256ph"))
+        .expect(ERR_SIGN);
+    assert_eq!(
+        keypair
+            .verify_signature_any(&hashed, &sig)
+            .expect(ERR_VERIFY),
+        Some(SignatureType::ES256ph)
+    );
+
+    assert_eq!(
+        keypair
+            .verify_signature_any(b"bad input", &sig)
+            .expect(ERR_VERIFY),
+        None
+    );
+}
+
+#[test]
+pub fn compare_key_material() {
+    let key = LocalKey::generate_with_rng(KeyAlg::Ed25519, true).expect(ERR_CREATE_KEYPAIR);
+    let jwk = key.to_jwk_secret().expect("Error exporting key");
+    let copy = LocalKey::from_jwk_slice(&jwk).expect("Error importing key");
+    let other = LocalKey::generate_with_rng(KeyAlg::Ed25519, true).expect(ERR_CREATE_KEYPAIR);
+    assert!(key.secret_eq(&copy));
+    assert!(key.public_eq(&copy));
+    assert!(!key.secret_eq(&other));
+    assert!(!key.public_eq(&other));
+}
+
+#[test]
+pub fn encrypted_jwk_round_trip() {
+    let key = LocalKey::generate_with_rng(KeyAlg::EcCurve(EcCurves::Secp256r1), true)
+        .expect(ERR_CREATE_KEYPAIR);
+    let jwe = key
+        .to_jwk_encrypted("password")
+        .expect("Error encrypting JWK");
+    let loaded = LocalKey::from_jwk_encrypted(&jwe, "password").expect("Error decrypting JWK");
+    assert!(key.secret_eq(&loaded));
+    assert!(LocalKey::from_jwk_encrypted(&jwe, "other password").is_err());
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "blocking")]
+
+use aries_askar::{blocking::Store, entry::TagMode, kms::KeyAlg, StoreKeyMethod};
+
+const ERR_RAW_KEY: &str = "Error creating raw store key";
+const ERR_SESSION: &str = "Error creating store session";
+const ERR_OPEN: &str = "Error opening test store instance";
+const ERR_REQ_ROW: &str = "Row required";
+const ERR_CLOSE: &str = "Error closing test store instance";
+
+#[test]
+fn blocking_insert_fetch() {
+    let pass_key = aries_askar::Store::new_raw_key(None).expect(ERR_RAW_KEY);
+    let db = Store::provision(
+        "sqlite://:memory:",
+        StoreKeyMethod::RawKey,
+        pass_key,
+        None,
+        true,
+    )
+    .expect(ERR_OPEN);
+
+    let mut txn = db.transaction(None).expect(ERR_SESSION);
+    txn.insert("category", "name", b"value", None, None)
+        .expect("Error inserting row");
+    txn.commit().expect("Error committing transaction");
+
+    let mut conn = db.session(None).expect(ERR_SESSION);
+    let found = conn
+        .fetch("category", "name", TagMode::All, false)
+        .expect("Error fetching row")
+        .expect(ERR_REQ_ROW);
+    assert_eq!(found.value, &b"value"[..]);
+    assert_eq!(
+        conn.count(Some("category"), None).expect("Error counting"),
+        1
+    );
+    drop(conn);
+
+    db.close().expect(ERR_CLOSE);
+}
+
+#[test]
+fn blocking_create_key() {
+    let pass_key = aries_askar::Store::new_raw_key(None).expect(ERR_RAW_KEY);
+    let db = Store::provision(
+        "sqlite://:memory:",
+        StoreKeyMethod::RawKey,
+        pass_key,
+        None,
+        true,
+    )
+    .expect(ERR_OPEN);
+
+    let mut conn = db.session(None).expect(ERR_SESSION);
+    let (ident, key) = conn
+        .create_key(KeyAlg::Ed25519, None, None)
+        .expect("Error creating key");
+    let found = conn
+        .fetch_key(&ident, false)
+        .expect("Error fetching key")
+        .expect(ERR_REQ_ROW);
+    assert_eq!(
+        found
+            .load_local_key()
+            .expect("Error loading key")
+            .to_jwk_thumbprint(None)
+            .expect("Error getting thumbprint"),
+        key.to_jwk_thumbprint(None)
+            .expect("Error getting thumbprint")
+    );
+    drop(conn);
+
+    db.close().expect(ERR_CLOSE);
+}
@@ -1,4 +1,5 @@
 use aries_askar::{
+    entry::TagMode,
     future::block_on,
     kms::{KeyAlg, LocalKey},
     Store, StoreKeyMethod,
@@ -68,7 +69,7 @@ fn store_copy() {
         found.load_local_key().expect("Error loading key");
 
         let found = conn
-            .fetch(row_cat, row_name, false)
+            .fetch(row_cat, row_name, TagMode::All, false)
             .await
             .expect("Error loading row");
         assert!(found.is_some());
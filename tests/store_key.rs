@@ -1,11 +1,15 @@
 use aries_askar::{
+    crypto::alg::{Chacha20Types, EcCurves},
+    entry::{EntryTag, TagFilter},
     future::block_on,
     kms::{KeyAlg, LocalKey},
-    Store, StoreKeyMethod,
+    ErrorKind, Store, StoreKeyMethod,
 };
 
 const ERR_RAW_KEY: &str = "Error creating raw store key";
 const ERR_SESSION: &str = "Error creating store session";
+const ERR_TRANSACTION: &str = "Error creating store transaction";
+const ERR_COMMIT: &str = "Error committing transaction";
 const ERR_OPEN: &str = "Error opening test store instance";
 const ERR_REQ_ROW: &str = "Row required";
 const ERR_CLOSE: &str = "Error closing test store instance";
@@ -50,3 +54,421 @@ fn keypair_create_fetch() {
         db.close().await.expect(ERR_CLOSE);
     })
 }
+
+#[test]
+fn keypair_sign_message_with() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let keypair = LocalKey::generate_with_rng(KeyAlg::EcCurve(EcCurves::Secp256r1), false)
+            .expect("Error creating keypair");
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+
+        let key_name = "testkey";
+        conn.insert_key(key_name, &keypair, None, None, None, None)
+            .await
+            .expect("Error inserting key");
+
+        let message = b"message";
+        let sig = conn
+            .sign_message(key_name, message)
+            .await
+            .expect("Error signing message");
+        assert!(keypair
+            .verify_signature(message, &sig, Some("ES256"))
+            .expect("Error verifying signature"));
+
+        let hashed = [1u8; 32];
+        let sig = conn
+            .sign_message_with(key_name, &hashed, "ES256ph")
+            .await
+            .expect("Error signing message");
+        assert!(keypair
+            .verify_signature(&hashed, &sig, Some("ES256ph"))
+            .expect("Error verifying signature"));
+
+        let err = conn
+            .sign_message_with(key_name, message, "EdDSA")
+            .await
+            .expect_err("Expected error");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
+
+#[test]
+fn keypair_scan() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        for (name, alg, status) in [
+            ("ed-active", KeyAlg::Ed25519, "active"),
+            ("ed-revoked", KeyAlg::Ed25519, "revoked"),
+            ("x-active", KeyAlg::X25519, "active"),
+        ] {
+            let keypair = LocalKey::generate_with_rng(alg, false).expect("Error creating keypair");
+            let tags = [
+                EntryTag::Encrypted("status".to_string(), status.to_string()),
+                EntryTag::Plaintext("owner".to_string(), name.to_string()),
+            ];
+            conn.insert_key(name, &keypair, None, None, Some(&tags), None)
+                .await
+                .expect("Error inserting key");
+        }
+        drop(conn);
+
+        let mut scan = db
+            .scan_keys(
+                None,
+                Some(KeyAlg::Ed25519),
+                Some(TagFilter::is_eq("status", "active")),
+                None,
+                None,
+            )
+            .await
+            .expect("Error starting key scan");
+        let found = scan
+            .fetch_next()
+            .await
+            .expect("Error scanning keys")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name(), "ed-active");
+        assert!(scan
+            .fetch_next()
+            .await
+            .expect("Error scanning keys")
+            .is_none());
+
+        // plaintext tags support range and pattern filters
+        let mut scan = db
+            .scan_keys(
+                None,
+                None,
+                Some(TagFilter::all_of(vec![
+                    TagFilter::is_like("~owner", "%-active"),
+                    TagFilter::is_eq("status", "active"),
+                ])),
+                None,
+                None,
+            )
+            .await
+            .expect("Error starting key scan");
+        let mut found = scan
+            .fetch_next()
+            .await
+            .expect("Error scanning keys")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.len(), 2);
+        found.sort_by(|a, b| a.name().cmp(b.name()));
+        assert_eq!(found[0].name(), "ed-active");
+        assert!(found[0].tags_as_slice().contains(&EntryTag::Plaintext(
+            "owner".to_string(),
+            "ed-active".to_string()
+        )));
+        assert_eq!(found[1].name(), "x-active");
+
+        let mut scan = db
+            .scan_keys(None, None, None, Some(1), Some(5))
+            .await
+            .expect("Error starting key scan");
+        let found = scan
+            .fetch_next()
+            .await
+            .expect("Error scanning keys")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.len(), 2);
+
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
+
+#[test]
+fn keypair_import() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let jwk = LocalKey::generate_with_rng(KeyAlg::Ed25519, true)
+            .expect("Error creating keypair")
+            .to_jwk_secret()
+            .expect("Error encoding JWK");
+        let jwk = jwk.as_ref();
+        let keypair = LocalKey::from_jwk_slice(jwk).expect("Error loading JWK");
+        assert!(LocalKey::from_jwk_slice(&jwk[..jwk.len() - 2]).is_err());
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        let ident = conn
+            .import_key(None, &keypair, None, None)
+            .await
+            .expect("Error importing key");
+        assert_eq!(
+            ident,
+            keypair
+                .to_jwk_thumbprint(None)
+                .expect("Error getting thumbprint")
+        );
+        conn.fetch_key(&ident, false)
+            .await
+            .expect("Error fetching key")
+            .expect(ERR_REQ_ROW);
+
+        let reimport = conn
+            .import_key(None, &keypair, None, None)
+            .await
+            .expect("Error re-importing key");
+        assert_eq!(reimport, ident);
+        let keys = conn
+            .fetch_all_keys(None, None, None, None, false)
+            .await
+            .expect("Error fetching keys");
+        assert_eq!(keys.len(), 1);
+
+        let err = conn
+            .import_key(Some(&ident), &keypair, None, None)
+            .await
+            .expect_err("Expected duplicate error");
+        assert_eq!(err.kind(), ErrorKind::Duplicate);
+
+        let symm = LocalKey::generate_with_rng(KeyAlg::Chacha20(Chacha20Types::XC20P), true)
+            .expect("Error creating key");
+        let symm_ident = conn
+            .import_key(None, &symm, None, None)
+            .await
+            .expect("Error importing key");
+        assert_ne!(
+            symm_ident,
+            symm.to_jwk_thumbprint(None)
+                .expect("Error getting thumbprint")
+        );
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
+
+#[test]
+fn key_create_any_alg() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        for alg in [KeyAlg::Ed25519, KeyAlg::Chacha20(Chacha20Types::XC20P)] {
+            let (ident, key) = conn
+                .create_key(alg, Some("meta"), None)
+                .await
+                .expect("Error creating key");
+            assert_eq!(key.algorithm(), alg);
+            let thumbprint = key
+                .to_jwk_thumbprint(None)
+                .expect("Error getting thumbprint");
+            // symmetric keys are not identified by a hash of the secret key
+            assert_eq!(ident == thumbprint, alg == KeyAlg::Ed25519);
+            let found = conn
+                .fetch_key(&ident, false)
+                .await
+                .expect("Error fetching key")
+                .expect(ERR_REQ_ROW);
+            assert_eq!(found.algorithm(), Some(alg.as_str()));
+            assert_eq!(found.metadata(), Some("meta"));
+            let loaded = found.load_local_key().expect("Error loading key");
+            assert_eq!(
+                loaded.to_secret_bytes().expect("Error exporting key"),
+                key.to_secret_bytes().expect("Error exporting key")
+            );
+        }
+
+        // the returned key is usable immediately and matches the stored key
+        let (ident, key) = conn
+            .create_key(KeyAlg::Ed25519, None, None)
+            .await
+            .expect("Error creating key");
+        let sig = key.sign_message(b"message", None).expect("Error signing");
+        let stored_sig = conn
+            .sign_message(&ident, b"message")
+            .await
+            .expect("Error signing with stored key");
+        assert_eq!(sig, stored_sig);
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
+
+#[test]
+fn key_cache_invalidate() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN)
+        .with_key_cache(1);
+
+        let keypair =
+            LocalKey::generate_with_rng(KeyAlg::Ed25519, false).expect("Error creating keypair");
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        conn.insert_key("testkey", &keypair, Some("a"), None, None, None)
+            .await
+            .expect("Error inserting key");
+        conn.insert_key("otherkey", &keypair, Some("other"), None, None, None)
+            .await
+            .expect("Error inserting key");
+
+        for (name, metadata) in [("testkey", "a"), ("otherkey", "other"), ("testkey", "a")] {
+            let found = conn
+                .fetch_key(name, false)
+                .await
+                .expect("Error fetching key")
+                .expect(ERR_REQ_ROW);
+            assert_eq!(found.metadata(), Some(metadata));
+        }
+
+        conn.update_key("testkey", Some("b"), None, None)
+            .await
+            .expect("Error updating key");
+        let found = conn
+            .fetch_key("testkey", false)
+            .await
+            .expect("Error fetching key")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.metadata(), Some("b"));
+        drop(conn);
+
+        let mut txn = db.transaction(None).await.expect(ERR_TRANSACTION);
+        txn.update_key("testkey", Some("c"), None, None)
+            .await
+            .expect("Error updating key");
+        txn.commit().await.expect(ERR_COMMIT);
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        let found = conn
+            .fetch_key("testkey", false)
+            .await
+            .expect("Error fetching key")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.metadata(), Some("c"));
+
+        assert!(conn
+            .remove_key("testkey")
+            .await
+            .expect("Error removing key"));
+        assert!(!conn
+            .remove_key("testkey")
+            .await
+            .expect("Error removing key"));
+        assert!(conn
+            .fetch_key("testkey", false)
+            .await
+            .expect("Error fetching key")
+            .is_none());
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
+
+#[test]
+fn keypair_tombstone() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        let tags = [EntryTag::Plaintext(
+            "purpose".to_string(),
+            "issue".to_string(),
+        )];
+        let (ident, key) = conn
+            .create_key(KeyAlg::Ed25519, Some("meta"), Some(&tags[..]))
+            .await
+            .expect("Error creating key");
+        let sig = key.sign_message(b"message", None).expect("Error signing");
+
+        conn.tombstone_key(&ident)
+            .await
+            .expect("Error tombstoning key");
+        let found = conn
+            .fetch_key(&ident, false)
+            .await
+            .expect("Error fetching key")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(found.metadata(), Some("meta"));
+        assert_eq!(found.tags_as_slice(), &tags[..]);
+        let public = found.load_local_key().expect("Error loading key");
+        assert!(public
+            .verify_signature(b"message", &sig, None)
+            .expect("Error verifying signature"));
+        let err = conn
+            .sign_message(&ident, b"message")
+            .await
+            .expect_err("Expected signing error");
+        assert_eq!(err.kind(), ErrorKind::Input);
+
+        // symmetric keys have no public key to retain
+        let (ident, _) = conn
+            .create_key(KeyAlg::Chacha20(Chacha20Types::XC20P), None, None)
+            .await
+            .expect("Error creating key");
+        let err = conn
+            .tombstone_key(&ident)
+            .await
+            .expect_err("Expected tombstone error");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
@@ -0,0 +1,39 @@
+use aries_askar::{future::block_on, Store, StoreKeyMethod};
+
+const ERR_RAW_KEY: &str = "Error creating raw store key";
+const ERR_OPEN: &str = "Error opening test store instance";
+const ERR_CLOSE: &str = "Error closing test store instance";
+
+#[test]
+fn store_current_schema() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let version = db
+            .schema_version()
+            .await
+            .expect("Error fetching schema version");
+        assert!(!db
+            .needs_migration()
+            .await
+            .expect("Error checking for migration"));
+        db.migrate().await.expect("Error migrating store");
+        assert_eq!(
+            db.schema_version()
+                .await
+                .expect("Error fetching schema version"),
+            version
+        );
+
+        db.close().await.expect(ERR_CLOSE);
+    })
+}
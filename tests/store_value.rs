@@ -0,0 +1,56 @@
+use aries_askar::{future::block_on, Store, StoreKeyMethod, ValueStream};
+
+const ERR_RAW_KEY: &str = "Error creating raw store key";
+const ERR_SESSION: &str = "Error creating store session";
+const ERR_OPEN: &str = "Error opening test store instance";
+const ERR_REQ_ROW: &str = "Row required";
+const ERR_CLOSE: &str = "Error closing test store instance";
+
+#[test]
+fn fetch_value_stream() {
+    block_on(async {
+        let pass_key = Store::new_raw_key(None).expect(ERR_RAW_KEY);
+        let db = Store::provision(
+            "sqlite://:memory:",
+            StoreKeyMethod::RawKey,
+            pass_key,
+            None,
+            true,
+        )
+        .await
+        .expect(ERR_OPEN);
+
+        let mut conn = db.session(None).await.expect(ERR_SESSION);
+        let value = (0..ValueStream::CHUNK_SIZE * 2 + 10)
+            .map(|i| i as u8)
+            .collect::<Vec<u8>>();
+        conn.insert("category", "name", &value, None, None)
+            .await
+            .expect("Error inserting row");
+
+        let mut stream = conn
+            .fetch_value_stream("category", "name")
+            .await
+            .expect("Error fetching value stream")
+            .expect(ERR_REQ_ROW);
+        assert_eq!(stream.len(), value.len());
+        let mut chunks = 0;
+        let mut found = Vec::new();
+        while let Some(chunk) = stream.fetch_next().await.expect("Error reading chunk") {
+            assert!(chunk.len() <= ValueStream::CHUNK_SIZE);
+            found.extend_from_slice(&chunk);
+            chunks += 1;
+        }
+        assert_eq!(chunks, 3);
+        assert_eq!(found, value);
+
+        assert!(conn
+            .fetch_value_stream("category", "missing")
+            .await
+            .expect("Error fetching value stream")
+            .is_none());
+
+        drop(conn);
+        db.close().await.expect(ERR_CLOSE);
+    });
+}
@@ -386,15 +386,20 @@ async def session_count(
 
 
 async def session_fetch(
-    handle: SessionHandle, category: str, name: str, for_update: bool = False
+    handle: SessionHandle,
+    category: str,
+    name: str,
+    tag_names: Optional[Sequence[str]] = None,
+    for_update: bool = False,
 ) -> EntryListHandle:
     """Fetch a row from the Store."""
     return await invoke_async(
         "askar_session_fetch",
-        (SessionHandle, FfiStr, FfiStr, c_int8),
+        (SessionHandle, FfiStr, FfiStr, FfiJson, c_int8),
         handle,
         category,
         name,
+        list(tag_names) if tag_names is not None else None,
         for_update,
         return_type=EntryListHandle,
     )
@@ -404,6 +409,7 @@ async def session_fetch_all(
     handle: SessionHandle,
     category: Optional[str] = None,
     tag_filter: Optional[Union[str, dict]] = None,
+    tag_names: Optional[Sequence[str]] = None,
     limit: Optional[int] = None,
     order_by: Optional[str] = None,
     descending: bool = False,
@@ -412,10 +418,11 @@ async def session_fetch_all(
     """Fetch all matching rows in the Store."""
     return await invoke_async(
         "askar_session_fetch_all",
-        (SessionHandle, FfiStr, FfiJson, c_int64, FfiStr, c_int8, c_int8),
+        (SessionHandle, FfiStr, FfiJson, FfiJson, c_int64, FfiStr, c_int8, c_int8),
         handle,
         category,
         tag_filter,
+        list(tag_names) if tag_names is not None else None,
         limit if limit is not None else -1,
         order_by,
         descending,
@@ -554,6 +561,7 @@ async def scan_start(
     profile: Optional[str],
     category: Optional[str] = None,
     tag_filter: Optional[Union[str, dict]] = None,
+    tag_names: Optional[Sequence[str]] = None,
     offset: Optional[int] = None,
     limit: Optional[int] = None,
     order_by: Optional[str] = None,
@@ -562,11 +570,22 @@ async def scan_start(
     """Create a new Scan against the Store."""
     return await invoke_async(
         "askar_scan_start",
-        (StoreHandle, FfiStr, FfiStr, FfiJson, c_int64, c_int64, FfiStr, c_int8),
+        (
+            StoreHandle,
+            FfiStr,
+            FfiStr,
+            FfiJson,
+            FfiJson,
+            c_int64,
+            c_int64,
+            FfiStr,
+            c_int8,
+        ),
         handle,
         profile,
         category,
         tag_filter,
+        list(tag_names) if tag_names is not None else None,
         offset or 0,
         limit if limit is not None else -1,
         order_by,
@@ -571,7 +571,7 @@ class FfiJson:
     def from_param(cls, value):
         if isinstance(value, FfiStr):
             return value
-        if isinstance(value, dict):
+        if isinstance(value, (dict, list)):
             value = json.dumps(value)
         return FfiStr(value)
 
@@ -250,6 +250,7 @@ class Scan:
         limit: int = None,
         order_by: Optional[str] = None,
         descending: bool = False,
+        tag_names: Optional[Sequence[str]] = None,
     ):
         """Initialize the Scan instance."""
         self._params = (
@@ -257,6 +258,7 @@ class Scan:
             profile,
             category,
             tag_filter,
+            tag_names,
             offset,
             limit,
             order_by,
@@ -278,6 +280,7 @@ class Scan:
                 profile,
                 category,
                 tag_filter,
+                tag_names,
                 offset,
                 limit,
                 order_by,
@@ -293,6 +296,7 @@ class Scan:
                 profile,
                 category,
                 tag_filter,
+                tag_names,
                 offset,
                 limit,
                 order_by,
@@ -479,10 +483,22 @@ class Store:
         profile: str = None,
         order_by: Optional[str] = None,
         descending: bool = False,
+        tag_names: Optional[Sequence[str]] = None,
     ) -> Scan:
-        """Start a new record scan."""
+        """Start a new record scan.
+
+        If `tag_names` is provided, only the tags with these names are loaded.
+        """
         return Scan(
-            self, profile, category, tag_filter, offset, limit, order_by, descending
+            self,
+            profile,
+            category,
+            tag_filter,
+            offset,
+            limit,
+            order_by,
+            descending,
+            tag_names,
         )
 
     def session(self, profile: str = None) -> "OpenSession":
@@ -562,13 +578,21 @@ class Session:
         return await bindings.session_count(self._handle, category, tag_filter)
 
     async def fetch(
-        self, category: str, name: str, *, for_update: bool = False
+        self,
+        category: str,
+        name: str,
+        *,
+        tag_names: Optional[Sequence[str]] = None,
+        for_update: bool = False,
     ) -> Optional[Entry]:
-        """Fetch a record from the store by category and name."""
+        """Fetch a record from the store by category and name.
+
+        If `tag_names` is provided, only the tags with these names are loaded.
+        """
         if not self._handle:
             raise AskarError(AskarErrorCode.WRAPPER, "Cannot fetch from closed session")
         result_handle = await bindings.session_fetch(
-            self._handle, category, name, for_update
+            self._handle, category, name, tag_names, for_update
         )
         return next(iter(EntryList(result_handle, 1)), None) if result_handle else None
 
@@ -578,11 +602,15 @@ class Session:
         tag_filter: Union[str, dict] = None,
         limit: int = None,
         *,
+        tag_names: Optional[Sequence[str]] = None,
         order_by: Optional[str] = None,
         descending: bool = False,
         for_update: bool = False,
     ) -> EntryList:
-        """Fetch all records matching a category and tag filter."""
+        """Fetch all records matching a category and tag filter.
+
+        If `tag_names` is provided, only the tags with these names are loaded.
+        """
         if not self._handle:
             raise AskarError(AskarErrorCode.WRAPPER, "Cannot fetch from closed session")
         return EntryList(
@@ -590,6 +618,7 @@ class Session:
                 self._handle,
                 category,
                 tag_filter,
+                tag_names,
                 limit,
                 order_by,
                 descending,
@@ -47,6 +47,7 @@ class KeyBackend(Enum):
 
 class SeedMethod(Enum):
     BlsKeyGen = "bls_keygen"
+    BlsKeyGenDraft2 = "bls_keygen_draft2"
 
     @classmethod
     def from_seed_method(cls, method: str) -> Optional["SeedMethod"]:
@@ -64,6 +64,14 @@ async def test_insert_update(store: Store):
         )
         assert len(found) == 1 and dict(found[0]) == TEST_ENTRY
 
+        # Fetch an entry, loading only the selected tags
+        found = await session.fetch(
+            TEST_ENTRY["category"], TEST_ENTRY["name"], tag_names=["enctag"]
+        )
+        assert found.tags == {"enctag": TEST_ENTRY["tags"]["enctag"]}
+        found = await session.fetch_all(TEST_ENTRY["category"], tag_names=[])
+        assert len(found) == 1 and found[0].tags == {}
+
         # Update an entry (outside of a transaction)
         upd_entry = TEST_ENTRY.copy()
         upd_entry["value"] = b"new_value"